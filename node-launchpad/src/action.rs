@@ -7,8 +7,9 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::{
+    components::beta_programme::BetaProgrammeSession,
     mode::{InputMode, Scene},
-    node_stats::NodeStats,
+    node_stats::{NodeStats, NodeStatusSummary},
 };
 use serde::{Deserialize, Serialize};
 use strum::Display;
@@ -22,6 +23,9 @@ pub enum Action {
 
     StoreDiscordUserName(String),
     StoreNodesToStart(usize),
+    StoreBetaProgrammeSession(Option<BetaProgrammeSession>),
+
+    OpenUrl(String),
 
     Tick,
     Render,
@@ -46,6 +50,7 @@ pub enum HomeActions {
     ErrorWhileRunningNatDetection,
 
     NodesStatsObtained(NodeStats),
+    NodeStatusSummariesObtained(Vec<NodeStatusSummary>),
 
     TriggerBetaProgramme,
     TriggerManageNodes,
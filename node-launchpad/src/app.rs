@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, process::Stdio};
 
 use crate::{
     action::Action,
@@ -55,7 +55,10 @@ impl App {
         )
         .await?;
         let config = Config::new()?;
-        let discord_username_input = BetaProgramme::new(app_data.discord_username.clone());
+        let mut discord_username_input = BetaProgramme::new(app_data.discord_username.clone());
+        if let Some(session) = app_data.beta_programme_session.clone() {
+            discord_username_input.restore(session);
+        }
         let manage_nodes = ManageNodes::new(app_data.nodes_to_start)?;
         let footer = Footer::new(app_data.nodes_to_start > 0);
         let help = HelpPopUp::default();
@@ -97,6 +100,11 @@ impl App {
             component.init(tui.size()?)?;
         }
 
+        if self.app_data.beta_programme_session.is_some() {
+            debug!("Resuming the Beta Programme entry from the previous session");
+            action_tx.send(Action::SwitchScene(Scene::BetaProgramme))?;
+        }
+
         loop {
             if let Some(e) = tui.next().await {
                 match e {
@@ -193,6 +201,16 @@ impl App {
                         self.app_data.nodes_to_start = count;
                         self.app_data.save()?;
                     }
+                    Action::StoreBetaProgrammeSession(ref session) => {
+                        self.app_data.beta_programme_session.clone_from(session);
+                        self.app_data.save()?;
+                    }
+                    Action::OpenUrl(ref url) => {
+                        debug!("Opening url: {url:?}");
+                        if let Err(err) = open_url(url) {
+                            error!("Failed to open {url:?}: {err:?}");
+                        }
+                    }
                     _ => {}
                 }
                 for component in self.components.iter_mut() {
@@ -218,3 +236,26 @@ impl App {
         Ok(())
     }
 }
+
+/// Open the url with the platform's default handler, e.g., the user's browser.
+fn open_url(url: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    // The TUI owns the terminal, so the opener must not write to it.
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
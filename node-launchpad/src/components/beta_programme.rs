@@ -16,8 +16,11 @@ use crate::{
 use color_eyre::Result;
 use crossterm::event::{Event, KeyCode, KeyEvent};
 use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
 use tui_input::{backend::crossterm::EventHandler, Input};
 
+const TERMS_URL: &str = "https://autonomi.com/beta/terms";
+
 pub struct BetaProgramme {
     /// Whether the component is active right now, capturing keystrokes + draw things.
     active: bool,
@@ -25,15 +28,31 @@ pub struct BetaProgramme {
     discord_input_filed: Input,
     // cache the old value incase user presses Esc.
     old_value: String,
+    /// Whether the middle of the saved value is hidden, for screen sharing.
+    masked: bool,
+    /// The scene to return to when the pop up is closed.
+    back_to: Scene,
 }
 
-enum BetaProgrammeState {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BetaProgrammeState {
     DiscordIdAlreadySet,
     ShowTCs,
     RejectTCs,
     AcceptTCsAndEnterDiscordId,
 }
 
+/// An in-progress entry in the pop up, persisted so it can be resumed if the launchpad is
+/// restarted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BetaProgrammeSession {
+    /// The username as entered so far.
+    pub value: String,
+    pub state_tag: BetaProgrammeState,
+    /// The scene to return to when the pop up is closed.
+    pub back_to: Scene,
+}
+
 impl BetaProgramme {
     pub fn new(username: String) -> Self {
         let state = if username.is_empty() {
@@ -46,7 +65,37 @@ impl BetaProgramme {
             state,
             discord_input_filed: Input::default().with_value(username),
             old_value: Default::default(),
+            masked: false,
+            back_to: Scene::Home,
+        }
+    }
+
+    /// The session to persist, if any.
+    ///
+    /// There is nothing to persist while the pop up is closed. The rejection of the T&Cs is only
+    /// a confirmation, so it's never persisted and the T&Cs are shown again after a restart.
+    pub fn session(&self) -> Option<BetaProgrammeSession> {
+        if !self.active || self.state == BetaProgrammeState::RejectTCs {
+            return None;
         }
+        Some(BetaProgrammeSession {
+            value: self.discord_input_filed.value().to_string(),
+            state_tag: self.state,
+            back_to: self.back_to,
+        })
+    }
+
+    /// Re-open the pop up in the state a persisted session left it in. Cancelling the entry
+    /// still restores the saved username.
+    pub fn restore(&mut self, session: BetaProgrammeSession) {
+        self.old_value = self.discord_input_filed.value().to_string();
+        self.discord_input_filed = Input::default().with_value(session.value);
+        self.state = match session.state_tag {
+            BetaProgrammeState::RejectTCs => BetaProgrammeState::ShowTCs,
+            state => state,
+        };
+        self.back_to = session.back_to;
+        self.active = true;
     }
 
     fn capture_inputs(&mut self, key: KeyEvent) -> Vec<Action> {
@@ -64,7 +113,7 @@ impl BetaProgramme {
                 self.state = BetaProgrammeState::DiscordIdAlreadySet;
                 vec![
                     Action::StoreDiscordUserName(self.discord_input_filed.value().to_string()),
-                    Action::SwitchScene(Scene::Home),
+                    Action::SwitchScene(self.back_to),
                 ]
             }
             KeyCode::Esc => {
@@ -77,15 +126,21 @@ impl BetaProgramme {
                     .discord_input_filed
                     .clone()
                     .with_value(self.old_value.clone());
-                vec![Action::SwitchScene(Scene::Home)]
+                vec![Action::SwitchScene(self.back_to)]
             }
             KeyCode::Char(' ') => vec![],
+            KeyCode::Tab => {
+                self.masked = !self.masked;
+                vec![]
+            }
             KeyCode::Backspace => {
                 // if max limit reached, we should allow Backspace to work.
                 self.discord_input_filed.handle_event(&Event::Key(key));
+                self.masked = false;
                 vec![]
             }
             _ => {
+                self.masked = false;
                 // max 32 limit as per discord docs
                 if self.discord_input_filed.value().chars().count() < 32 {
                     self.discord_input_filed.handle_event(&Event::Key(key));
@@ -97,13 +152,29 @@ impl BetaProgramme {
     }
 }
 
+/// Hide the middle of a value, keeping enough of each end to recognise it, e.g.,
+/// `0x03B7...B124`. Values too short to hide anything are returned unchanged.
+fn mask_middle(value: &str) -> String {
+    const PREFIX_LEN: usize = 6;
+    const SUFFIX_LEN: usize = 4;
+
+    let chars = value.chars().collect::<Vec<_>>();
+    if chars.len() <= PREFIX_LEN + SUFFIX_LEN {
+        return value.to_string();
+    }
+    let prefix = chars[..PREFIX_LEN].iter().collect::<String>();
+    let suffix = chars[chars.len() - SUFFIX_LEN..].iter().collect::<String>();
+    format!("{prefix}...{suffix}")
+}
+
 impl Component for BetaProgramme {
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Vec<Action>> {
         if !self.active {
             return Ok(vec![]);
         }
+        let session_before = self.session();
         // while in entry mode, keybinds are not captured, so gotta exit entry mode from here
-        let send_back = match &self.state {
+        let mut send_back = match &self.state {
             BetaProgrammeState::DiscordIdAlreadySet => self.capture_inputs(key),
             BetaProgrammeState::ShowTCs => {
                 match key.code {
@@ -117,6 +188,12 @@ impl Component for BetaProgramme {
                             self.state = BetaProgrammeState::AcceptTCsAndEnterDiscordId;
                         }
                     }
+                    KeyCode::Char('o') | KeyCode::Char('O') => {
+                        // Not every terminal can follow the hyperlink, so allow it to be opened
+                        // from the keyboard.
+                        debug!("User asked to open the TCs link");
+                        return Ok(vec![Action::OpenUrl(TERMS_URL.to_string())]);
+                    }
                     KeyCode::Esc => {
                         debug!("User rejected the TCs, moving to RejectTCs");
                         self.state = BetaProgrammeState::RejectTCs;
@@ -130,10 +207,19 @@ impl Component for BetaProgramme {
                     debug!("RejectTCs msg closed. Switching to home scene.");
                     self.state = BetaProgrammeState::ShowTCs;
                 }
-                vec![Action::SwitchScene(Scene::Home)]
+                vec![Action::SwitchScene(self.back_to)]
             }
             BetaProgrammeState::AcceptTCsAndEnterDiscordId => self.capture_inputs(key),
         };
+
+        // the session is cleared when the pop up is closed, so only store it while it stays open
+        let is_closing = send_back
+            .iter()
+            .any(|action| matches!(action, Action::SwitchScene(_)));
+        let session = self.session();
+        if !is_closing && session != session_before {
+            send_back.push(Action::StoreBetaProgrammeSession(session));
+        }
         Ok(send_back)
     }
 
@@ -141,15 +227,18 @@ impl Component for BetaProgramme {
         let send_back = match action {
             Action::SwitchScene(scene) => match scene {
                 Scene::BetaProgramme => {
-                    self.active = true;
-                    self.old_value = self.discord_input_filed.value().to_string();
+                    // a restored session is already active, with the saved value to go back to
+                    if !self.active {
+                        self.active = true;
+                        self.old_value = self.discord_input_filed.value().to_string();
+                    }
                     // set to entry input mode as we want to handle everything within our handle_key_events
                     // so by default if this scene is active, we capture inputs.
                     Some(Action::SwitchInputMode(InputMode::Entry))
                 }
                 _ => {
-                    self.active = false;
-                    None
+                    let was_active = std::mem::replace(&mut self.active, false);
+                    was_active.then_some(Action::StoreBetaProgrammeSession(None))
                 }
             },
             _ => None,
@@ -214,19 +303,20 @@ impl Component for BetaProgramme {
 
                 f.render_widget(prompt, layer_two[0]);
 
-                let input = Paragraph::new(self.discord_input_filed.value())
+                let value = if self.masked {
+                    mask_middle(self.discord_input_filed.value())
+                } else {
+                    self.discord_input_filed.value().to_string()
+                };
+                let input = Paragraph::new(value.as_str())
                     .alignment(Alignment::Center)
                     .fg(VIVID_SKY_BLUE);
                 f.set_cursor(
                     // Put cursor past the end of the input text
                     layer_two[1].x
                         + (layer_two[1].width / 2) as u16
-                        + (self.discord_input_filed.value().len() / 2) as u16
-                        + if self.discord_input_filed.value().len() % 2 != 0 {
-                            1
-                        } else {
-                            0
-                        },
+                        + (value.len() / 2) as u16
+                        + if value.len() % 2 != 0 { 1 } else { 0 },
                     layer_two[1].y,
                 );
                 f.render_widget(input, layer_two[1]);
@@ -239,6 +329,19 @@ impl Component for BetaProgramme {
                     .border_style(Style::new().fg(GHOST_WHITE));
                 f.render_widget(dash, layer_two[3]);
 
+                let mask_hint = if self.masked {
+                    "  Show the full value [Tab]"
+                } else {
+                    "  Hide the middle of the value [Tab]"
+                };
+                f.render_widget(
+                    Paragraph::new(mask_hint).fg(LIGHT_PERIWINKLE),
+                    Rect {
+                        height: 1,
+                        ..layer_two[3]
+                    },
+                );
+
                 let buttons_layer = Layout::horizontal(vec![
                     Constraint::Percentage(55),
                     Constraint::Percentage(45),
@@ -271,8 +374,10 @@ impl Component for BetaProgramme {
                         Constraint::Length(6),
                         // for the hypertext
                         Constraint::Length(1),
+                        // for the open link hint
+                        Constraint::Length(1),
                         // gap
-                        Constraint::Length(5),
+                        Constraint::Length(4),
                         // for the buttons
                         Constraint::Length(1),
                     ],
@@ -283,24 +388,27 @@ impl Component for BetaProgramme {
                 f.render_widget(text.fg(GHOST_WHITE), layer_two[0]);
                 let link = Hyperlink::new(
                     Span::styled(
-                        "  https://autonomi.com/beta/terms",
+                        format!("  {TERMS_URL}"),
                         Style::default().fg(VIVID_SKY_BLUE),
                     ),
-                    "https://autonomi.com/beta/terms",
+                    TERMS_URL,
                 );
 
                 f.render_widget_ref(link, layer_two[1]);
 
+                let hint = Paragraph::new("  Open the link in your browser [O]");
+                f.render_widget(hint.fg(LIGHT_PERIWINKLE), layer_two[2]);
+
                 let dash = Block::new()
                     .borders(Borders::BOTTOM)
                     .border_style(Style::new().fg(GHOST_WHITE));
-                f.render_widget(dash, layer_two[2]);
+                f.render_widget(dash, layer_two[3]);
 
                 let buttons_layer = Layout::horizontal(vec![
                     Constraint::Percentage(45),
                     Constraint::Percentage(55),
                 ])
-                .split(layer_two[3]);
+                .split(layer_two[4]);
 
                 let button_no = Line::from(vec![Span::styled(
                     "  No, Cancel [Esc]",
@@ -419,3 +527,91 @@ impl Component for BetaProgramme {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn the_o_key_should_open_the_terms_link_without_changing_state() -> Result<()> {
+        let mut beta_programme = BetaProgramme::new(String::new());
+        beta_programme.update(Action::SwitchScene(Scene::BetaProgramme))?;
+
+        let actions = beta_programme
+            .handle_key_events(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE))?;
+
+        assert_eq!(actions, vec![Action::OpenUrl(TERMS_URL.to_string())]);
+        assert!(matches!(beta_programme.state, BetaProgrammeState::ShowTCs));
+        Ok(())
+    }
+
+    #[test]
+    fn a_session_should_round_trip_and_resume_the_entry() -> Result<()> {
+        let mut beta_programme = BetaProgramme::new(String::new());
+        beta_programme.update(Action::SwitchScene(Scene::BetaProgramme))?;
+        beta_programme.handle_key_events(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE))?;
+        let actions = beta_programme
+            .handle_key_events(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE))?;
+
+        let session = beta_programme.session().expect("the entry is in progress");
+        assert_eq!(
+            actions,
+            vec![Action::StoreBetaProgrammeSession(Some(session.clone()))]
+        );
+        let restored_session: BetaProgrammeSession =
+            serde_json::from_str(&serde_json::to_string(&session)?)?;
+        assert_eq!(restored_session, session);
+
+        let mut restored = BetaProgramme::new(String::new());
+        restored.restore(restored_session);
+        assert_eq!(restored.discord_input_filed.value(), "a");
+        assert_eq!(
+            restored.state,
+            BetaProgrammeState::AcceptTCsAndEnterDiscordId
+        );
+        assert_eq!(
+            restored.update(Action::SwitchScene(Scene::BetaProgramme))?,
+            Some(Action::SwitchInputMode(InputMode::Entry))
+        );
+        assert_eq!(restored.old_value, "");
+        Ok(())
+    }
+
+    #[test]
+    fn a_session_should_not_be_kept_while_rejecting_the_terms() -> Result<()> {
+        let mut beta_programme = BetaProgramme::new(String::new());
+        beta_programme.update(Action::SwitchScene(Scene::BetaProgramme))?;
+        assert!(beta_programme.session().is_some());
+
+        let actions =
+            beta_programme.handle_key_events(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))?;
+        assert_eq!(actions, vec![Action::StoreBetaProgrammeSession(None)]);
+        assert_eq!(beta_programme.session(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn mask_middle_should_keep_the_prefix_and_suffix() {
+        assert_eq!(
+            mask_middle("0x03B770D9cD32077cC0bF330c13C114a87643B124"),
+            "0x03B7...B124"
+        );
+        assert_eq!(mask_middle("short"), "short");
+    }
+
+    #[test]
+    fn the_tab_key_should_toggle_masking_of_the_saved_value() -> Result<()> {
+        let mut beta_programme =
+            BetaProgramme::new("0x03B770D9cD32077cC0bF330c13C114a87643B124".to_string());
+        beta_programme.update(Action::SwitchScene(Scene::BetaProgramme))?;
+        assert!(!beta_programme.masked);
+
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+        beta_programme.handle_key_events(tab)?;
+        assert!(beta_programme.masked);
+        beta_programme.handle_key_events(tab)?;
+        assert!(!beta_programme.masked);
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use crate::{
     action::{Action, HomeActions},
     config::Config,
     mode::{InputMode, Scene},
-    node_stats::NodeStats,
+    node_stats::{NodeStats, NodeStatusSummary},
     style::{
         clear_area, COOL_GREY, EUCALYPTUS, GHOST_WHITE, LIGHT_PERIWINKLE, VERY_LIGHT_AZURE,
         VIVID_SKY_BLUE,
@@ -50,6 +50,7 @@ pub struct Home {
     error_while_running_nat_detection: usize,
     node_stats: NodeStats,
     node_stats_last_update: Instant,
+    node_status_summaries: Vec<NodeStatusSummary>,
     node_table_state: TableState,
     nodes_to_start: usize,
     discord_username: String,
@@ -85,6 +86,7 @@ impl Home {
             error_while_running_nat_detection: 0,
             node_stats: NodeStats::default(),
             node_stats_last_update: Instant::now(),
+            node_status_summaries: Default::default(),
             nodes_to_start: allocated_disk_space,
             node_table_state: Default::default(),
             lock_registry: None,
@@ -105,12 +107,17 @@ impl Home {
     }
 
     /// Tries to trigger the update of node stats if the last update was more than `NODE_STAT_UPDATE_INTERVAL` ago.
-    /// The result is sent via the HomeActions::NodesStatsObtained action.
+    /// The result is sent via the HomeActions::NodesStatsObtained and
+    /// HomeActions::NodeStatusSummariesObtained actions.
     fn try_update_node_stats(&mut self, force_update: bool) -> Result<()> {
         if self.node_stats_last_update.elapsed() > NODE_STAT_UPDATE_INTERVAL || force_update {
             self.node_stats_last_update = Instant::now();
 
             NodeStats::fetch_all_node_stats(&self.node_services, self.get_actions_sender()?);
+            NodeStatusSummary::fetch_all_node_status_summaries(
+                &self.node_services,
+                self.get_actions_sender()?,
+            );
         }
         Ok(())
     }
@@ -312,6 +319,9 @@ impl Component for Home {
             Action::HomeActions(HomeActions::NodesStatsObtained(stats)) => {
                 self.node_stats = stats;
             }
+            Action::HomeActions(HomeActions::NodeStatusSummariesObtained(summaries)) => {
+                self.node_status_summaries = summaries;
+            }
             Action::HomeActions(HomeActions::StartNodesCompleted)
             | Action::HomeActions(HomeActions::StopNodesCompleted) => {
                 self.lock_registry = None;
@@ -488,8 +498,16 @@ impl Component for Home {
                 }
                 let peer_id = peer_id.map(|p| p.to_string()).unwrap_or("-".to_string());
                 let status = format!("{:?}", n.status);
-
-                let row = vec![n.service_name.clone(), peer_id, status];
+                // Summaries are only fetched for running nodes.
+                let summary = self
+                    .node_status_summaries
+                    .iter()
+                    .find(|summary| summary.service_name == n.service_name)
+                    .filter(|_| n.status == ServiceStatus::Running)
+                    .map(|summary| summary.to_string())
+                    .unwrap_or("-".to_string());
+
+                let row = vec![n.service_name.clone(), peer_id, status, summary];
                 let row_style = if n.status == ServiceStatus::Running {
                     Style::default().fg(EUCALYPTUS)
                 } else {
@@ -518,6 +536,7 @@ impl Component for Home {
                 Constraint::Max(15),
                 Constraint::Min(30),
                 Constraint::Max(10),
+                Constraint::Max(25),
             ];
             let table = Table::new(node_rows, node_widths)
                 .column_spacing(2)
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{action::Action, mode::Scene};
+use crate::{action::Action, components::beta_programme::BetaProgrammeSession, mode::Scene};
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
@@ -52,6 +52,9 @@ pub async fn configure_winsw() -> Result<()> {
 pub struct AppData {
     pub discord_username: String,
     pub nodes_to_start: usize,
+    /// An entry in the Beta Programme pop up that was in progress when the launchpad exited.
+    #[serde(default)]
+    pub beta_programme_session: Option<BetaProgrammeSession>,
 }
 
 impl AppData {
@@ -9,8 +9,16 @@
 use color_eyre::Result;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sn_service_management::{NodeServiceData, ServiceStatus};
-use std::{path::PathBuf, time::Instant};
+use sn_service_management::{
+    rpc::{RpcActions, RpcClient},
+    NodeServiceData, ServiceStatus,
+};
+use std::{
+    fmt,
+    net::SocketAddr,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::action::{Action, HomeActions};
@@ -135,3 +143,151 @@ impl NodeStats {
         Ok(stats)
     }
 }
+
+/// The uptime and connectivity of a single running node, for the status view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStatusSummary {
+    pub service_name: String,
+    /// The uptime reported by the node, if it could be queried.
+    pub uptime: Option<Duration>,
+    pub connected_peers: Option<usize>,
+}
+
+impl NodeStatusSummary {
+    /// Query the uptime and connected peers of the running nodes through their RPC services, as
+    /// the node manager's status does. The result is sent via the
+    /// HomeActions::NodeStatusSummariesObtained action.
+    pub fn fetch_all_node_status_summaries(
+        nodes: &[NodeServiceData],
+        action_sender: UnboundedSender<Action>,
+    ) {
+        let node_details = nodes
+            .iter()
+            .filter(|node| node.status == ServiceStatus::Running)
+            .map(|node| {
+                (
+                    node.service_name.clone(),
+                    node.rpc_socket_addr,
+                    node.connected_peers.as_ref().map(|peers| peers.len()),
+                )
+            })
+            .collect::<Vec<_>>();
+        if node_details.is_empty() {
+            debug!("No running nodes to fetch status summaries from.");
+            return;
+        }
+        tokio::task::spawn_local(async move {
+            let summaries = futures::stream::iter(node_details)
+                .map(|(service_name, rpc_socket_addr, connected_peers)| {
+                    Self::fetch_node_status_summary(service_name, rpc_socket_addr, connected_peers)
+                })
+                .buffer_unordered(5)
+                .collect::<Vec<_>>()
+                .await;
+            if let Err(err) = action_sender.send(Action::HomeActions(
+                HomeActions::NodeStatusSummariesObtained(summaries),
+            )) {
+                error!("Error while sending action: {err:?}");
+            }
+        });
+    }
+
+    /// A node that can't be queried keeps the connected peers last saved in the registry.
+    async fn fetch_node_status_summary(
+        service_name: String,
+        rpc_socket_addr: SocketAddr,
+        connected_peers: Option<usize>,
+    ) -> Self {
+        let mut rpc_client = RpcClient::from_socket_addr(rpc_socket_addr);
+        rpc_client.set_max_attempts(1);
+        let uptime = match rpc_client.node_info().await {
+            Ok(node_info) => Some(node_info.uptime),
+            Err(err) => {
+                error!("Error while fetching the uptime of {service_name:?}: {err:?}");
+                None
+            }
+        };
+        let connected_peers = match rpc_client.network_info().await {
+            Ok(network_info) => Some(network_info.connected_peers.len()),
+            Err(err) => {
+                error!("Error while fetching the connected peers of {service_name:?}: {err:?}");
+                connected_peers
+            }
+        };
+        Self {
+            service_name,
+            uptime,
+            connected_peers,
+        }
+    }
+}
+
+impl fmt::Display for NodeStatusSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uptime = self
+            .uptime
+            .map(format_uptime)
+            .unwrap_or_else(|| "-".to_string());
+        let connected_peers = self
+            .connected_peers
+            .map(|peers| peers.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        write!(f, "up {uptime}, {connected_peers} peers")
+    }
+}
+
+/// Format an uptime as days, hours and minutes, e.g., `3d 4h 12m`.
+///
+/// Uptimes under a minute are shown in seconds, and leading zero units are dropped.
+pub fn format_uptime(uptime: Duration) -> String {
+    let total_secs = uptime.as_secs();
+    if total_secs < 60 {
+        return format!("{total_secs}s");
+    }
+    let days = total_secs / 86_400;
+    let hours = (total_secs % 86_400) / 3_600;
+    let minutes = (total_secs % 3_600) / 60;
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_uptime_should_format_the_boundaries() {
+        assert_eq!(format_uptime(Duration::from_secs(0)), "0s");
+        assert_eq!(format_uptime(Duration::from_secs(59)), "59s");
+        assert_eq!(format_uptime(Duration::from_secs(60)), "1m");
+        assert_eq!(format_uptime(Duration::from_secs(3_600)), "1h 0m");
+        assert_eq!(format_uptime(Duration::from_secs(86_399)), "23h 59m");
+        assert_eq!(format_uptime(Duration::from_secs(86_400)), "1d 0h 0m");
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 86_400 + 4 * 3_600 + 12 * 60 + 30)),
+            "3d 4h 12m"
+        );
+    }
+
+    #[test]
+    fn node_status_summary_should_display_the_uptime_and_connected_peers() {
+        let summary = NodeStatusSummary {
+            service_name: "safenode1".to_string(),
+            uptime: Some(Duration::from_secs(86_400 + 2 * 3_600 + 5 * 60)),
+            connected_peers: Some(8),
+        };
+        assert_eq!(summary.to_string(), "up 1d 2h 5m, 8 peers");
+
+        let unknown = NodeStatusSummary {
+            service_name: "safenode2".to_string(),
+            uptime: None,
+            connected_peers: None,
+        };
+        assert_eq!(unknown.to_string(), "up -, - peers");
+    }
+}
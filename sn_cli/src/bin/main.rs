@@ -26,7 +26,7 @@ use bls::SecretKey;
 use clap::Parser;
 use color_eyre::Result;
 use indicatif::ProgressBar;
-use sn_client::transfers::bls_secret_from_hex;
+use sn_client::transfers::{bls_secret_from_hex, get_wallet_base_dir};
 use sn_client::{Client, ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver};
 #[cfg(feature = "metrics")]
 use sn_logging::{metrics::init_metrics, Level, LogBuilder, LogFormat};
@@ -226,8 +226,7 @@ fn get_client_secret_key(root_dir: &PathBuf) -> Result<SecretKey> {
 }
 
 fn get_client_data_dir_path() -> Result<PathBuf> {
-    let mut home_dirs = dirs_next::data_dir().expect("Data directory is obtainable");
-    home_dirs.push("safe");
+    let mut home_dirs = get_wallet_base_dir();
     home_dirs.push("client");
     std::fs::create_dir_all(home_dirs.as_path())?;
     Ok(home_dirs)
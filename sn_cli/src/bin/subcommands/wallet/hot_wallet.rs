@@ -39,6 +39,9 @@ pub enum WalletCmds {
         /// it's not the source of the entropy for the mnemonic generation.
         /// The mnemonic+passphrase will be the seed. See detail at
         /// `<https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki#from-mnemonic-to-seed>`
+        ///
+        /// Different passphrases yield different wallets from the same mnemonic, so the same
+        /// passphrase has to be used again to restore the wallet.
         passphrase: Option<String>,
     },
     /// Print the wallet balance.
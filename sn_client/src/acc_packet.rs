@@ -6,20 +6,28 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::path::Path;
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use super::error::Result;
-use sn_transfers::{get_faucet_data_dir, HotWallet};
+use sn_transfers::{get_faucet_data_dir, sanity_check_key, HotWallet, MainPubkey, WALLET_DIR_NAME};
 
 pub mod user_secret;
 
 const DEFAULT_WALLET_DERIVIATION_PASSPHRASE: &str = "default";
 
 /// Load a account from disk, with wallet, or create a new one using the mnemonic system
+///
+/// When a new wallet is created, its key is derived from the mnemonic and the given passphrase,
+/// which defaults to `"default"`. Different passphrases yield different wallets from the same
+/// mnemonic.
 pub fn load_account_wallet_or_create_with_mnemonic(
     root_dir: &Path,
     derivation_passphrase: Option<&str>,
 ) -> Result<HotWallet> {
+    user_secret::check_account_derivation_paths()?;
     let wallet = HotWallet::load_from(root_dir);
     match wallet {
         Ok(wallet) => Ok(wallet),
@@ -46,11 +54,85 @@ pub fn load_account_wallet_or_create_with_mnemonic(
             let passphrase = derivation_passphrase.unwrap_or(DEFAULT_WALLET_DERIVIATION_PASSPHRASE);
 
             let wallet = user_secret::account_wallet_secret_key(mnemonic, passphrase)?;
+            sanity_check_key(&wallet)?;
             Ok(HotWallet::create_from_key(root_dir, wallet)?)
         }
     }
 }
 
+/// Whether the wallet on disk holds the key derived from the account's mnemonic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsistencyReport {
+    Consistent,
+    /// The wallet holds a different key, e.g., after a restore went wrong.
+    ///
+    /// `rederive_wallet_from_mnemonic` replaces the wallet with one for the derived key.
+    Mismatch {
+        wallet_pubkey: MainPubkey,
+        derived_pubkey: MainPubkey,
+    },
+}
+
+/// Check the wallet in `root_dir` holds the key derived from the mnemonic stored alongside it.
+///
+/// The passphrase must be the one the wallet was created with, which defaults to `"default"`.
+pub fn check_wallet_consistency(
+    root_dir: &Path,
+    derivation_passphrase: Option<&str>,
+) -> Result<ConsistencyReport> {
+    let mnemonic = user_secret::read_mnemonic_from_disk(root_dir)?;
+    let passphrase = derivation_passphrase.unwrap_or(DEFAULT_WALLET_DERIVIATION_PASSPHRASE);
+    let derived_pubkey =
+        user_secret::account_wallet_secret_key(mnemonic, passphrase)?.main_pubkey();
+    let wallet_pubkey = HotWallet::try_load_from(root_dir)?.address();
+
+    if wallet_pubkey == derived_pubkey {
+        Ok(ConsistencyReport::Consistent)
+    } else {
+        warn!("The wallet key {wallet_pubkey:?} in {root_dir:?} was not derived from the mnemonic, which derives {derived_pubkey:?}");
+        Ok(ConsistencyReport::Mismatch {
+            wallet_pubkey,
+            derived_pubkey,
+        })
+    }
+}
+
+/// Replace the wallet in `root_dir` with a new one for the key derived from the mnemonic.
+///
+/// A wallet that already holds the derived key is left as it is. Otherwise, the existing wallet
+/// directory is kept, renamed with a `.mismatched-<timestamp>` suffix, so any cash notes held for
+/// its key are not lost, even across repeated repairs.
+pub fn rederive_wallet_from_mnemonic(
+    root_dir: &Path,
+    derivation_passphrase: Option<&str>,
+) -> Result<HotWallet> {
+    let mnemonic = user_secret::read_mnemonic_from_disk(root_dir)?;
+    let passphrase = derivation_passphrase.unwrap_or(DEFAULT_WALLET_DERIVIATION_PASSPHRASE);
+    let wallet = user_secret::account_wallet_secret_key(mnemonic, passphrase)?;
+    sanity_check_key(&wallet)?;
+
+    let wallet_dir = root_dir.join(WALLET_DIR_NAME);
+    if wallet_dir.exists() {
+        if matches!(
+            check_wallet_consistency(root_dir, derivation_passphrase),
+            Ok(ConsistencyReport::Consistent)
+        ) {
+            info!("The wallet in {root_dir:?} already holds the key derived from the mnemonic");
+            return Ok(HotWallet::load_from(root_dir)?);
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let backup_dir = root_dir.join(format!("{WALLET_DIR_NAME}.mismatched-{timestamp}"));
+        info!("Moving the mismatched wallet {wallet_dir:?} to {backup_dir:?}");
+        std::fs::rename(&wallet_dir, backup_dir)?;
+    }
+
+    Ok(HotWallet::create_from_key(root_dir, wallet)?)
+}
+
 pub fn create_faucet_account_and_wallet() -> HotWallet {
     let root_dir = get_faucet_data_dir();
 
@@ -58,3 +140,75 @@ pub fn create_faucet_account_and_wallet() -> HotWallet {
     load_account_wallet_or_create_with_mnemonic(&root_dir, None)
         .expect("Faucet wallet shall be created successfully.")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_transfers::MainSecretKey;
+
+    #[test]
+    fn check_wallet_consistency_should_detect_a_wallet_not_derived_from_the_mnemonic() -> Result<()>
+    {
+        let dir = tempfile::tempdir()?;
+        let wallet = load_account_wallet_or_create_with_mnemonic(dir.path(), None)?;
+        assert_eq!(
+            check_wallet_consistency(dir.path(), None)?,
+            ConsistencyReport::Consistent
+        );
+
+        // Replace the mnemonic with one for a different account.
+        let other_mnemonic = user_secret::mnemonic_from_seed_bytes([9u8; 32])?;
+        user_secret::write_mnemonic_to_disk(dir.path(), &other_mnemonic)?;
+        let derived_pubkey =
+            user_secret::account_wallet_secret_key(other_mnemonic, "default")?.main_pubkey();
+        assert_eq!(
+            check_wallet_consistency(dir.path(), None)?,
+            ConsistencyReport::Mismatch {
+                wallet_pubkey: wallet.address(),
+                derived_pubkey,
+            }
+        );
+
+        let repaired = rederive_wallet_from_mnemonic(dir.path(), None)?;
+        assert_eq!(repaired.address(), derived_pubkey);
+        assert_eq!(
+            check_wallet_consistency(dir.path(), None)?,
+            ConsistencyReport::Consistent
+        );
+        assert_eq!(count_wallet_backups(dir.path())?, 1);
+
+        // Repairing a consistent wallet leaves it, and the backups, as they are.
+        let repaired_again = rederive_wallet_from_mnemonic(dir.path(), None)?;
+        assert_eq!(repaired_again.address(), derived_pubkey);
+        assert_eq!(count_wallet_backups(dir.path())?, 1);
+
+        Ok(())
+    }
+
+    fn count_wallet_backups(root_dir: &Path) -> Result<usize> {
+        Ok(std::fs::read_dir(root_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(&format!("{WALLET_DIR_NAME}.mismatched-"))
+            })
+            .count())
+    }
+
+    #[test]
+    fn check_wallet_consistency_should_detect_a_wallet_created_from_another_key() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mnemonic = user_secret::mnemonic_from_seed_bytes([3u8; 32])?;
+        user_secret::write_mnemonic_to_disk(dir.path(), &mnemonic)?;
+        let wallet = HotWallet::create_from_key(dir.path(), MainSecretKey::random())?;
+
+        assert!(matches!(
+            check_wallet_consistency(dir.path(), None)?,
+            ConsistencyReport::Mismatch { wallet_pubkey, .. } if wallet_pubkey == wallet.address()
+        ));
+
+        Ok(())
+    }
+}
@@ -8,13 +8,24 @@
 
 use crate::{
     error::{Error, Result},
-    transfers::MainSecretKey,
+    transfers::{sanity_check_key, MainSecretKey},
 };
 use bls::SecretKey;
+use ctr::cipher::{KeyIvInit, StreamCipher};
 use curv::elliptic::curves::ECScalar;
+use hmac::Hmac;
 use rand::RngCore;
-use std::path::Path;
+use serde::Deserialize;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use thiserror::Error as ThisError;
 use xor_name::XorName;
+use zeroize::Zeroizing;
 
 const MNEMONIC_FILENAME: &str = "account_secret";
 
@@ -22,33 +33,373 @@ const ACCOUNT_ROOT_XORNAME_DERIVATION: &str = "m/1/0";
 
 const ACCOUNT_WALLET_DERIVATION: &str = "m/2/0";
 
+/// The first malformed account derivation path, if any, found by the check done once per process.
+static MALFORMED_DERIVATION_PATH: OnceLock<Option<&'static str>> = OnceLock::new();
+
+/// The most keys a `DerivedKeyCache` keeps by default. A process normally uses one or two
+/// accounts, so this only bounds how many keys stay in memory when many mnemonics are tried, e.g.,
+/// during a restore.
+pub const DEFAULT_MAX_CACHED_KEYS: usize = 32;
+
+/// The entropy, in bits, of mnemonics generated for new accounts, i.e., a 24 word mnemonic.
+pub const DEFAULT_MNEMONIC_STRENGTH: usize = 256;
+
 pub fn random_eip2333_mnemonic() -> Result<bip39::Mnemonic> {
-    let mut entropy = [1u8; 32];
-    let rng = &mut rand::rngs::OsRng;
+    generate_mnemonic_with_rng(&mut rand::rngs::OsRng, DEFAULT_MNEMONIC_STRENGTH)
+}
+
+/// Generate a mnemonic with entropy from the given source, e.g., an HSM-backed RNG.
+///
+/// The strength is the entropy in bits, a multiple of 32 between 128 and 256.
+pub fn generate_mnemonic_with_rng(
+    rng: &mut impl RngCore,
+    strength: usize,
+) -> Result<bip39::Mnemonic> {
+    if ![128, 160, 192, 224, 256].contains(&strength) {
+        return Err(Error::FailedToParseEntropy);
+    }
+    let mut entropy = vec![0u8; strength / 8];
     rng.fill_bytes(&mut entropy);
+    bip39::Mnemonic::from_entropy(&entropy).map_err(|_error| Error::FailedToParseEntropy)
+}
+
+/// Create the mnemonic for the given entropy, so tests can reproduce a known wallet.
+///
+/// This must never be used for real wallets: anyone who knows or guesses the entropy can recover
+/// the wallet. Use `random_eip2333_mnemonic` instead.
+pub fn mnemonic_from_seed_bytes(entropy: [u8; 32]) -> Result<bip39::Mnemonic> {
     let mnemonic =
         bip39::Mnemonic::from_entropy(&entropy).map_err(|_error| Error::FailedToParseEntropy)?;
     Ok(mnemonic)
 }
 
+/// Why a mnemonic typed in by the user is not valid.
+#[derive(Clone, Debug, PartialEq, Eq, ThisError)]
+pub enum MnemonicValidationError {
+    #[error("Word {position} ('{word}') is not a valid mnemonic word")]
+    UnknownWord { position: usize, word: String },
+    #[error("A mnemonic must have 12, 15, 18, 21 or 24 words, not {0}")]
+    BadWordCount(usize),
+    #[error("The mnemonic checksum failed, re-check the last word")]
+    ChecksumFailed,
+}
+
+/// Validate a mnemonic as a list of words, so a restore UI can tell the user which word is wrong.
+///
+/// Every word is checked against the English word list before the checksum, so an unknown word is
+/// never reported as a checksum failure. Positions are 1-based.
+pub fn validate_mnemonic_words(words: &[&str]) -> std::result::Result<(), MnemonicValidationError> {
+    let words = words
+        .iter()
+        .map(|word| word.trim().to_lowercase())
+        .collect::<Vec<_>>();
+    if let Some((index, word)) = words
+        .iter()
+        .enumerate()
+        .find(|(_, word)| bip39::Language::English.find_word(word).is_none())
+    {
+        return Err(MnemonicValidationError::UnknownWord {
+            position: index + 1,
+            word: word.clone(),
+        });
+    }
+
+    match bip39::Mnemonic::parse_in_normalized(bip39::Language::English, &words.join(" ")) {
+        Ok(_) => Ok(()),
+        Err(bip39::Error::InvalidChecksum) => Err(MnemonicValidationError::ChecksumFailed),
+        Err(_) => Err(MnemonicValidationError::BadWordCount(words.len())),
+    }
+}
+
+/// Check the account derivation paths are well formed, as `m` followed by `/<index>` segments.
+///
+/// The paths are constants, but a malformed one would otherwise only surface when a user creates
+/// a wallet, so the client checks them when it starts. The check runs once per process.
+pub fn check_account_derivation_paths() -> Result<()> {
+    let malformed = MALFORMED_DERIVATION_PATH.get_or_init(|| {
+        [ACCOUNT_ROOT_XORNAME_DERIVATION, ACCOUNT_WALLET_DERIVATION]
+            .into_iter()
+            .find(|path| !is_valid_derivation_path(path))
+    });
+    match malformed {
+        Some(path) => {
+            error!("The account derivation path {path:?} is malformed");
+            Err(Error::InvalidDerivationPath(path.to_string()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether EIP2333 can derive a key along the path. The derivation panics on a path it can't
+/// parse, so the path is derived from a throwaway key rather than parsed separately.
+fn is_valid_derivation_path(path: &str) -> bool {
+    let Ok(root_sk) = eip2333::derive_master_sk(&[0u8; 32]) else {
+        return false;
+    };
+    // The throwaway key is dropped whether or not the derivation panics.
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        eip2333::derive_child_sk(root_sk, path)
+    }))
+    .is_ok()
+}
+
 /// Derive a wallet secret key from the mnemonic for the account.
+///
+/// The passphrase is combined with the mnemonic to form the BIP39 seed, so different passphrases
+/// yield different, unrelated wallets from the same mnemonic. The same passphrase must be supplied
+/// again to restore a wallet.
+///
+/// The seed and EIP2333 derivations are expensive. Use a `DerivedKeyCache` to derive the keys of
+/// an account more than once.
 pub fn account_wallet_secret_key(
     mnemonic: bip39::Mnemonic,
     passphrase: &str,
 ) -> Result<MainSecretKey> {
-    let seed = mnemonic.to_seed(passphrase);
+    check_account_derivation_paths()?;
+    derive_account_wallet_secret_key(&mnemonic, passphrase).map(MainSecretKey::new)
+}
 
+fn derive_account_wallet_secret_key(
+    mnemonic: &bip39::Mnemonic,
+    passphrase: &str,
+) -> Result<SecretKey> {
+    let seed = mnemonic.to_seed(passphrase);
     let root_sk =
         eip2333::derive_master_sk(&seed).map_err(|_err| Error::InvalidMnemonicSeedPhrase)?;
     let derived_key = eip2333::derive_child_sk(root_sk, ACCOUNT_WALLET_DERIVATION);
     let key_bytes = derived_key.serialize();
-    let sk = SecretKey::from_bytes(key_bytes.into()).map_err(|_err| Error::InvalidKeyBytes)?;
-    Ok(MainSecretKey::new(sk))
+    SecretKey::from_bytes(key_bytes.into()).map_err(|_err| Error::InvalidKeyBytes)
+}
+
+/// The wallet keys derived for an account's mnemonics, so each is only derived once.
+///
+/// The cache holds at most its capacity of keys, evicting the least recently used one to make
+/// room. The keys are held as bytes which are zeroized when a key is evicted, the cache is cleared
+/// or the cache is dropped.
+pub struct DerivedKeyCache {
+    capacity: usize,
+    /// The keys, keyed by a hash of the mnemonic's entropy, the passphrase and the derivation
+    /// path, with the most recently used last.
+    keys: VecDeque<(XorName, Zeroizing<[u8; 32]>)>,
+}
+
+impl Default for DerivedKeyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CACHED_KEYS)
+    }
+}
+
+impl DerivedKeyCache {
+    /// Create a cache holding at most `capacity` keys. A capacity of zero caches nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            keys: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The number of keys in the cache.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Drop every cached key, e.g., when the user locks or logs out of the account.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Derive a wallet secret key from the mnemonic for the account, as `account_wallet_secret_key`
+    /// does, returning the cached key if it was derived before.
+    pub fn account_wallet_secret_key(
+        &mut self,
+        mnemonic: &bip39::Mnemonic,
+        passphrase: &str,
+    ) -> Result<MainSecretKey> {
+        check_account_derivation_paths()?;
+        // The cache is keyed before the seed is derived, as that is the costly PBKDF2 step.
+        let cache_key = XorName::from_content_parts(&[
+            &mnemonic.to_entropy(),
+            passphrase.as_bytes(),
+            ACCOUNT_WALLET_DERIVATION.as_bytes(),
+        ]);
+        let sk = self.get_or_derive(cache_key, || {
+            derive_account_wallet_secret_key(mnemonic, passphrase)
+        })?;
+        Ok(MainSecretKey::new(sk))
+    }
+
+    /// Return the key cached under `cache_key`, or derive and cache it.
+    ///
+    /// None of the mnemonic, passphrase or derivation path hashed into the cache key are kept.
+    fn get_or_derive(
+        &mut self,
+        cache_key: XorName,
+        derive: impl FnOnce() -> Result<SecretKey>,
+    ) -> Result<SecretKey> {
+        if let Some(index) = self.keys.iter().position(|(key, _)| *key == cache_key) {
+            if let Some(entry) = self.keys.remove(index) {
+                let sk = SecretKey::from_bytes(*entry.1).map_err(|_err| Error::InvalidKeyBytes)?;
+                self.keys.push_back(entry);
+                return Ok(sk);
+            }
+        }
+
+        let sk = derive()?;
+        if self.capacity == 0 {
+            return Ok(sk);
+        }
+        if self.keys.len() >= self.capacity {
+            let _ = self.keys.pop_front();
+        }
+        self.keys
+            .push_back((cache_key, Zeroizing::new(sk.to_bytes())));
+        Ok(sk)
+    }
+}
+
+/// Recover a wallet secret key from an Ethereum-style encrypted keystore JSON.
+///
+/// The keystore holds secp256k1 key material, which is not in general a valid BLS secret key. The
+/// decrypted bytes are instead used as the seed for the EIP2333 master key derivation. The same
+/// keystore always maps to the same wallet, but the wallet key is not the Ethereum key itself and
+/// the Ethereum address is unrelated to the wallet's address.
+pub fn main_secret_key_from_keystore(json: &str, password: &str) -> Result<MainSecretKey> {
+    let key_material = decrypt_keystore(json, password)?;
+    let sk = eip2333::derive_master_sk(&key_material)
+        .map_err(|_err| Error::KeystoreDecryption("key material is too short".to_string()))?;
+    let sk = SecretKey::from_bytes(sk.serialize().into()).map_err(|_err| Error::InvalidKeyBytes)?;
+    let main_sk = MainSecretKey::new(sk);
+    sanity_check_key(&main_sk)?;
+    Ok(main_sk)
+}
+
+/// The parts of an Ethereum-style (version 3) keystore needed to decrypt it.
+#[derive(Deserialize)]
+struct Keystore {
+    crypto: KeystoreCrypto,
+}
+
+#[derive(Deserialize)]
+struct KeystoreCrypto {
+    cipher: String,
+    cipherparams: KeystoreCipherParams,
+    ciphertext: String,
+    kdf: String,
+    kdfparams: serde_json::Value,
+    mac: String,
+}
+
+#[derive(Deserialize)]
+struct KeystoreCipherParams {
+    iv: String,
+}
+
+#[derive(Deserialize)]
+struct ScryptParams {
+    dklen: usize,
+    n: u32,
+    p: u32,
+    r: u32,
+    salt: String,
+}
+
+#[derive(Deserialize)]
+struct Pbkdf2Params {
+    c: u32,
+    dklen: usize,
+    prf: String,
+    salt: String,
+}
+
+/// Decrypt the key material held in a keystore JSON.
+///
+/// The key is derived from the password with the keystore's KDF, scrypt or PBKDF2, and checked
+/// against the keystore's MAC before the AES-128-CTR ciphertext is decrypted, so a wrong password
+/// is reported as such.
+fn decrypt_keystore(json: &str, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let crypto = serde_json::from_str::<Keystore>(json)
+        .map_err(keystore_error)?
+        .crypto;
+    if crypto.cipher != "aes-128-ctr" {
+        return Err(keystore_error(format!(
+            "unsupported cipher {}",
+            crypto.cipher
+        )));
+    }
+
+    let derived_key = match crypto.kdf.as_str() {
+        "scrypt" => {
+            let params =
+                serde_json::from_value::<ScryptParams>(crypto.kdfparams).map_err(keystore_error)?;
+            if !params.n.is_power_of_two() {
+                return Err(keystore_error("the scrypt cost is not a power of two"));
+            }
+            let scrypt_params =
+                scrypt::Params::new(params.n.trailing_zeros() as u8, params.r, params.p)
+                    .map_err(keystore_error)?;
+            let mut derived_key = Zeroizing::new(vec![0u8; params.dklen]);
+            scrypt::scrypt(
+                password.as_bytes(),
+                &hex::decode(params.salt).map_err(keystore_error)?,
+                &scrypt_params,
+                &mut derived_key,
+            )
+            .map_err(keystore_error)?;
+            derived_key
+        }
+        "pbkdf2" => {
+            let params =
+                serde_json::from_value::<Pbkdf2Params>(crypto.kdfparams).map_err(keystore_error)?;
+            if params.prf != "hmac-sha256" {
+                return Err(keystore_error(format!(
+                    "unsupported PBKDF2 PRF {}",
+                    params.prf
+                )));
+            }
+            let mut derived_key = Zeroizing::new(vec![0u8; params.dklen]);
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(
+                password.as_bytes(),
+                &hex::decode(params.salt).map_err(keystore_error)?,
+                params.c,
+                &mut derived_key,
+            );
+            derived_key
+        }
+        kdf => return Err(keystore_error(format!("unsupported KDF {kdf}"))),
+    };
+    if derived_key.len() < 32 {
+        return Err(keystore_error("the derived key is too short"));
+    }
+
+    let ciphertext = hex::decode(crypto.ciphertext).map_err(keystore_error)?;
+    let mac = Keccak256::new()
+        .chain_update(&derived_key[16..32])
+        .chain_update(&ciphertext)
+        .finalize();
+    if mac.as_slice() != hex::decode(crypto.mac).map_err(keystore_error)? {
+        return Err(Error::KeystoreWrongPassword);
+    }
+
+    let iv = hex::decode(crypto.cipherparams.iv).map_err(keystore_error)?;
+    let mut cipher = ctr::Ctr128BE::<aes::Aes128>::new_from_slices(&derived_key[..16], &iv)
+        .map_err(keystore_error)?;
+    let mut key_material = Zeroizing::new(ciphertext);
+    cipher.apply_keystream(&mut key_material);
+    Ok(key_material)
+}
+
+fn keystore_error(err: impl std::fmt::Display) -> Error {
+    Error::KeystoreDecryption(err.to_string())
 }
 
 #[allow(dead_code)] // as yet unused, will be used soon
 /// Derive an xorname from the mnemonic for the account to store data.
 pub(crate) fn account_root_xorname(mnemonic: bip39::Mnemonic, passphrase: &str) -> Result<XorName> {
+    check_account_derivation_paths()?;
     let seed = mnemonic.to_seed(passphrase);
 
     let root_sk =
@@ -66,9 +417,306 @@ pub fn write_mnemonic_to_disk(files_dir: &Path, mnemonic: &bip39::Mnemonic) -> R
 }
 
 pub(super) fn read_mnemonic_from_disk(files_dir: &Path) -> Result<bip39::Mnemonic> {
-    let filename = files_dir.join(MNEMONIC_FILENAME);
-    let content = std::fs::read_to_string(filename)?;
+    read_mnemonic_file(&files_dir.join(MNEMONIC_FILENAME))
+}
+
+/// Write the mnemonic of a named account, alongside the default account's, as
+/// `account_secret.<account_name>`.
+pub fn write_named_mnemonic_to_disk(
+    files_dir: &Path,
+    account_name: &str,
+    mnemonic: &bip39::Mnemonic,
+) -> Result<()> {
+    let filename = named_mnemonic_path(files_dir, account_name)?;
+    std::fs::write(filename, mnemonic.to_string())?;
+    Ok(())
+}
+
+/// Read the mnemonic of a named account from `account_secret.<account_name>`.
+pub fn read_named_mnemonic(files_dir: &Path, account_name: &str) -> Result<bip39::Mnemonic> {
+    read_mnemonic_file(&named_mnemonic_path(files_dir, account_name)?)
+}
+
+/// The names of the named accounts found in the directory, sorted. The default, unnamed account
+/// is not included.
+pub fn list_accounts(files_dir: &Path) -> Result<Vec<String>> {
+    let prefix = format!("{MNEMONIC_FILENAME}.");
+    let mut accounts = vec![];
+    for entry in std::fs::read_dir(files_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        if let Some(account_name) = entry
+            .file_name()
+            .to_str()
+            .and_then(|filename| filename.strip_prefix(&prefix))
+        {
+            if is_valid_account_name(account_name) {
+                accounts.push(account_name.to_string());
+            }
+        }
+    }
+    accounts.sort();
+    Ok(accounts)
+}
+
+fn is_valid_account_name(account_name: &str) -> bool {
+    !account_name.is_empty()
+        && account_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn named_mnemonic_path(files_dir: &Path, account_name: &str) -> Result<PathBuf> {
+    if !is_valid_account_name(account_name) {
+        return Err(Error::InvalidAccountName(account_name.to_string()));
+    }
+    Ok(files_dir.join(format!("{MNEMONIC_FILENAME}.{account_name}")))
+}
+
+fn read_mnemonic_file(path: &Path) -> Result<bip39::Mnemonic> {
+    let content = std::fs::read_to_string(path)?;
     let mnemonic =
         bip39::Mnemonic::parse_normalized(&content).map_err(|_err| Error::FailedToParseMnemonic)?;
     Ok(mnemonic)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn account_derivation_paths_should_be_well_formed() -> Result<()> {
+        check_account_derivation_paths()?;
+
+        let root_sk = eip2333::derive_master_sk(&[7u8; 32])
+            .map_err(|_err| Error::InvalidMnemonicSeedPhrase)?;
+        for path in [ACCOUNT_ROOT_XORNAME_DERIVATION, ACCOUNT_WALLET_DERIVATION] {
+            assert!(is_valid_derivation_path(path));
+            let derived_key = eip2333::derive_child_sk(root_sk.clone(), path);
+            assert_ne!(derived_key.serialize(), root_sk.serialize());
+        }
+
+        for malformed in ["1/0", "m/1/x", "n/1/0"] {
+            assert!(!is_valid_derivation_path(malformed), "{malformed:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn named_accounts_should_be_listed_and_read_independently() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let savings = mnemonic_from_seed_bytes([1u8; 32])?;
+        let spending = mnemonic_from_seed_bytes([2u8; 32])?;
+        let default = mnemonic_from_seed_bytes([3u8; 32])?;
+        write_named_mnemonic_to_disk(dir.path(), "spending", &spending)?;
+        write_named_mnemonic_to_disk(dir.path(), "savings", &savings)?;
+        write_mnemonic_to_disk(dir.path(), &default)?;
+
+        assert_eq!(list_accounts(dir.path())?, vec!["savings", "spending"]);
+        assert_eq!(read_named_mnemonic(dir.path(), "savings")?, savings);
+        assert_eq!(read_named_mnemonic(dir.path(), "spending")?, spending);
+        assert_eq!(read_mnemonic_from_disk(dir.path())?, default);
+        assert!(matches!(
+            read_named_mnemonic(dir.path(), "../savings"),
+            Err(Error::InvalidAccountName(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_mnemonic_with_rng_should_be_deterministic_for_a_seeded_rng() -> Result<()> {
+        let mnemonic = generate_mnemonic_with_rng(&mut StdRng::seed_from_u64(42), 256)?;
+        let same_seed = generate_mnemonic_with_rng(&mut StdRng::seed_from_u64(42), 256)?;
+        let other_seed = generate_mnemonic_with_rng(&mut StdRng::seed_from_u64(43), 256)?;
+
+        assert_eq!(mnemonic, same_seed);
+        assert_ne!(mnemonic, other_seed);
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(
+            generate_mnemonic_with_rng(&mut StdRng::seed_from_u64(42), 128)?.word_count(),
+            12
+        );
+        assert!(generate_mnemonic_with_rng(&mut StdRng::seed_from_u64(42), 100).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_mnemonic_words_should_distinguish_unknown_words_from_checksum_failures(
+    ) -> Result<()> {
+        let phrase = mnemonic_from_seed_bytes([7u8; 32])?.to_string();
+        let mut words = phrase.split(' ').collect::<Vec<_>>();
+        assert_eq!(validate_mnemonic_words(&words), Ok(()));
+
+        let mut unknown = words.clone();
+        unknown[3] = "notaword";
+        assert_eq!(
+            validate_mnemonic_words(&unknown),
+            Err(MnemonicValidationError::UnknownWord {
+                position: 4,
+                word: "notaword".to_string()
+            })
+        );
+
+        // Most, but not all, replacements for the last word break the checksum.
+        let last = words.len() - 1;
+        let wrong_last_word = bip39::Language::English
+            .word_list()
+            .iter()
+            .find(|word| {
+                let mut candidate = words.clone();
+                candidate[last] = word;
+                validate_mnemonic_words(&candidate).is_err()
+            })
+            .ok_or(Error::FailedToParseMnemonic)?;
+        words[last] = wrong_last_word;
+        assert_eq!(
+            validate_mnemonic_words(&words),
+            Err(MnemonicValidationError::ChecksumFailed)
+        );
+
+        assert_eq!(
+            validate_mnemonic_words(&words[..5]),
+            Err(MnemonicValidationError::BadWordCount(5))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn account_wallet_secret_key_should_depend_on_the_passphrase() -> Result<()> {
+        let mnemonic = random_eip2333_mnemonic()?;
+
+        let default_key = account_wallet_secret_key(mnemonic.clone(), "default")?;
+        let same_key = account_wallet_secret_key(mnemonic.clone(), "default")?;
+        let other_key = account_wallet_secret_key(mnemonic, "25th word")?;
+
+        assert_eq!(default_key.main_pubkey(), same_key.main_pubkey());
+        assert_ne!(default_key.main_pubkey(), other_key.main_pubkey());
+
+        Ok(())
+    }
+
+    #[test]
+    fn mnemonic_from_seed_bytes_should_be_deterministic() -> Result<()> {
+        let first = mnemonic_from_seed_bytes([7u8; 32])?;
+        let second = mnemonic_from_seed_bytes([7u8; 32])?;
+        let other = mnemonic_from_seed_bytes([8u8; 32])?;
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(
+            account_wallet_secret_key(first, "default")?.main_pubkey(),
+            account_wallet_secret_key(second, "default")?.main_pubkey()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn derived_key_cache_should_return_the_same_key_as_an_uncached_derivation() -> Result<()> {
+        let mnemonic = random_eip2333_mnemonic()?;
+        let mut cache = DerivedKeyCache::default();
+
+        let first_key = cache.account_wallet_secret_key(&mnemonic, "default")?;
+        let second_key = cache.account_wallet_secret_key(&mnemonic, "default")?;
+        let uncached_key = account_wallet_secret_key(mnemonic, "default")?;
+        assert_eq!(first_key.main_pubkey(), second_key.main_pubkey());
+        assert_eq!(first_key.main_pubkey(), uncached_key.main_pubkey());
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn derived_key_cache_should_only_derive_once_per_key() -> Result<()> {
+        let mut cache = DerivedKeyCache::default();
+        let cache_key = XorName::random(&mut rand::thread_rng());
+        let sk = SecretKey::random();
+        let mut derivation_count = 0;
+
+        let first = cache.get_or_derive(cache_key, || {
+            derivation_count += 1;
+            Ok(sk.clone())
+        })?;
+        let second = cache.get_or_derive(cache_key, || {
+            derivation_count += 1;
+            Ok(SecretKey::random())
+        })?;
+
+        assert_eq!(derivation_count, 1);
+        assert_eq!(first, sk);
+        assert_eq!(second, sk);
+
+        Ok(())
+    }
+
+    #[test]
+    fn derived_key_cache_should_evict_the_least_recently_used_key() -> Result<()> {
+        let mut cache = DerivedKeyCache::new(2);
+        let mut rng = rand::thread_rng();
+        let (first_key, second_key, third_key) = (
+            XorName::random(&mut rng),
+            XorName::random(&mut rng),
+            XorName::random(&mut rng),
+        );
+        let first_sk = cache.get_or_derive(first_key, || Ok(SecretKey::random()))?;
+        let second_sk = cache.get_or_derive(second_key, || Ok(SecretKey::random()))?;
+
+        // Using the first key makes the second the least recently used, so it is evicted.
+        let _ = cache.get_or_derive(first_key, || Ok(SecretKey::random()))?;
+        let _ = cache.get_or_derive(third_key, || Ok(SecretKey::random()))?;
+        assert_eq!(cache.len(), 2);
+
+        let mut derivation_count = 0;
+        let cached_first_sk = cache.get_or_derive(first_key, || {
+            derivation_count += 1;
+            Ok(SecretKey::random())
+        })?;
+        assert_eq!(cached_first_sk, first_sk);
+        assert_eq!(derivation_count, 0);
+        let rederived_second_sk = cache.get_or_derive(second_key, || {
+            derivation_count += 1;
+            Ok(SecretKey::random())
+        })?;
+        assert_ne!(rederived_second_sk, second_sk);
+        assert_eq!(derivation_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn main_secret_key_from_keystore_should_recover_the_key() -> Result<()> {
+        let keystore_dir = tempfile::tempdir()?;
+        let (key_material, _uuid) = eth_keystore::new(
+            keystore_dir.path(),
+            &mut rand::thread_rng(),
+            "password",
+            Some("keystore.json"),
+        )
+        .map_err(|err| Error::KeystoreDecryption(err.to_string()))?;
+        let json = std::fs::read_to_string(keystore_dir.path().join("keystore.json"))?;
+
+        let recovered = main_secret_key_from_keystore(&json, "password")?;
+        let expected = eip2333::derive_master_sk(&key_material)
+            .map_err(|_err| Error::InvalidMnemonicSeedPhrase)?;
+        let expected = SecretKey::from_bytes(expected.serialize().into())
+            .map_err(|_err| Error::InvalidKeyBytes)?;
+        assert_eq!(
+            recovered.main_pubkey(),
+            MainSecretKey::new(expected).main_pubkey()
+        );
+
+        let result = main_secret_key_from_keystore(&json, "wrong password");
+        assert!(matches!(result, Err(Error::KeystoreWrongPassword)));
+
+        let result = main_secret_key_from_keystore("{}", "password");
+        assert!(matches!(result, Err(Error::KeystoreDecryption(_))));
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
+    acc_packet::user_secret::check_account_derivation_paths,
     error::{Error, Result},
     Client, ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver, ClientRegister,
     WalletClient,
@@ -24,7 +25,7 @@ use sn_networking::{
     get_signed_spend_from_record, multiaddr_is_global,
     target_arch::{interval, spawn, timeout, Instant},
     GetRecordCfg, GetRecordError, NetworkBuilder, NetworkError, NetworkEvent, PutRecordCfg,
-    VerificationKind, CLOSE_GROUP_SIZE,
+    QuoteGatherConfig, VerificationKind, CLOSE_GROUP_SIZE,
 };
 use sn_protocol::{
     error::Error as ProtocolError,
@@ -37,7 +38,8 @@ use sn_protocol::{
 };
 use sn_registers::{Permissions, SignedRegister};
 use sn_transfers::{
-    CashNote, CashNoteRedemption, MainPubkey, NanoTokens, Payment, SignedSpend, TransferError,
+    CashNote, CashNoteRedemption, MainPubkey, NanoTokens, PaymentEnvelope, SignedSpend,
+    TransferError,
 };
 #[cfg(target_arch = "wasm32")]
 use std::path::PathBuf;
@@ -90,6 +92,30 @@ impl Client {
         connection_timeout: Option<Duration>,
         client_event_broadcaster: Option<ClientEventsBroadcaster>,
     ) -> Result<Self> {
+        Self::new_with_quote_gather_config(
+            signer,
+            peers,
+            connection_timeout,
+            client_event_broadcaster,
+            QuoteGatherConfig::default(),
+        )
+        .await
+    }
+
+    /// Instantiate a new client, setting how long to wait for store cost quotes, and how many are
+    /// needed, when paying for data.
+    ///
+    /// The other arguments are as for [`Client::new`].
+    pub async fn new_with_quote_gather_config(
+        signer: SecretKey,
+        peers: Option<Vec<Multiaddr>>,
+        connection_timeout: Option<Duration>,
+        client_event_broadcaster: Option<ClientEventsBroadcaster>,
+        quote_gather_config: QuoteGatherConfig,
+    ) -> Result<Self> {
+        // Fail fast on a malformed account derivation path rather than when a wallet is created.
+        check_account_derivation_paths()?;
+
         // If any of our contact peers has a global address, we'll assume we're in a global network.
         let local = match peers {
             Some(ref peers) => !peers.iter().any(multiaddr_is_global),
@@ -105,11 +131,8 @@ impl Client {
         let root_dir = std::env::temp_dir();
         trace!("Starting Kad swarm in client mode..{root_dir:?}.");
 
-        #[cfg(not(feature = "open-metrics"))]
-        let network_builder = NetworkBuilder::new(Keypair::generate_ed25519(), local, root_dir);
-
-        #[cfg(feature = "open-metrics")]
         let mut network_builder = NetworkBuilder::new(Keypair::generate_ed25519(), local, root_dir);
+        network_builder.quote_gather_config(quote_gather_config);
         #[cfg(feature = "open-metrics")]
         network_builder.metrics_registry(Some(Registry::default()));
 
@@ -624,7 +647,7 @@ impl Client {
     /// # Arguments
     /// * 'chunk' - [Chunk]
     /// * 'payee' - [PeerId]
-    /// * 'payment' - [Payment]
+    /// * 'payment' - [PaymentEnvelope]
     /// * 'verify_store' - Boolean
     /// * 'retry_strategy' - [Option]<[RetryStrategy]> : Uses Balanced by default
     ///
@@ -632,7 +655,7 @@ impl Client {
         &self,
         chunk: Chunk,
         payee: PeerId,
-        payment: Payment,
+        payment: PaymentEnvelope,
         verify_store: bool,
         retry_strategy: Option<RetryStrategy>,
     ) -> Result<()> {
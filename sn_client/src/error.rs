@@ -160,6 +160,22 @@ pub enum Error {
     #[error("Invalid mnemonic seed phrase")]
     InvalidMnemonicSeedPhrase,
 
+    #[error(
+        "The account derivation path {0:?} is malformed, it must be of the form 'm/<index>/...'"
+    )]
+    InvalidDerivationPath(String),
+
+    #[error(
+        "Invalid account name {0:?}, it must be non-empty and made of letters, digits, '-' or '_'"
+    )]
+    InvalidAccountName(String),
+
     #[error("SecretKey could not be created from the provided bytes")]
     InvalidKeyBytes,
+
+    #[error("The keystore could not be decrypted with the provided password")]
+    KeystoreWrongPassword,
+
+    #[error("Failed to decrypt the keystore: {0}")]
+    KeystoreDecryption(String),
 }
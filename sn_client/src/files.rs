@@ -7,10 +7,16 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 pub(crate) mod download;
+pub(crate) mod upload_report;
 
+use self::upload_report::{store_paid_chunks, UploadReport};
 use crate::{
-    acc_packet::load_account_wallet_or_create_with_mnemonic, chunks::Error as ChunksError,
-    error::Result, wallet::StoragePaymentResult, Client, Error, WalletClient,
+    acc_packet::load_account_wallet_or_create_with_mnemonic,
+    chunks::Error as ChunksError,
+    error::Result,
+    retry::{retry, RetryPolicy},
+    wallet::StoragePaymentResult,
+    Client, Error, WalletClient,
 };
 use bytes::Bytes;
 use self_encryption::{self, MIN_ENCRYPTABLE_BYTES};
@@ -114,7 +120,8 @@ impl FilesApi {
     /// Directly writes Chunks to the network in the
     /// form of immutable self encrypted chunks.
     ///
-    /// * 'retry_strategy' - [Option]<[RetryStrategy]> : Uses Balanced by default
+    /// * 'retry_strategy' - [Option]<[RetryStrategy]> : How many attempts to make; the default
+    ///   [RetryPolicy] is used if not set
     pub async fn get_local_payment_and_upload_chunk(
         &self,
         chunk: Chunk,
@@ -129,9 +136,18 @@ impl FilesApi {
 
         debug!("Payments for chunk: {chunk_addr:?} to {payee:?}:  {payment:?}");
 
-        self.client
-            .store_chunk(chunk, payee, payment, verify_store, retry_strategy)
-            .await?;
+        // The attempts are made here, so each one makes a single PUT rather than retrying within.
+        let policy = retry_strategy.map(RetryPolicy::from).unwrap_or_default();
+        retry(&policy, || {
+            self.client.store_chunk(
+                chunk.clone(),
+                payee,
+                payment.clone(),
+                verify_store,
+                Some(RetryStrategy::None),
+            )
+        })
+        .await?;
 
         wallet_client.remove_payment_for_addr(&chunk_addr)?;
 
@@ -158,6 +174,34 @@ impl FilesApi {
         Ok(res)
     }
 
+    /// Pay for the chunks, then store each of them, reporting which step each chunk got through.
+    ///
+    /// Chunks that were paid for but could not be stored can be retried with
+    /// `get_local_payment_and_upload_chunk` without paying again.
+    pub async fn pay_and_store(
+        &self,
+        chunks: Vec<Chunk>,
+        verify_store: bool,
+    ) -> Result<UploadReport> {
+        let payment = self
+            .pay_for_chunks(chunks.iter().map(|chunk| *chunk.name()).collect())
+            .await?;
+        let wallet_client = self.wallet()?;
+
+        Ok(store_paid_chunks(
+            chunks,
+            &payment.skipped_chunks,
+            |chunk| {
+                wallet_client
+                    .get_recent_payment_for_addr(&chunk.network_address())
+                    .ok()
+                    .map(|(_, payee)| payee)
+            },
+            |chunk| self.get_local_payment_and_upload_chunk(chunk, verify_store, None),
+        )
+        .await)
+    }
+
     // --------------------------------------------
     // ---------- Private helpers -----------------
     // --------------------------------------------
@@ -12,7 +12,7 @@ use crate::{
     Client, FilesApi, BATCH_SIZE,
 };
 use bytes::Bytes;
-use futures::StreamExt;
+use futures::{Future, Stream, StreamExt};
 use itertools::Itertools;
 use self_encryption::{decrypt_full_set, DataMap, EncryptedChunk, StreamSelfDecryptor};
 use sn_networking::target_arch::Instant;
@@ -471,6 +471,21 @@ impl FilesDownload {
         }
     }
 
+    /// Download the file described by the `data_map` as a stream of its decrypted bytes, in order.
+    ///
+    /// Up to the batch size of chunks are fetched concurrently, but each chunk's bytes are only
+    /// yielded after those of the chunks before it, so they can be written straight to a file or
+    /// an HTTP response. Chunks are only fetched as the stream is polled, so a slow consumer holds
+    /// back the download rather than buffering the whole file.
+    pub fn download_stream(&self, data_map: DataMap) -> impl Stream<Item = Result<Bytes>> {
+        let client = self.api.client.clone();
+        let show_holders = self.show_holders;
+        let retry_strategy = self.retry_strategy;
+        ordered_decrypted_stream(data_map, self.batch_size, move |address, index| {
+            Self::get_chunk(client.clone(), address, index, show_holders, retry_strategy)
+        })
+    }
+
     /// Extracts a file DataMapLevel from a chunk.
     /// If the DataMapLevel is not the first level mapping directly to the user's contents,
     /// the process repeats itself until it obtains the first level DataMapLevel.
@@ -533,3 +548,136 @@ impl FilesDownload {
         Ok((chunk.address, index, encrypted_chunk))
     }
 }
+
+/// Fetch the chunks of the `data_map` with `fetch`, up to `batch_size` at a time, and decrypt them
+/// in order as the stream is polled.
+fn ordered_decrypted_stream<F, Fut>(
+    data_map: DataMap,
+    batch_size: usize,
+    mut fetch: F,
+) -> impl Stream<Item = Result<Bytes>>
+where
+    F: FnMut(XorName, usize) -> Fut,
+    Fut: Future<Output = std::result::Result<(ChunkAddress, usize, EncryptedChunk), ChunksError>>,
+{
+    let chunk_infos = data_map.infos();
+    let file_size = data_map.file_size();
+    // The position in the file of each chunk's first byte.
+    let positions = chunk_infos
+        .iter()
+        .scan(0, |position, chunk_info| {
+            let chunk_position = *position;
+            *position += chunk_info.src_size;
+            Some(chunk_position)
+        })
+        .collect_vec();
+    futures::stream::iter(chunk_infos.into_iter().zip(positions))
+        .map(move |(chunk_info, position)| {
+            let fetched = fetch(chunk_info.dst_hash, chunk_info.index);
+            async move {
+                fetched
+                    .await
+                    .map(|(_, _, chunk)| (chunk, position, chunk_info.src_size))
+            }
+        })
+        // `buffered` rather than `buffer_unordered`, so the chunks come out in the order of the
+        // data map however they complete.
+        .buffered(batch_size)
+        .map(move |result| {
+            let (encrypted_chunk, position, src_size) = result?;
+            // Each chunk's keys come from the data map, so a chunk can be decrypted on its own,
+            // given where its bytes sit within it.
+            let info = self_encryption::seek_info(file_size, position, src_size);
+            let bytes = self_encryption::decrypt_range(
+                &data_map,
+                &[encrypted_chunk],
+                info.relative_pos,
+                src_size,
+            )
+            .map_err(ChunksError::SelfEncryption)?;
+            Ok(bytes)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, RngCore};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn ordered_decrypted_stream_should_yield_the_file_in_order() -> Result<()> {
+        let mut file = vec![0u8; 5 * 1024 * 1024 + 123];
+        thread_rng().fill_bytes(&mut file);
+        let (data_map, encrypted_chunks) = self_encryption::encrypt(Bytes::from(file.clone()))
+            .map_err(ChunksError::SelfEncryption)?;
+        let chunk_count = encrypted_chunks.len();
+        assert!(chunk_count > 3);
+
+        let store = encrypted_chunks
+            .into_iter()
+            .map(|chunk| (chunk.index, chunk))
+            .collect::<HashMap<_, _>>();
+
+        // Later chunks are fetched faster, so they complete out of order.
+        let fetch = |address: XorName, index: usize| {
+            let chunk = store.get(&index).cloned();
+            let delay = Duration::from_millis(10 * (chunk_count - index) as u64);
+            async move {
+                tokio::time::sleep(delay).await;
+                chunk
+                    .map(|chunk| (ChunkAddress::new(address), index, chunk))
+                    .ok_or(ChunksError::ChunkMissing(address))
+            }
+        };
+
+        let mut downloaded = Vec::new();
+        let mut stream = Box::pin(ordered_decrypted_stream(data_map.clone(), 4, fetch));
+        let mut yielded = 0;
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes?;
+            assert_eq!(bytes.len(), data_map.infos()[yielded].src_size);
+            downloaded.extend_from_slice(&bytes);
+            yielded += 1;
+        }
+
+        assert_eq!(yielded, chunk_count);
+        assert_eq!(downloaded, file);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ordered_decrypted_stream_should_match_decrypting_the_whole_file() -> Result<()> {
+        let mut file = vec![0u8; 3 * 1024 * 1024 - 17];
+        thread_rng().fill_bytes(&mut file);
+        let (data_map, encrypted_chunks) =
+            self_encryption::encrypt(Bytes::from(file)).map_err(ChunksError::SelfEncryption)?;
+        assert!(encrypted_chunks.len() >= 3);
+
+        // This is how `read` decrypts a file it downloads in full.
+        let whole_file =
+            decrypt_full_set(&data_map, &encrypted_chunks).map_err(ChunksError::SelfEncryption)?;
+
+        let store = encrypted_chunks
+            .into_iter()
+            .map(|chunk| (chunk.index, chunk))
+            .collect::<HashMap<_, _>>();
+        let fetch = |address: XorName, index: usize| {
+            let chunk = store.get(&index).cloned();
+            async move {
+                chunk
+                    .map(|chunk| (ChunkAddress::new(address), index, chunk))
+                    .ok_or(ChunksError::ChunkMissing(address))
+            }
+        };
+
+        let mut streamed = Vec::new();
+        let mut stream = Box::pin(ordered_decrypted_stream(data_map, 2, fetch));
+        while let Some(bytes) = stream.next().await {
+            streamed.extend_from_slice(&bytes?);
+        }
+
+        assert_eq!(streamed, whole_file.to_vec());
+        Ok(())
+    }
+}
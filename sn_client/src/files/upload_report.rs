@@ -0,0 +1,143 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::error::Result;
+use libp2p::PeerId;
+use sn_protocol::storage::Chunk;
+use std::future::Future;
+use xor_name::XorName;
+
+/// What happened to a single chunk during `FilesApi::pay_and_store`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkUploadStatus {
+    pub name: XorName,
+    /// Whether a payment for the chunk was made.
+    pub paid: bool,
+    /// Whether the chunk is now stored, including when it was already on the network.
+    pub stored: bool,
+    /// The node that was paid to store the chunk.
+    pub payee: Option<PeerId>,
+    /// Why the chunk could not be stored.
+    pub error: Option<String>,
+}
+
+/// The per chunk outcome of paying for and storing a set of chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UploadReport {
+    pub chunks: Vec<ChunkUploadStatus>,
+}
+
+impl UploadReport {
+    /// Whether every chunk is stored.
+    pub fn succeeded(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.stored)
+    }
+
+    /// The chunks that were paid for but are not stored, which can be retried without paying
+    /// again.
+    pub fn paid_but_not_stored(&self) -> impl Iterator<Item = &ChunkUploadStatus> {
+        self.chunks
+            .iter()
+            .filter(|chunk| chunk.paid && !chunk.stored)
+    }
+}
+
+/// Store every paid chunk, recording the outcome of each.
+///
+/// `payee_for` returns the node paid for the chunk, or `None` if it was not paid for. Chunks in
+/// `already_stored` were found on the network when paying, so they are neither paid for nor
+/// stored again.
+pub(crate) async fn store_paid_chunks<P, S, Fut>(
+    chunks: Vec<Chunk>,
+    already_stored: &[XorName],
+    mut payee_for: P,
+    mut store: S,
+) -> UploadReport
+where
+    P: FnMut(&Chunk) -> Option<PeerId>,
+    S: FnMut(Chunk) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut report = UploadReport::default();
+    for chunk in chunks {
+        let name = *chunk.name();
+        if already_stored.contains(&name) {
+            report.chunks.push(ChunkUploadStatus {
+                name,
+                paid: false,
+                stored: true,
+                payee: None,
+                error: None,
+            });
+            continue;
+        }
+
+        let Some(payee) = payee_for(&chunk) else {
+            report.chunks.push(ChunkUploadStatus {
+                name,
+                paid: false,
+                stored: false,
+                payee: None,
+                error: Some("No payment was made for the chunk".to_string()),
+            });
+            continue;
+        };
+
+        let error = store(chunk).await.err().map(|err| err.to_string());
+        report.chunks.push(ChunkUploadStatus {
+            name,
+            paid: true,
+            stored: error.is_none(),
+            payee: Some(payee),
+            error,
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn a_failed_store_should_be_reported_as_paid_but_not_stored() {
+        let chunks = (0..3)
+            .map(|i| Chunk::new(Bytes::from(format!("chunk {i}"))))
+            .collect::<Vec<_>>();
+        let failing = *chunks[1].name();
+        let existing = *chunks[2].name();
+        let payee = PeerId::random();
+
+        let report = store_paid_chunks(
+            chunks,
+            &[existing],
+            |_| Some(payee),
+            |chunk| async move {
+                if *chunk.name() == failing {
+                    Err(Error::SystemIO(std::io::Error::other("store failed")))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .await;
+
+        assert!(!report.succeeded());
+        assert_eq!(
+            report
+                .paid_but_not_stored()
+                .map(|chunk| (chunk.name, chunk.payee))
+                .collect::<Vec<_>>(),
+            vec![(failing, Some(payee))]
+        );
+        assert!(report.chunks[0].paid && report.chunks[0].stored);
+        assert!(!report.chunks[2].paid && report.chunks[2].stored);
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{error::Result, Client, ClientRegister, WalletClient};
+use serde::{Deserialize, Serialize};
+use sn_protocol::storage::{ChunkAddress, RegisterAddress};
+use sn_registers::{Entry, EntryHash};
+use std::collections::BTreeSet;
+use xor_name::XorName;
+
+/// A user-defined label pointing at the root address of uploaded data, as stored in a Register
+/// entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LabelEntry {
+    label: String,
+    address: ChunkAddress,
+}
+
+/// Labels for uploaded data, kept in a Register so they can be resolved later, from any device.
+///
+/// Each label is a separate branch of the Register. Re-tagging a label writes atop every branch
+/// holding it, so concurrent writes of the same label are merged the next time it's tagged.
+#[derive(Clone)]
+pub struct DataLabels {
+    register: ClientRegister,
+}
+
+impl DataLabels {
+    /// Create a new, empty set of labels. Nothing is stored until `sync` is called.
+    pub fn new(client: Client, meta: XorName) -> Self {
+        Self {
+            register: ClientRegister::create(client, meta),
+        }
+    }
+
+    /// Retrieve an existing set of labels from the network.
+    pub async fn retrieve(client: Client, address: RegisterAddress) -> Result<Self> {
+        let register = ClientRegister::retrieve(client, address).await?;
+        Ok(Self { register })
+    }
+
+    /// The address of the Register holding the labels.
+    pub fn address(&self) -> &RegisterAddress {
+        self.register.address()
+    }
+
+    /// Label the data at `address`, usually the head address returned when uploading a file,
+    /// replacing whatever the label pointed at before (locally).
+    pub fn tag(&mut self, label: &str, address: ChunkAddress) -> Result<EntryHash> {
+        let entry = label_entry(label, address)?;
+        let children = labelled(&self.register.read(), label)
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+        self.register.write_atop(&entry, &children)
+    }
+
+    /// The address a label points at, if it has been tagged.
+    pub fn resolve(&self, label: &str) -> Option<ChunkAddress> {
+        resolve_label(&self.register.read(), label)
+    }
+
+    /// Store the labels on the network, paying for the Register if it is new.
+    pub async fn sync(
+        &mut self,
+        wallet_client: &mut WalletClient,
+        verify_store: bool,
+    ) -> Result<()> {
+        let _ = self
+            .register
+            .sync(wallet_client, verify_store, None)
+            .await?;
+        Ok(())
+    }
+}
+
+fn label_entry(label: &str, address: ChunkAddress) -> Result<Entry> {
+    Ok(rmp_serde::to_vec(&LabelEntry {
+        label: label.to_string(),
+        address,
+    })?)
+}
+
+/// The latest entries holding the label. Entries that aren't labels are ignored.
+fn labelled(entries: &BTreeSet<(EntryHash, Entry)>, label: &str) -> Vec<(EntryHash, ChunkAddress)> {
+    entries
+        .iter()
+        .filter_map(|(hash, entry)| {
+            rmp_serde::from_slice::<LabelEntry>(entry)
+                .ok()
+                .filter(|entry| entry.label == label)
+                .map(|entry| (*hash, entry.address))
+        })
+        .collect()
+}
+
+/// When concurrent writes have left several branches with the label, the one with the greatest
+/// entry hash wins, so every replica resolves the label the same way.
+fn resolve_label(entries: &BTreeSet<(EntryHash, Entry)>, label: &str) -> Option<ChunkAddress> {
+    labelled(entries, label)
+        .into_iter()
+        .max_by_key(|(hash, _)| *hash)
+        .map(|(_, address)| address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::SecretKey;
+    use sn_registers::{Permissions, Register};
+
+    fn random_address() -> ChunkAddress {
+        ChunkAddress::new(XorName::random(&mut rand::thread_rng()))
+    }
+
+    #[test]
+    fn a_tagged_label_should_resolve_to_its_latest_address() -> Result<()> {
+        let owner = SecretKey::random();
+        let mut register = Register::new(
+            owner.public_key(),
+            XorName::random(&mut rand::thread_rng()),
+            Permissions::default(),
+        );
+        let photos = random_address();
+        let backup = random_address();
+        let new_photos = random_address();
+
+        let (photos_hash, _) =
+            register.write(label_entry("photos", photos)?, &BTreeSet::new(), &owner)?;
+        let _ = register.write(label_entry("backup", backup)?, &BTreeSet::new(), &owner)?;
+        assert_eq!(resolve_label(&register.read(), "photos"), Some(photos));
+        assert_eq!(resolve_label(&register.read(), "backup"), Some(backup));
+        assert_eq!(resolve_label(&register.read(), "missing"), None);
+
+        let _ = register.write(
+            label_entry("photos", new_photos)?,
+            &BTreeSet::from([photos_hash]),
+            &owner,
+        )?;
+        assert_eq!(resolve_label(&register.read(), "photos"), Some(new_photos));
+        assert_eq!(resolve_label(&register.read(), "backup"), Some(backup));
+        Ok(())
+    }
+
+    #[test]
+    fn a_label_written_concurrently_should_resolve_to_the_greatest_entry_hash() -> Result<()> {
+        let owner = SecretKey::random();
+        let mut register = Register::new(
+            owner.public_key(),
+            XorName::random(&mut rand::thread_rng()),
+            Permissions::default(),
+        );
+        let first = random_address();
+        let second = random_address();
+
+        let (first_hash, _) =
+            register.write(label_entry("photos", first)?, &BTreeSet::new(), &owner)?;
+        let (second_hash, _) =
+            register.write(label_entry("photos", second)?, &BTreeSet::new(), &owner)?;
+
+        let expected = if first_hash > second_hash {
+            first
+        } else {
+            second
+        };
+        assert_eq!(labelled(&register.read(), "photos").len(), 2);
+        assert_eq!(resolve_label(&register.read(), "photos"), Some(expected));
+        Ok(())
+    }
+}
@@ -18,7 +18,10 @@ mod event;
 mod faucet;
 mod files;
 mod folders;
+mod labels;
+mod preflight;
 mod register;
+mod retry;
 mod uploader;
 mod wallet;
 
@@ -43,10 +46,14 @@ pub use self::{
     faucet::fund_faucet_from_genesis_wallet,
     files::{
         download::{FilesDownload, FilesDownloadEvent},
+        upload_report::{ChunkUploadStatus, UploadReport},
         FilesApi, BATCH_SIZE,
     },
     folders::{FolderEntry, FoldersApi, Metadata},
+    labels::DataLabels,
+    preflight::{preflight_check, PreflightCheck, PreflightReport},
     register::ClientRegister,
+    retry::{retry, RetryPolicy},
     uploader::{UploadCfg, UploadEvent, UploadSummary, Uploader},
     wallet::{broadcast_signed_spends, send, StoragePaymentResult, WalletClient},
 };
@@ -0,0 +1,183 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Client;
+use bls::SecretKey;
+use libp2p::{multiaddr::Protocol, Multiaddr};
+use sn_transfers::HotWallet;
+use std::{path::Path, time::Duration};
+
+/// The outcome of a single preflight check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub message: String,
+}
+
+impl PreflightCheck {
+    fn pass(name: &'static str, message: String) -> Self {
+        Self {
+            name,
+            passed: true,
+            message,
+        }
+    }
+
+    fn fail(name: &'static str, message: String) -> Self {
+        Self {
+            name,
+            passed: false,
+            message,
+        }
+    }
+}
+
+/// The outcome of all the preflight checks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Whether every check passed, i.e., the client is ready to upload.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// The checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+}
+
+/// Check everything a client needs before an upload: usable bootstrap peers, a wallet with funds,
+/// and a network that can be joined through those peers.
+///
+/// The network is only contacted if the peers are usable.
+pub async fn preflight_check(
+    peers: &[Multiaddr],
+    wallet_dir: &Path,
+    connection_timeout: Duration,
+) -> PreflightReport {
+    let peers_check = check_peers(peers);
+    let network_check = if peers_check.passed {
+        check_network(peers, connection_timeout).await
+    } else {
+        PreflightCheck::fail(
+            "network",
+            "Not attempted, as there are no usable peers".to_string(),
+        )
+    };
+
+    PreflightReport {
+        checks: vec![peers_check, check_wallet(wallet_dir), network_check],
+    }
+}
+
+/// Every peer must include its peer ID, otherwise it can't be dialled.
+fn check_peers(peers: &[Multiaddr]) -> PreflightCheck {
+    if peers.is_empty() {
+        return PreflightCheck::fail("peers", "No peers were provided".to_string());
+    }
+    let invalid = peers
+        .iter()
+        .filter(|peer| {
+            !peer
+                .iter()
+                .any(|protocol| matches!(protocol, Protocol::P2p(_)))
+        })
+        .map(|peer| peer.to_string())
+        .collect::<Vec<_>>();
+    if !invalid.is_empty() {
+        return PreflightCheck::fail(
+            "peers",
+            format!("These peers have no peer ID: {}", invalid.join(", ")),
+        );
+    }
+    PreflightCheck::pass("peers", format!("{} peers provided", peers.len()))
+}
+
+fn check_wallet(wallet_dir: &Path) -> PreflightCheck {
+    match HotWallet::try_load_from(wallet_dir) {
+        Ok(wallet) if wallet.balance().is_zero() => {
+            PreflightCheck::fail("wallet", "The wallet has no funds".to_string())
+        }
+        Ok(wallet) => PreflightCheck::pass("wallet", format!("Balance: {}", wallet.balance())),
+        Err(err) => {
+            PreflightCheck::fail("wallet", format!("The wallet could not be loaded: {err}"))
+        }
+    }
+}
+
+async fn check_network(peers: &[Multiaddr], connection_timeout: Duration) -> PreflightCheck {
+    match Client::new(
+        SecretKey::random(),
+        Some(peers.to_vec()),
+        Some(connection_timeout),
+        None,
+    )
+    .await
+    {
+        Ok(_) => PreflightCheck::pass("network", "Connected to the network".to_string()),
+        Err(err) => PreflightCheck::fail(
+            "network",
+            format!("Could not connect to the network: {err}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_transfers::MainSecretKey;
+
+    const PEER: &str =
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE";
+
+    #[test]
+    fn peers_without_a_peer_id_should_fail_the_peers_check() -> eyre::Result<()> {
+        assert!(check_peers(&[PEER.parse()?]).passed);
+        assert!(!check_peers(&[]).passed);
+
+        let check = check_peers(&[PEER.parse()?, "/ip4/127.0.0.1/udp/12001/quic-v1".parse()?]);
+        assert!(!check.passed);
+        assert!(check.message.contains("/ip4/127.0.0.1/udp/12001/quic-v1"));
+        Ok(())
+    }
+
+    #[test]
+    fn a_missing_or_empty_wallet_should_fail_the_wallet_check() -> eyre::Result<()> {
+        let wallet_dir = tempfile::tempdir()?;
+        assert!(!check_wallet(wallet_dir.path()).passed);
+
+        let _wallet = HotWallet::create_from_key(wallet_dir.path(), MainSecretKey::random())?;
+        let check = check_wallet(wallet_dir.path());
+        assert_eq!(
+            check,
+            PreflightCheck::fail("wallet", "The wallet has no funds".to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn the_network_should_not_be_contacted_without_usable_peers() -> eyre::Result<()> {
+        let wallet_dir = tempfile::tempdir()?;
+        let report = preflight_check(&[], wallet_dir.path(), Duration::from_secs(1)).await;
+
+        assert!(!report.passed());
+        assert_eq!(
+            report
+                .failures()
+                .map(|check| check.name)
+                .collect::<Vec<_>>(),
+            vec!["peers", "wallet", "network"]
+        );
+        Ok(())
+    }
+}
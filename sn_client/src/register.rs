@@ -21,7 +21,7 @@ use sn_protocol::{
     NetworkAddress,
 };
 use sn_registers::{Entry, EntryHash, Permissions, Register, RegisterAddress, SignedRegister};
-use sn_transfers::{NanoTokens, Payment};
+use sn_transfers::{NanoTokens, PaymentEnvelope};
 use std::collections::{BTreeSet, HashSet, LinkedList};
 use xor_name::XorName;
 
@@ -492,7 +492,7 @@ impl ClientRegister {
         &mut self,
         wallet_client: &mut WalletClient,
         verify_store: bool,
-        mut payment_info: Option<(Payment, PeerId)>,
+        mut payment_info: Option<(PaymentEnvelope, PeerId)>,
     ) -> Result<(NanoTokens, NanoTokens)> {
         let addr = *self.address();
         debug!("Syncing Register at {addr:?}!");
@@ -764,7 +764,7 @@ impl ClientRegister {
     pub(crate) async fn publish_register(
         client: Client,
         cmd: RegisterCmd,
-        payment: Option<(Payment, PeerId)>,
+        payment: Option<(PaymentEnvelope, PeerId)>,
         verify_store: bool,
     ) -> Result<()> {
         let cmd_dst = cmd.dst();
@@ -0,0 +1,158 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use rand::Rng;
+use sn_protocol::storage::RetryStrategy;
+use std::{fmt::Debug, future::Future};
+use tokio::time::{sleep, Duration};
+
+/// How many times a client operation is attempted, and how long to wait between attempts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first one.
+    pub max_attempts: usize,
+    /// The delay after the first failed attempt, doubled after each subsequent one.
+    pub base_delay: Duration,
+    /// The upper bound for the delay between attempts.
+    pub max_delay: Duration,
+    /// Randomise each delay to between half and all of its value, so that many clients retrying
+    /// at once don't do so in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl From<RetryStrategy> for RetryPolicy {
+    /// Make as many attempts as the strategy would, with the default delays between them.
+    fn from(strategy: RetryStrategy) -> Self {
+        Self {
+            max_attempts: strategy.get_count() + 1,
+            ..Self::default()
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay to wait after the given failed attempt, counting from 1.
+    pub fn delay_after(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(u32::MAX as usize) as u32;
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay);
+        if self.jitter && !delay.is_zero() {
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Run `op` until it succeeds or the policy's attempts are used up, returning the last error in
+/// that case.
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy, mut op: F) -> Result<T, E>
+where
+    E: Debug,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= policy.max_attempts => return Err(err),
+            Err(err) => {
+                let delay = policy.delay_after(attempt);
+                warn!("Attempt {attempt} failed with {err:?}, retrying after {delay:?}");
+                sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn policy(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_should_make_every_attempt_before_returning_the_last_error() {
+        let attempts = Cell::new(0);
+        let result: Result<(), usize> = retry(&policy(4), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(attempts.get()) }
+        })
+        .await;
+
+        assert_eq!(result, Err(4));
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[tokio::test]
+    async fn retry_should_stop_on_the_first_success() {
+        let attempts = Cell::new(0);
+        let result: Result<usize, ()> = retry(&policy(5), || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt < 2 {
+                    Err(())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(2));
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn delay_after_should_back_off_exponentially_up_to_the_max_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            jitter: false,
+        };
+
+        assert_eq!(policy.delay_after(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_after(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_after(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_after(4), Duration::from_millis(500));
+        assert_eq!(policy.delay_after(100), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn a_policy_from_a_strategy_should_make_as_many_attempts_as_the_strategy() {
+        assert_eq!(RetryPolicy::from(RetryStrategy::None).max_attempts, 1);
+        assert_eq!(RetryPolicy::from(RetryStrategy::Quick).max_attempts, 2);
+        assert_eq!(RetryPolicy::from(RetryStrategy::Balanced).max_attempts, 4);
+        assert_eq!(RetryPolicy::from(RetryStrategy::Persistent).max_attempts, 7);
+    }
+}
@@ -987,7 +987,7 @@ impl InnerUploader {
         let xorname = upload_item.xorname();
 
         let payment_details = wallet_api.get_recent_payment(&xorname)?;
-        let payment = payment_details.to_payment();
+        let payment = payment_details.to_payment_envelope()?;
         let payee = PeerId::from_bytes(&payment_details.peer_id_bytes)
             .map_err(|_| ClientError::Wallet(WalletError::NoPaymentForAddress(xorname)))?;
 
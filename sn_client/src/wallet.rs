@@ -8,7 +8,11 @@
 
 use crate::Error;
 
-use super::{error::Result, Client};
+use super::{
+    error::Result,
+    retry::{retry, RetryPolicy},
+    Client,
+};
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use futures::{future::join_all, TryFutureExt};
 use libp2p::PeerId;
@@ -16,7 +20,7 @@ use sn_networking::target_arch::Instant;
 use sn_networking::{GetRecordError, PayeeQuote};
 use sn_protocol::NetworkAddress;
 use sn_transfers::{
-    CashNote, DerivationIndex, HotWallet, MainPubkey, NanoTokens, Payment, PaymentQuote,
+    CashNote, DerivationIndex, HotWallet, MainPubkey, NanoTokens, PaymentEnvelope, PaymentQuote,
     SignedSpend, SpendAddress, Transaction, Transfer, UniquePubkey, WalletError, WalletResult,
 };
 use std::{
@@ -165,13 +169,13 @@ impl WalletClient {
     pub fn get_recent_payment_for_addr(
         &self,
         address: &NetworkAddress,
-    ) -> WalletResult<(Payment, PeerId)> {
+    ) -> WalletResult<(PaymentEnvelope, PeerId)> {
         let xorname = address
             .as_xorname()
             .ok_or(WalletError::InvalidAddressType)?;
         let payment_detail = self.wallet.api().get_recent_payment(&xorname)?;
 
-        let payment = payment_detail.to_payment();
+        let payment = payment_detail.to_payment_envelope()?;
         trace!("Payment retrieved for {xorname:?} from wallet: {payment:?}");
         let peer_id = PeerId::from_bytes(&payment_detail.peer_id_bytes)
             .map_err(|_| WalletError::NoPaymentForAddress(xorname))?;
@@ -209,7 +213,7 @@ impl WalletClient {
     pub fn get_all_payments_for_addr(
         &self,
         address: &NetworkAddress,
-    ) -> WalletResult<Vec<(Payment, PeerId)>> {
+    ) -> WalletResult<Vec<(PaymentEnvelope, PeerId)>> {
         let xorname = address
             .as_xorname()
             .ok_or(WalletError::InvalidAddressType)?;
@@ -218,7 +222,7 @@ impl WalletClient {
         let payments = payment_details
             .into_iter()
             .map(|details| {
-                let payment = details.to_payment();
+                let payment = details.to_payment_envelope()?;
 
                 match PeerId::from_bytes(&details.peer_id_bytes) {
                     Ok(peer_id) => Ok((payment, peer_id)),
@@ -419,11 +423,13 @@ impl WalletClient {
         &self,
         address: NetworkAddress,
     ) -> WalletResult<PayeeQuote> {
-        self.client
-            .network
-            .get_store_costs_from_network(address, vec![])
-            .await
-            .map_err(|error| WalletError::CouldNotSendMoney(error.to_string()))
+        retry(&RetryPolicy::default(), || {
+            self.client
+                .network
+                .get_store_costs_from_network(address.clone(), vec![])
+        })
+        .await
+        .map_err(|error| WalletError::CouldNotSendMoney(error.to_string()))
     }
 
     /// Send tokens to nodes closest to the data we want to make storage payment for. Runs mandatory verification.
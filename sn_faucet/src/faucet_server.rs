@@ -20,8 +20,8 @@ use sn_client::{
 use sn_transfers::{
     get_faucet_data_dir, wallet_lockfile_name, NanoTokens, Transfer, WALLET_DIR_NAME,
 };
-use std::path::Path;
 use std::{collections::HashMap, sync::Arc};
+use std::{path::Path, str::FromStr};
 use tokio::sync::Semaphore;
 use tracing::{debug, error, info, warn};
 use warp::{
@@ -44,6 +44,13 @@ use std::{fs::File, path::PathBuf};
 #[cfg(feature = "initial-data")]
 use tokio::{fs, io::AsyncWriteExt};
 
+/// The amount sent for each gift request when no amount is specified: one token.
+pub const DEFAULT_GIFT_AMOUNT_NANOS: u64 = 1_000_000_000;
+
+/// The file in the faucet's data directory recording the amount the server sends for each gift
+/// request, so a restarted server keeps sending the same amount.
+const GIFT_AMOUNT_FILENAME: &str = "gift_amount";
+
 /// Run the faucet server.
 ///
 /// This will listen on port 8000 and send a transfer of tokens as response to any GET request.
@@ -62,8 +69,9 @@ use tokio::{fs, io::AsyncWriteExt};
 ///
 /// # balance should be updated
 /// ```
-pub async fn run_faucet_server(client: &Client) -> Result<()> {
+pub async fn run_faucet_server(client: &Client, gift_amount: NanoTokens) -> Result<()> {
     let root_dir = get_faucet_data_dir();
+    write_gift_amount(&root_dir, gift_amount)?;
     let wallet = load_account_wallet_or_create_with_mnemonic(&root_dir, None)?;
     claim_genesis(client, wallet).await.map_err(|err| {
         println!("Faucet Server couldn't start as we failed to claim Genesis");
@@ -77,7 +85,7 @@ pub async fn run_faucet_server(client: &Client) -> Result<()> {
         let _ = upload_initial_data(client, &root_dir).await;
     }
 
-    startup_server(client.clone()).await
+    startup_server(client.clone(), gift_amount).await
 }
 
 #[cfg(feature = "initial-data")]
@@ -241,7 +249,7 @@ async fn upload_downloaded_book(
     Ok(head_addresses)
 }
 
-pub async fn restart_faucet_server(client: &Client) -> Result<()> {
+pub async fn restart_faucet_server(client: &Client, gift_amount: Option<NanoTokens>) -> Result<()> {
     let root_dir = get_faucet_data_dir();
     println!("Loading the previous wallet at {root_dir:?}");
     debug!("Loading the previous wallet at {root_dir:?}");
@@ -251,7 +259,31 @@ pub async fn restart_faucet_server(client: &Client) -> Result<()> {
     println!("Previous wallet loaded");
     debug!("Previous wallet loaded");
 
-    startup_server(client.clone()).await
+    // Without an amount given, the restarted server sends what the previous one did.
+    let gift_amount = match gift_amount {
+        Some(gift_amount) => gift_amount,
+        None => read_gift_amount(&root_dir)?,
+    };
+    write_gift_amount(&root_dir, gift_amount)?;
+    startup_server(client.clone(), gift_amount).await
+}
+
+/// Record the gift amount in the faucet's data directory, in tokens as it is given to `--amount`.
+fn write_gift_amount(root_dir: &Path, gift_amount: NanoTokens) -> Result<()> {
+    std::fs::create_dir_all(root_dir)?;
+    std::fs::write(root_dir.join(GIFT_AMOUNT_FILENAME), gift_amount.to_string())?;
+    Ok(())
+}
+
+/// Read the gift amount recorded in the faucet's data directory, or the default amount if none
+/// was recorded, e.g., by a server started before the amount was recorded.
+fn read_gift_amount(root_dir: &Path) -> Result<NanoTokens> {
+    let path = root_dir.join(GIFT_AMOUNT_FILENAME);
+    if !path.exists() {
+        return Ok(NanoTokens::from(DEFAULT_GIFT_AMOUNT_NANOS));
+    }
+    let gift_amount = NanoTokens::from_str(std::fs::read_to_string(&path)?.trim())?;
+    Ok(gift_amount)
 }
 
 #[cfg(feature = "distribution")]
@@ -401,6 +433,7 @@ async fn respond_to_gift_request(
     _client: Client,
     _key: String,
     _semaphore: Arc<Semaphore>,
+    _gift_amount: NanoTokens,
 ) -> std::result::Result<impl Reply, std::convert::Infallible> {
     let mut response = Response::new("Gifting not enabled".to_string());
     *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
@@ -413,6 +446,7 @@ async fn respond_to_gift_request(
     client: Client,
     key: String,
     semaphore: Arc<Semaphore>,
+    gift_amount: NanoTokens,
 ) -> std::result::Result<impl Reply, std::convert::Infallible> {
     let faucet_root = get_faucet_data_dir();
 
@@ -439,8 +473,7 @@ async fn respond_to_gift_request(
         return Ok(response);
     }
 
-    const GIFT_AMOUNT_SNT: &str = "1";
-    match send_tokens(&client, from, GIFT_AMOUNT_SNT, &key).await {
+    match send_tokens(&client, from, &gift_amount.to_string(), &key).await {
         Ok(transfer) => {
             println!("Sent tokens to {key}");
             debug!("Sent tokens to {key}");
@@ -454,7 +487,7 @@ async fn respond_to_gift_request(
     }
 }
 
-async fn startup_server(client: Client) -> Result<()> {
+async fn startup_server(client: Client, gift_amount: NanoTokens) -> Result<()> {
     // Create a semaphore with a single permit
     let semaphore = Arc::new(Semaphore::new(1));
 
@@ -508,7 +541,7 @@ async fn startup_server(client: Client) -> Result<()> {
             let client = gift_client.clone();
             let semaphore = semaphore.clone();
 
-            respond_to_gift_request(client, key, semaphore)
+            respond_to_gift_request(client, key, semaphore, gift_amount)
         });
 
     // GET /donate
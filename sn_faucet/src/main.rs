@@ -23,7 +23,7 @@ use sn_client::{
 use sn_logging::{Level, LogBuilder, LogOutputDest};
 use sn_peers_acquisition::PeersArgs;
 use sn_transfers::{get_faucet_data_dir, HotWallet, MainPubkey, NanoTokens, Transfer};
-use std::{path::PathBuf, time::Duration};
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
 use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
 use tracing::{debug, error, info};
 
@@ -31,14 +31,6 @@ use tracing::{debug, error, info};
 async fn main() -> Result<()> {
     let opt = Opt::parse();
 
-    let bootstrap_peers = opt.peers.get_peers().await?;
-    let bootstrap_peers = if bootstrap_peers.is_empty() {
-        // empty vec is returned if `local-discovery` flag is provided
-        None
-    } else {
-        Some(bootstrap_peers)
-    };
-
     let logging_targets = vec![
         // TODO: Reset to nice and clean defaults once we have a better idea of what we want
         ("faucet".to_string(), Level::TRACE),
@@ -65,8 +57,34 @@ async fn main() -> Result<()> {
         "faucet built with git version: {}",
         sn_build_info::git_info()
     );
+
+    // A dry run only reads the faucet wallet, so it's done before connecting to the network.
+    if let SubCmd::Send {
+        amount,
+        to,
+        dry_run: true,
+    } = &opt.cmd
+    {
+        let recipient = MainPubkey::from_hex(to)?;
+        let amount = NanoTokens::from_str(amount)?;
+        let wallet = load_account_wallet_or_create_with_mnemonic(&get_faucet_data_dir(), None)?;
+        println!(
+            "{}",
+            dispense_preview(wallet.balance(), &recipient, amount)?
+        );
+        return Ok(());
+    }
+
     info!("Instantiating a SAFE Test Faucet...");
 
+    let bootstrap_peers = opt.peers.get_peers().await?;
+    let bootstrap_peers = if bootstrap_peers.is_empty() {
+        // empty vec is returned if `local-discovery` flag is provided
+        None
+    } else {
+        Some(bootstrap_peers)
+    };
+
     let secret_key = bls::SecretKey::random();
     let broadcaster = ClientEventsBroadcaster::default();
     let (progress_bar, handle) = spawn_connection_progress_bar(broadcaster.subscribe());
@@ -184,10 +202,19 @@ enum SubCmd {
         /// This must be a hex-encoded `MainPubkey`.
         #[clap(name = "to")]
         to: String,
+        /// Show the recipient, amount and resulting faucet balance without sending anything.
+        #[clap(long)]
+        dry_run: bool,
     },
     /// Starts an http server that will send tokens to anyone who requests them.
     /// curl http://localhost:8000/your-hex-encoded-wallet-public-address
-    Server,
+    Server {
+        /// The amount of tokens to send for each request, e.g., `0.5`.
+        ///
+        /// If not used, one token is sent.
+        #[clap(long, value_parser = NanoTokens::from_str)]
+        amount: Option<NanoTokens>,
+    },
     /// Restart the faucet_server from the last breaking point.
     ///
     /// Before firing this cmd, ensure:
@@ -196,7 +223,13 @@ enum SubCmd {
     ///   3, The old `wallet` and `wallet.lock` files shall also be removed.
     /// The command will create a new wallet with the same key,
     /// then deposit all valid cash_notes into wallet and startup the faucet_server.
-    RestartServer,
+    RestartServer {
+        /// The amount of tokens to send for each request, e.g., `0.5`.
+        ///
+        /// If not used, the amount the previous faucet_server sent is used.
+        #[clap(long, value_parser = NanoTokens::from_str)]
+        amount: Option<NanoTokens>,
+    },
 }
 
 async fn faucet_cmds(cmds: SubCmd, client: &Client, funded_wallet: HotWallet) -> Result<()> {
@@ -204,16 +237,21 @@ async fn faucet_cmds(cmds: SubCmd, client: &Client, funded_wallet: HotWallet) ->
         SubCmd::ClaimGenesis => {
             claim_genesis(client, funded_wallet).await?;
         }
-        SubCmd::Send { amount, to } => {
+        SubCmd::Send { dry_run: true, .. } => {
+            bail!("A dry run is previewed before connecting to the network")
+        }
+        SubCmd::Send { amount, to, .. } => {
             send_tokens(client, funded_wallet, &amount, &to).await?;
         }
-        SubCmd::Server => {
+        SubCmd::Server { amount } => {
+            let gift_amount =
+                amount.unwrap_or(NanoTokens::from(faucet_server::DEFAULT_GIFT_AMOUNT_NANOS));
             // shouldn't return except on error
-            run_faucet_server(client).await?;
+            run_faucet_server(client, gift_amount).await?;
         }
-        SubCmd::RestartServer => {
+        SubCmd::RestartServer { amount } => {
             // shouldn't return except on error
-            restart_faucet_server(client).await?;
+            restart_faucet_server(client, amount).await?;
         }
     }
     Ok(())
@@ -235,7 +273,6 @@ async fn claim_genesis(client: &Client, mut wallet: HotWallet) -> Result<()> {
 /// returns the hex-encoded transfer
 async fn send_tokens(client: &Client, from: HotWallet, amount: &str, to: &str) -> Result<String> {
     let to = MainPubkey::from_hex(to)?;
-    use std::str::FromStr;
     let amount = NanoTokens::from_str(amount)?;
     if amount.as_nano() == 0 {
         println!("Invalid format or zero amount passed in. Nothing sent.");
@@ -251,6 +288,42 @@ async fn send_tokens(client: &Client, from: HotWallet, amount: &str, to: &str) -
     Ok(transfer_hex)
 }
 
+/// What a faucet dispense would do, without sending anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DispensePreview {
+    recipient: MainPubkey,
+    amount: NanoTokens,
+    balance_before: NanoTokens,
+    balance_after: NanoTokens,
+}
+
+impl fmt::Display for DispensePreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Recipient: {}", self.recipient.to_hex())?;
+        writeln!(f, "Amount: {}", self.amount)?;
+        writeln!(f, "Faucet balance: {}", self.balance_before)?;
+        write!(f, "Faucet balance after dispense: {}", self.balance_after)
+    }
+}
+
+/// Preview sending `amount` from a faucet wallet holding `balance` to `recipient`, erroring if the
+/// wallet can't cover it.
+fn dispense_preview(
+    balance: NanoTokens,
+    recipient: &MainPubkey,
+    amount: NanoTokens,
+) -> Result<DispensePreview> {
+    let balance_after = balance.checked_sub(amount).ok_or_else(|| {
+        eyre!("The faucet balance of {balance} is not enough to dispense {amount}")
+    })?;
+    Ok(DispensePreview {
+        recipient: *recipient,
+        amount,
+        balance_before: balance,
+        balance_after,
+    })
+}
+
 fn parse_log_output(val: &str) -> Result<LogOutputDest> {
     match val {
         "stdout" => Ok(LogOutputDest::Stdout),
@@ -263,3 +336,30 @@ fn parse_log_output(val: &str) -> Result<LogOutputDest> {
         value => Ok(LogOutputDest::Path(PathBuf::from(value))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_transfers::MainSecretKey;
+
+    #[test]
+    fn dispense_preview_should_show_the_balance_after_the_dispense() -> Result<()> {
+        let recipient = MainSecretKey::random().main_pubkey();
+        let preview = dispense_preview(NanoTokens::from(1_000), &recipient, NanoTokens::from(300))?;
+
+        assert_eq!(preview.recipient, recipient);
+        assert_eq!(preview.amount, NanoTokens::from(300));
+        assert_eq!(preview.balance_before, NanoTokens::from(1_000));
+        assert_eq!(preview.balance_after, NanoTokens::from(700));
+        Ok(())
+    }
+
+    #[test]
+    fn dispense_preview_should_error_when_the_balance_is_insufficient() {
+        let recipient = MainSecretKey::random().main_pubkey();
+        let amount = NanoTokens::from(300);
+
+        assert!(dispense_preview(NanoTokens::from(299), &recipient, amount).is_err());
+        assert!(dispense_preview(NanoTokens::from(300), &recipient, amount).is_ok());
+    }
+}
@@ -233,7 +233,7 @@ impl TracingLayers {
 /// If any custom keyword is encountered in the CSV, for e.g., VERBOSE_SN_LOGS ('all'), then they will override some
 /// of the value that you might have provided, `sn_client=ERROR` in the above example will be ignored and
 /// instead will be set to `TRACE` since `all` keyword is provided.
-fn get_logging_targets(logging_env_value: &str) -> Result<Vec<(String, Level)>> {
+pub(crate) fn get_logging_targets(logging_env_value: &str) -> Result<Vec<(String, Level)>> {
     let mut targets = BTreeMap::new();
     let mut contains_keyword_all_sn_logs = false;
     let mut contains_keyword_verbose_sn_logs = false;
@@ -27,6 +27,12 @@ pub use layers::ReloadHandle;
 // re-exporting the tracing crate's Level as it is used in our public API
 pub use tracing_core::Level;
 
+/// Check that `value` is a valid value for the `SN_LOG` variable, e.g.,
+/// `sn_node=debug,libp2p=info` or `all`.
+pub fn validate_log_targets(value: &str) -> Result<()> {
+    layers::get_logging_targets(value).map(|_| ())
+}
+
 #[derive(Debug, Clone)]
 pub enum LogOutputDest {
     Stdout,
@@ -29,7 +29,7 @@ use crate::{
     },
     GetRecordError, Network, CLOSE_GROUP_SIZE,
 };
-use crate::{transport, NodeIssue};
+use crate::{transport, NodeIssue, QuoteGatherConfig};
 use futures::future::Either;
 use futures::StreamExt;
 #[cfg(feature = "local-discovery")]
@@ -208,6 +208,7 @@ pub struct NetworkBuilder {
     request_timeout: Option<Duration>,
     concurrency_limit: Option<usize>,
     initial_peers: Vec<Multiaddr>,
+    quote_gather_config: QuoteGatherConfig,
     #[cfg(feature = "open-metrics")]
     metrics_registry: Option<Registry>,
     #[cfg(feature = "open-metrics")]
@@ -228,6 +229,7 @@ impl NetworkBuilder {
             request_timeout: None,
             concurrency_limit: None,
             initial_peers: Default::default(),
+            quote_gather_config: Default::default(),
             #[cfg(feature = "open-metrics")]
             metrics_registry: None,
             #[cfg(feature = "open-metrics")]
@@ -257,6 +259,11 @@ impl NetworkBuilder {
         self.initial_peers = initial_peers;
     }
 
+    /// Set how long to wait for store cost quotes, and how many are needed.
+    pub fn quote_gather_config(&mut self, quote_gather_config: QuoteGatherConfig) {
+        self.quote_gather_config = quote_gather_config;
+    }
+
     #[cfg(feature = "open-metrics")]
     pub fn metrics_registry(&mut self, metrics_registry: Option<Registry>) {
         self.metrics_registry = metrics_registry;
@@ -621,6 +628,7 @@ impl NetworkBuilder {
                 peer_id: Arc::new(peer_id),
                 root_dir_path: Arc::new(self.root_dir),
                 keypair: Arc::new(self.keypair),
+                quote_gather_config: self.quote_gather_config,
             },
             network_event_receiver,
             swarm_driver,
@@ -145,6 +145,12 @@ pub enum NetworkError {
     #[error("No Store Cost Responses")]
     NoStoreCostResponses,
 
+    #[error("Only {got} quotes were received, {min_responders} were required")]
+    NotEnoughQuotes { got: usize, min_responders: usize },
+
+    #[error("Every quote gathered had expired, after {attempts} attempts")]
+    AllQuotesExpired { attempts: usize },
+
     #[error("Could not create storage dir: {path:?}, error: {source}")]
     FailedToCreateRecordStoreDir {
         path: PathBuf,
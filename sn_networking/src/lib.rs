@@ -42,10 +42,16 @@ pub use self::{
     record_store::{calculate_cost_for_records, NodeRecordStore},
     transfers::{get_raw_signed_spends_from_record, get_signed_spend_from_record},
 };
+// The close group sizes are defined by the protocol and re-exported for existing users.
+pub use sn_protocol::{close_group_majority, CLOSE_GROUP_SIZE, REPLICATION_PEERS_COUNT};
 
 use self::{cmd::SwarmCmd, error::Result};
 use backoff::{Error as BackoffError, ExponentialBackoff};
-use futures::future::select_all;
+use futures::{
+    future::{select, select_all, Either},
+    stream::FuturesUnordered,
+    StreamExt,
+};
 use libp2p::{
     identity::Keypair,
     kad::{KBucketDistance, KBucketKey, Quorum, Record, RecordKey},
@@ -56,12 +62,15 @@ use rand::Rng;
 use sn_protocol::{
     error::Error as ProtocolError,
     messages::{ChunkProof, Cmd, Nonce, Query, QueryResponse, Request, Response},
+    required_payees,
     storage::{RecordType, RetryStrategy},
-    NetworkAddress, PrettyPrintKBucketKey, PrettyPrintRecordKey,
+    NetworkAddress, PrettyPrintKBucketKey, PrettyPrintRecordKey, RedundancyLevel,
+};
+use sn_transfers::{
+    MainPubkey, NanoTokens, PaymentQuote, QuotingMetrics, SUPPORTED_QUOTE_VERSIONS,
 };
-use sn_transfers::{MainPubkey, NanoTokens, PaymentQuote, QuotingMetrics};
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     path::PathBuf,
     sync::Arc,
 };
@@ -76,29 +85,14 @@ use tracing::trace;
 /// The type of quote for a selected payee.
 pub type PayeeQuote = (PeerId, MainPubkey, PaymentQuote);
 
-/// The maximum number of peers to return in a `GetClosestPeers` response.
-/// This is the group size used in safe network protocol to be responsible for
-/// an item in the network.
-/// The peer should be present among the CLOSE_GROUP_SIZE if we're fetching the close_group(peer)
-/// The size has been set to 5 for improved performance.
-pub const CLOSE_GROUP_SIZE: usize = 5;
-
-/// The count of peers that will be considered as close to a record target,
-/// that a replication of the record shall be sent/accepted to/by the peer.
-pub const REPLICATION_PEERS_COUNT: usize = CLOSE_GROUP_SIZE + 2;
-
-/// Majority of a given group (i.e. > 1/2).
-#[inline]
-pub const fn close_group_majority() -> usize {
-    // Calculate the majority of the close group size by dividing it by 2 and adding 1.
-    // This ensures that the majority is always greater than half.
-    CLOSE_GROUP_SIZE / 2 + 1
-}
-
 /// Max duration to wait for verification.
 const MAX_WAIT_BEFORE_READING_A_PUT: Duration = Duration::from_millis(750);
 /// Min duration to wait for verification
 const MIN_WAIT_BEFORE_READING_A_PUT: Duration = Duration::from_millis(300);
+/// Max number of re-quotes when every quote gathered has expired.
+const MAX_REQUOTE_ATTEMPTS: usize = 3;
+/// Delay before the first re-quote, doubled for each one after.
+const REQUOTE_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 
 /// Sort the provided peers by their distance to the given `NetworkAddress`.
 /// Return with the closest expected number of entries if has.
@@ -152,6 +146,28 @@ pub fn sort_peers_by_key<'a, T>(
     Ok(sorted_peers)
 }
 
+/// How long to wait for store cost quotes, and how many are needed.
+///
+/// By default, every node in the close group is waited for, up to 30 seconds, so the cheapest
+/// quote is not missed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteGatherConfig {
+    /// The longest to wait for quotes before going ahead with those received.
+    pub timeout: Duration,
+    /// The number of quotes to wait for. Gathering stops as soon as this many arrive, and fails
+    /// if fewer than this arrive before the timeout.
+    pub min_responders: usize,
+}
+
+impl Default for QuoteGatherConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            min_responders: CLOSE_GROUP_SIZE,
+        }
+    }
+}
+
 #[derive(Clone)]
 /// API to interact with the underlying Swarm
 pub struct Network {
@@ -159,6 +175,7 @@ pub struct Network {
     pub peer_id: Arc<PeerId>,
     pub root_dir_path: Arc<PathBuf>,
     keypair: Arc<Keypair>,
+    quote_gather_config: QuoteGatherConfig,
 }
 
 impl Network {
@@ -362,15 +379,60 @@ impl Network {
         // close nodes will be checked internally automatically.
         let close_nodes = self.get_closest_peers(&record_address, true).await?;
 
+        let mut all_costs = gather_fresh_store_costs(
+            || self.gather_store_costs(&record_address, &close_nodes),
+            MAX_REQUOTE_ATTEMPTS,
+            REQUOTE_INITIAL_BACKOFF,
+        )
+        .await?;
+
+        // Sort all_costs by the NetworkAddress proximity to record_address
+        all_costs.sort_by(|(peer_address_a, _, _), (peer_address_b, _, _)| {
+            record_address
+                .distance(peer_address_a)
+                .cmp(&record_address.distance(peer_address_b))
+        });
+        #[allow(clippy::mutable_key_type)]
+        let ignore_peers = ignore_peers
+            .into_iter()
+            .map(NetworkAddress::from_peer)
+            .collect::<BTreeSet<_>>();
+
+        // Ensure we dont have any further out nodes than the payees required for a majority
+        // This should ensure that if we didnt get all responses from close nodes,
+        // we're less likely to be paying a node that is not in the CLOSE_GROUP
+        //
+        // Also filter out the peers.
+        let all_costs = all_costs
+            .into_iter()
+            .filter(|(peer_address, ..)| !ignore_peers.contains(peer_address))
+            .take(required_payees(CLOSE_GROUP_SIZE, RedundancyLevel::Majority))
+            .collect();
+
+        get_fees_from_store_cost_responses(all_costs)
+    }
+
+    /// Ask the `close_nodes` for a quote to store the record at `record_address`, sharing the
+    /// quotes received with each of them for verification.
+    ///
+    /// A peer reporting that the record already exists is included with a zero cost quote. How
+    /// long to wait, and for how many quotes, is set by the `QuoteGatherConfig`.
+    async fn gather_store_costs(
+        &self,
+        record_address: &NetworkAddress,
+        close_nodes: &[PeerId],
+    ) -> Result<Vec<(NetworkAddress, MainPubkey, PaymentQuote)>> {
         let request = Request::Query(Query::GetStoreCost(record_address.clone()));
-        let responses = self
-            .send_and_get_responses(&close_nodes, &request, true)
-            .await;
+        let requests = close_nodes
+            .iter()
+            .map(|peer| self.send_request(request.clone(), *peer))
+            .collect::<Vec<_>>();
+        let responses = gather_store_cost_responses(requests, self.quote_gather_config).await?;
 
         // loop over responses, generating an average fee and storing all responses along side
         let mut all_costs = vec![];
         let mut all_quotes = vec![];
-        for response in responses.into_values().flatten() {
+        for response in responses.into_iter().flatten() {
             debug!(
                 "StoreCostReq for {record_address:?} received response: {:?}",
                 response
@@ -381,6 +443,10 @@ impl Network {
                     payment_address,
                     peer_address,
                 }) => {
+                    if let Err(err) = quote.validate_version(SUPPORTED_QUOTE_VERSIONS) {
+                        warn!("Ignoring the quote from {peer_address:?}: {err}");
+                        continue;
+                    }
                     all_costs.push((peer_address.clone(), payment_address, quote.clone()));
                     all_quotes.push((peer_address, quote));
                 }
@@ -397,7 +463,7 @@ impl Network {
             }
         }
 
-        for peer_id in close_nodes.iter() {
+        for peer_id in close_nodes {
             let request = Request::Cmd(Cmd::QuoteVerification {
                 target: NetworkAddress::from_peer(*peer_id),
                 quotes: all_quotes.clone(),
@@ -406,30 +472,7 @@ impl Network {
             self.send_req_ignore_reply(request, *peer_id);
         }
 
-        // Sort all_costs by the NetworkAddress proximity to record_address
-        all_costs.sort_by(|(peer_address_a, _, _), (peer_address_b, _, _)| {
-            record_address
-                .distance(peer_address_a)
-                .cmp(&record_address.distance(peer_address_b))
-        });
-        #[allow(clippy::mutable_key_type)]
-        let ignore_peers = ignore_peers
-            .into_iter()
-            .map(NetworkAddress::from_peer)
-            .collect::<BTreeSet<_>>();
-
-        // Ensure we dont have any further out nodes than `close_group_majority()`
-        // This should ensure that if we didnt get all responses from close nodes,
-        // we're less likely to be paying a node that is not in the CLOSE_GROUP
-        //
-        // Also filter out the peers.
-        let all_costs = all_costs
-            .into_iter()
-            .filter(|(peer_address, ..)| !ignore_peers.contains(peer_address))
-            .take(close_group_majority())
-            .collect();
-
-        get_fees_from_store_cost_responses(all_costs)
+        Ok(all_costs)
     }
 
     /// Get a record from the network
@@ -894,6 +937,107 @@ fn get_fees_from_store_cost_responses(
     Ok((payee_id, payee.1, payee.2))
 }
 
+/// Await the store cost `requests` until `min_responders` of them have returned a quote, all of
+/// them have returned, or the timeout elapses.
+///
+/// A peer reporting that the record already exists counts as a quote. Errors if fewer than
+/// `min_responders` quotes arrive.
+async fn gather_store_cost_responses<Fut>(
+    requests: Vec<Fut>,
+    config: QuoteGatherConfig,
+) -> Result<Vec<Result<Response>>>
+where
+    Fut: std::future::Future<Output = Result<Response>>,
+{
+    let mut pending = requests.into_iter().collect::<FuturesUnordered<_>>();
+    let mut deadline = Box::pin(sleep(config.timeout));
+    let mut responses = vec![];
+    let mut quotes = 0;
+    while quotes < config.min_responders {
+        match select(pending.next(), &mut deadline).await {
+            Either::Left((Some(response), _)) => {
+                if is_store_cost_response(&response) {
+                    quotes += 1;
+                }
+                responses.push(response);
+            }
+            Either::Left((None, _)) => break,
+            Either::Right(_) => {
+                warn!(
+                    "Timed out after {:?} gathering quotes, with {quotes} received",
+                    config.timeout
+                );
+                break;
+            }
+        }
+    }
+
+    if quotes < config.min_responders {
+        return Err(NetworkError::NotEnoughQuotes {
+            got: quotes,
+            min_responders: config.min_responders,
+        });
+    }
+    Ok(responses)
+}
+
+fn is_store_cost_response(response: &Result<Response>) -> bool {
+    matches!(
+        response,
+        Ok(Response::Query(QueryResponse::GetStoreCost {
+            quote: Ok(_) | Err(ProtocolError::RecordExists(_)),
+            ..
+        }))
+    )
+}
+
+/// Drop the expired quotes, keeping the rest in their original order.
+fn filter_fresh(
+    all_costs: Vec<(NetworkAddress, MainPubkey, PaymentQuote)>,
+) -> Vec<(NetworkAddress, MainPubkey, PaymentQuote)> {
+    all_costs
+        .into_iter()
+        .filter(|(_, _, quote)| !quote.has_expired())
+        .collect()
+}
+
+/// Gather store costs with `gather`, re-quoting with a doubling backoff while every quote gathered
+/// has expired, so that stale prices are never paid.
+///
+/// An empty gather is returned as is, leaving the lack of responses to be reported by the caller.
+async fn gather_fresh_store_costs<F, Fut>(
+    mut gather: F,
+    max_requotes: usize,
+    initial_backoff: Duration,
+) -> Result<Vec<(NetworkAddress, MainPubkey, PaymentQuote)>>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<(NetworkAddress, MainPubkey, PaymentQuote)>>>,
+{
+    let mut backoff = initial_backoff;
+    for attempt in 0..=max_requotes {
+        if attempt > 0 {
+            sleep(backoff).await;
+            backoff *= 2;
+        }
+
+        let all_costs = gather().await?;
+        if all_costs.is_empty() {
+            return Ok(all_costs);
+        }
+
+        let fresh_costs = filter_fresh(all_costs);
+        if !fresh_costs.is_empty() {
+            return Ok(fresh_costs);
+        }
+        warn!("All quotes gathered have expired, on attempt {attempt}");
+    }
+
+    Err(NetworkError::AllQuotesExpired {
+        attempts: max_requotes + 1,
+    })
+}
+
 /// Get the value of the provided Quorum
 pub fn get_quorum_value(quorum: &Quorum) -> usize {
     match quorum {
@@ -904,6 +1048,16 @@ pub fn get_quorum_value(quorum: &Quorum) -> usize {
     }
 }
 
+/// Returns the members of the `expected` close group that haven't acknowledged a store, in the
+/// order they were expected, so that only those need to be retried.
+pub fn missing_acks(expected: &[PeerId], acked: &HashSet<PeerId>) -> Vec<PeerId> {
+    expected
+        .iter()
+        .filter(|peer| !acked.contains(peer))
+        .copied()
+        .collect()
+}
+
 /// Verifies if `Multiaddr` contains IPv4 address that is not global.
 /// This is used to filter out unroutable addresses from the Kademlia routing table.
 pub fn multiaddr_is_global(multiaddr: &Multiaddr) -> bool {
@@ -1045,6 +1199,122 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_gather_fresh_store_costs_requotes_when_all_quotes_expired() -> eyre::Result<()> {
+        let make_costs = |age_secs: u64| {
+            (1..=3)
+                .map(|i| {
+                    let mut quote =
+                        PaymentQuote::test_dummy(Default::default(), NanoTokens::from(i));
+                    quote.timestamp = std::time::SystemTime::now() - Duration::from_secs(age_secs);
+                    (
+                        NetworkAddress::from_peer(PeerId::random()),
+                        MainPubkey::new(bls::SecretKey::random().public_key()),
+                        quote,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut gathers = 0;
+        let all_costs = gather_fresh_store_costs(
+            || {
+                gathers += 1;
+                let age_secs = if gathers == 1 {
+                    sn_transfers::QUOTE_EXPIRATION_SECS + 60
+                } else {
+                    0
+                };
+                let costs = make_costs(age_secs);
+                async move { Ok(costs) }
+            },
+            MAX_REQUOTE_ATTEMPTS,
+            Duration::from_millis(10),
+        )
+        .await?;
+        assert_eq!(gathers, 2);
+        assert_eq!(all_costs.len(), 3);
+        assert!(all_costs.iter().all(|(_, _, quote)| !quote.has_expired()));
+
+        let result = gather_fresh_store_costs(
+            || {
+                let costs = make_costs(sn_transfers::QUOTE_EXPIRATION_SECS + 60);
+                async move { Ok(costs) }
+            },
+            2,
+            Duration::from_millis(10),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(NetworkError::AllQuotesExpired { attempts: 3 })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gather_store_cost_responses_returns_at_min_responders() -> eyre::Result<()> {
+        let responder = |delay_ms: u64| async move {
+            sleep(Duration::from_millis(delay_ms)).await;
+            Ok(Response::Query(QueryResponse::GetStoreCost {
+                quote: Ok(PaymentQuote::test_dummy(
+                    Default::default(),
+                    NanoTokens::from(1),
+                )),
+                payment_address: MainPubkey::new(bls::SecretKey::random().public_key()),
+                peer_address: NetworkAddress::from_peer(PeerId::random()),
+            }))
+        };
+        let config = QuoteGatherConfig {
+            timeout: Duration::from_secs(10),
+            min_responders: 3,
+        };
+
+        let start = std::time::Instant::now();
+        let responses = gather_store_cost_responses(
+            [10, 20, 30, 5_000, 5_000]
+                .into_iter()
+                .map(responder)
+                .collect(),
+            config,
+        )
+        .await?;
+        assert_eq!(responses.len(), 3);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let result = gather_store_cost_responses(
+            [10, 20, 5_000].into_iter().map(responder).collect(),
+            QuoteGatherConfig {
+                timeout: Duration::from_millis(200),
+                ..config
+            },
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(NetworkError::NotEnoughQuotes {
+                got: 2,
+                min_responders: 3
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_acks() {
+        let expected = (0..CLOSE_GROUP_SIZE)
+            .map(|_| PeerId::random())
+            .collect::<Vec<_>>();
+        let acked = HashSet::from([expected[0], expected[2], PeerId::random()]);
+
+        let mut missing = expected.clone();
+        missing.retain(|peer| *peer != expected[0] && *peer != expected[2]);
+        assert_eq!(missing_acks(&expected, &acked), missing);
+        assert!(missing_acks(&expected, &expected.iter().copied().collect()).is_empty());
+    }
+
     #[test]
     fn test_network_sign_verify() -> eyre::Result<()> {
         let (network, _, _) =
@@ -0,0 +1,62 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_node::RunningNode;
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, error, info};
+
+/// Start a minimal HTTP health endpoint, for load balancers that only need a status code.
+///
+/// Any request gets a 200 while the node is connected to at least one peer, and a 503 otherwise.
+pub(crate) fn start_health_service(addr: SocketAddr, running_node: RunningNode) {
+    let _handle = tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Failed to start the health endpoint on {addr}: {err}");
+                return;
+            }
+        };
+        info!("Health endpoint on http://{addr}");
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let running_node = running_node.clone();
+                    let _handle = tokio::spawn(respond(stream, running_node));
+                }
+                Err(err) => debug!("Failed to accept a health check connection: {err}"),
+            }
+        }
+    });
+}
+
+async fn respond(mut stream: TcpStream, running_node: RunningNode) {
+    // The request itself is irrelevant, but it's read so the client sees a clean response.
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request).await;
+
+    let is_healthy = match running_node.get_swarm_local_state().await {
+        Ok(state) => !state.connected_peers.is_empty(),
+        Err(err) => {
+            debug!("Failed to obtain the node state for a health check: {err}");
+            false
+        }
+    };
+    let response: &[u8] = if is_healthy {
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK"
+    } else {
+        b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 11\r\nConnection: close\r\n\r\nUnavailable"
+    };
+    if let Err(err) = stream.write_all(response).await {
+        debug!("Failed to respond to a health check: {err}");
+    }
+}
@@ -9,15 +9,17 @@
 #[macro_use]
 extern crate tracing;
 
+mod health_service;
 mod rpc_service;
 
+use chrono::NaiveTime;
 use clap::Parser;
 use eyre::{eyre, Result};
 use libp2p::{identity::Keypair, PeerId};
 #[cfg(feature = "metrics")]
 use sn_logging::metrics::init_metrics;
 use sn_logging::{Level, LogFormat, LogOutputDest, ReloadHandle};
-use sn_node::{Marker, NodeBuilder, NodeEvent, NodeEventsReceiver};
+use sn_node::{MaintenanceWindow, Marker, NodeBuilder, NodeEvent, NodeEventsReceiver};
 use sn_peers_acquisition::PeersArgs;
 use sn_protocol::{node::get_safenode_root_dir, node_rpc::NodeCtrl};
 use std::{
@@ -62,6 +64,11 @@ pub fn parse_log_output(val: &str) -> Result<LogOutputDestArg> {
     }
 }
 
+pub fn parse_time_of_day(val: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(val, "%H:%M")
+        .map_err(|err| eyre!("The time '{val}' must be given as HH:MM: {err}"))
+}
+
 // Please do not remove the blank lines in these doc comments.
 // They are used for inserting line breaks when the help menu is rendered in the UI.
 #[derive(Parser, Debug)]
@@ -159,6 +166,28 @@ struct Opt {
     #[clap(long)]
     owner: Option<String>,
 
+    /// Specify the port for a minimal health endpoint.
+    ///
+    /// It responds with 200 while the node is connected to peers and 503 otherwise, for load
+    /// balancers that don't need the full metrics.
+    #[clap(long)]
+    health_port: Option<u16>,
+
+    /// Specify the start of a daily maintenance window, in the host's local time as HH:MM.
+    ///
+    /// Periodic replication is paused from --maintenance-start until --maintenance-end. A window
+    /// that ends before it starts spans midnight.
+    #[clap(long, requires = "maintenance_end", value_parser = parse_time_of_day)]
+    maintenance_start: Option<NaiveTime>,
+
+    /// Specify the end of the daily maintenance window, in the host's local time as HH:MM.
+    #[clap(long, requires = "maintenance_start", value_parser = parse_time_of_day)]
+    maintenance_end: Option<NaiveTime>,
+
+    /// Specify the number of connected peers below which the node redials its bootstrap peers.
+    #[clap(long)]
+    min_connections: Option<usize>,
+
     #[cfg(feature = "open-metrics")]
     /// Specify the port for the OpenMetrics server.
     ///
@@ -184,6 +213,12 @@ fn main() -> Result<()> {
     let opt = Opt::parse();
 
     let node_socket_addr = SocketAddr::new(opt.ip, opt.port);
+    let maintenance_window = match (opt.maintenance_start, opt.maintenance_end) {
+        (Some(start), Some(end)) => Some(MaintenanceWindow::new(start, end).ok_or_else(|| {
+            eyre!("The maintenance window cannot start and end at the same time ({start})")
+        })?),
+        _ => None,
+    };
     let (root_dir, keypair) = get_root_dir_and_keypair(&opt.root_dir)?;
 
     let (log_output_dest, log_reload_handle, _log_appender_guard) =
@@ -222,6 +257,8 @@ fn main() -> Result<()> {
             opt.upnp,
         );
         node_builder.is_behind_home_network = opt.home_network;
+        node_builder.maintenance_window(maintenance_window);
+        node_builder.min_connections(opt.min_connections);
         #[cfg(feature = "open-metrics")]
         let mut node_builder = node_builder;
         // if enable flag is provided or only if the port is specified then enable the server by setting Some()
@@ -233,8 +270,14 @@ fn main() -> Result<()> {
         };
         #[cfg(feature = "open-metrics")]
         node_builder.metrics_server_port(metrics_server_port);
-        let restart_options =
-            run_node(node_builder, opt.rpc, &log_output_dest, log_reload_handle).await?;
+        let restart_options = run_node(
+            node_builder,
+            opt.rpc,
+            opt.health_port,
+            &log_output_dest,
+            log_reload_handle,
+        )
+        .await?;
 
         Ok::<_, eyre::Report>(restart_options)
     })?;
@@ -257,6 +300,7 @@ fn main() -> Result<()> {
 async fn run_node(
     node_builder: NodeBuilder,
     rpc: Option<SocketAddr>,
+    health_port: Option<u16>,
     log_output_dest: &str,
     log_reload_handle: ReloadHandle,
 ) -> Result<Option<(PathBuf, u16)>> {
@@ -307,6 +351,13 @@ You can check your reward balance by running:
         }
     });
 
+    if let Some(port) = health_port {
+        health_service::start_health_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            running_node.clone(),
+        );
+    }
+
     // Start up gRPC interface if enabled by user
     if let Some(addr) = rpc {
         rpc_service::start_rpc_service(
@@ -337,6 +388,9 @@ You can check your reward balance by running:
                 let msg = format!("Node is restarting in {delay:?}...");
                 info!("{msg}");
                 println!("{msg} Node path: {log_output_dest}");
+                if let Err(err) = running_node.shutdown(delay) {
+                    warn!("Failed to ask the node to process its queued events: {err}");
+                }
                 sleep(delay).await;
 
                 break Ok(res);
@@ -345,6 +399,9 @@ You can check your reward balance by running:
                 let msg = format!("Node is stopping in {delay:?}...");
                 info!("{msg}");
                 println!("{msg} Node log path: {log_output_dest}");
+                if let Err(err) = running_node.shutdown(delay) {
+                    warn!("Failed to ask the node to process its queued events: {err}");
+                }
                 sleep(delay).await;
                 return Err(cause);
             }
@@ -0,0 +1,108 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::future::Future;
+use tokio::{
+    sync::mpsc,
+    time::{timeout_at, Duration, Instant},
+};
+
+/// The outcome of draining a queue on shutdown.
+#[derive(Debug)]
+pub(crate) struct DrainReport<T> {
+    /// How many of the queued items were processed.
+    pub(crate) processed: usize,
+    /// The items still queued when the timeout elapsed.
+    pub(crate) undrained: Vec<T>,
+}
+
+/// Stop accepting new items on the queue, then process the items already in it until it's empty
+/// or the timeout elapses.
+///
+/// An item still being processed when the timeout elapses is abandoned, and is neither counted as
+/// processed nor returned as undrained.
+pub(crate) async fn drain_queue<T, F, Fut>(
+    receiver: &mut mpsc::Receiver<T>,
+    timeout: Duration,
+    mut process: F,
+) -> DrainReport<T>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    receiver.close();
+    let deadline = Instant::now() + timeout;
+
+    let mut processed = 0;
+    while Instant::now() < deadline {
+        let Ok(item) = receiver.try_recv() else {
+            break;
+        };
+        if timeout_at(deadline, process(item)).await.is_err() {
+            warn!("Timed out processing a queued item while draining the queue");
+            break;
+        }
+        processed += 1;
+    }
+
+    let mut undrained = vec![];
+    while let Ok(item) = receiver.try_recv() {
+        undrained.push(item);
+    }
+    DrainReport {
+        processed,
+        undrained,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drain_queue_should_process_every_queued_item_and_refuse_new_ones() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        for i in 0..5 {
+            sender.send(i).await.expect("the queue to be open");
+        }
+
+        let mut processed = vec![];
+        let report = drain_queue(&mut receiver, Duration::from_secs(10), |item| {
+            processed.push(item);
+            async {}
+        })
+        .await;
+
+        assert_eq!(processed, vec![0, 1, 2, 3, 4]);
+        assert_eq!(report.processed, 5);
+        assert!(report.undrained.is_empty());
+        assert!(sender.send(5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn drain_queue_should_report_the_items_left_when_the_timeout_elapses() {
+        let (sender, mut receiver) = mpsc::channel(10);
+        for i in 0..5 {
+            sender.send(i).await.expect("the queue to be open");
+        }
+
+        let report = drain_queue(
+            &mut receiver,
+            Duration::from_millis(100),
+            |item| async move {
+                if item == 1 {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(report.processed, 1);
+        assert_eq!(report.undrained, vec![2, 3, 4]);
+    }
+}
@@ -9,6 +9,7 @@
 use sn_protocol::{NetworkAddress, PrettyPrintRecordKey};
 use sn_transfers::{NanoTokens, WalletError};
 use thiserror::Error;
+use xor_name::XorName;
 
 pub(super) type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -49,6 +50,9 @@ pub enum Error {
     // The Record::key must match with the one that is derived from the Record::value
     #[error("The Record::key does not match with the key derived from Record::value")]
     RecordKeyMismatch,
+    /// The name of a Chunk must be the hash of its content
+    #[error("The Chunk name {0:?} does not match the hash of its content")]
+    ChunkNameMismatch(XorName),
 
     // ---------- Payment Errors
     #[error("The content of the payment quote is invalid")]
@@ -27,9 +27,11 @@
 #[macro_use]
 extern crate tracing;
 
+mod drain;
 mod error;
 mod event;
 mod log_markers;
+mod maintenance;
 #[cfg(feature = "open-metrics")]
 mod metrics;
 mod node;
@@ -40,6 +42,7 @@ mod replication;
 pub use self::{
     event::{NodeEvent, NodeEventsChannel, NodeEventsReceiver},
     log_markers::Marker,
+    maintenance::MaintenanceWindow,
     node::{NodeBuilder, NodeCmd, PERIODIC_REPLICATION_INTERVAL_MAX_S},
 };
 
@@ -52,6 +55,7 @@ use sn_transfers::{HotWallet, NanoTokens};
 use std::{
     collections::{BTreeMap, HashSet},
     path::PathBuf,
+    time::Duration,
 };
 use tokio::sync::broadcast;
 
@@ -61,7 +65,6 @@ use tokio::sync::broadcast;
 pub struct RunningNode {
     network: Network,
     node_events_channel: NodeEventsChannel,
-    #[allow(dead_code)]
     node_cmds: broadcast::Sender<NodeCmd>,
 }
 
@@ -111,6 +114,16 @@ impl RunningNode {
         &self.node_events_channel
     }
 
+    /// Ask the node to shut down, processing the network events already queued for up to
+    /// `timeout` rather than dropping them. Events received after this are not processed.
+    pub fn shutdown(&self, timeout: Duration) -> Result<()> {
+        let _ = self
+            .node_cmds
+            .send(NodeCmd::Shutdown { timeout })
+            .map_err(|err| Error::NodeCmdFailed(err.to_string()))?;
+        Ok(())
+    }
+
     /// Returns the list of all the RecordKeys held by the node
     pub async fn get_all_record_addresses(&self) -> Result<HashSet<NetworkAddress>> {
         #[allow(clippy::mutable_key_type)] // for Bytes in NetworkAddress
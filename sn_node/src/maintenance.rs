@@ -0,0 +1,77 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use chrono::{Local, NaiveTime};
+
+/// A daily window, in the host's local time, during which the node pauses its periodic
+/// replication so the host can be maintained.
+///
+/// A window whose end is before its start spans midnight, e.g., 23:00 to 01:00.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl MaintenanceWindow {
+    /// Returns `None` if the window would be empty, i.e., it starts and ends at the same time.
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Option<Self> {
+        (start != end).then_some(Self { start, end })
+    }
+
+    /// Whether `time` falls within the window. The start is inclusive and the end exclusive.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Whether the host's local time is currently within the window.
+    pub fn is_active(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).expect("a valid time")
+    }
+
+    #[test]
+    fn an_empty_window_is_rejected() {
+        assert_eq!(MaintenanceWindow::new(time(2, 0), time(2, 0)), None);
+    }
+
+    #[test]
+    fn a_window_within_a_day_contains_only_its_times() {
+        let window = MaintenanceWindow::new(time(2, 0), time(4, 0)).expect("a valid window");
+
+        assert!(!window.contains(time(1, 59)));
+        assert!(window.contains(time(2, 0)));
+        assert!(window.contains(time(3, 30)));
+        assert!(!window.contains(time(4, 0)));
+        assert!(!window.contains(time(23, 0)));
+    }
+
+    #[test]
+    fn a_window_spanning_midnight_contains_the_times_either_side_of_it() {
+        let window = MaintenanceWindow::new(time(23, 0), time(1, 0)).expect("a valid window");
+
+        assert!(window.contains(time(23, 0)));
+        assert!(window.contains(time(0, 0)));
+        assert!(window.contains(time(0, 59)));
+        assert!(!window.contains(time(1, 0)));
+        assert!(!window.contains(time(12, 0)));
+        assert!(!window.contains(time(22, 59)));
+    }
+}
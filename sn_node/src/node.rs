@@ -7,8 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
+    drain::drain_queue,
     error::{Error, Result},
     event::NodeEventsChannel,
+    maintenance::MaintenanceWindow,
     quote::quotes_verification,
     Marker, NodeEvent,
 };
@@ -83,6 +85,10 @@ pub struct NodeBuilder {
     owner: Option<String>,
     #[cfg(feature = "upnp")]
     upnp: bool,
+    /// Pause periodic replication during this daily window.
+    maintenance_window: Option<MaintenanceWindow>,
+    /// Redial the initial peers when the routing table drops below this many peers.
+    min_connections: Option<usize>,
 }
 
 impl NodeBuilder {
@@ -108,9 +114,21 @@ impl NodeBuilder {
             owner,
             #[cfg(feature = "upnp")]
             upnp,
+            maintenance_window: None,
+            min_connections: None,
         }
     }
 
+    /// Set a daily window during which periodic replication is paused. Defaults to none.
+    pub fn maintenance_window(&mut self, window: Option<MaintenanceWindow>) {
+        self.maintenance_window = window;
+    }
+
+    /// Set the number of peers below which the node redials its initial peers. Defaults to none.
+    pub fn min_connections(&mut self, min_connections: Option<usize>) {
+        self.min_connections = min_connections;
+    }
+
     #[cfg(feature = "open-metrics")]
     /// Set the port for the OpenMetrics server. Defaults to a random port if not set
     pub fn metrics_server_port(&mut self, port: Option<u16>) {
@@ -179,6 +197,8 @@ impl NodeBuilder {
             #[cfg(feature = "open-metrics")]
             node_metrics,
             owner: self.owner.clone(),
+            maintenance_window: self.maintenance_window,
+            min_connections: self.min_connections,
         };
         let running_node = RunningNode {
             network,
@@ -195,7 +215,13 @@ impl NodeBuilder {
 
 /// Commands that can be sent by the user to the Node instance, e.g. to mutate some settings.
 #[derive(Clone, Debug)]
-pub enum NodeCmd {}
+pub enum NodeCmd {
+    /// Stop handling network events, after processing those already queued for up to `timeout`.
+    Shutdown {
+        /// How long to spend processing the queued events.
+        timeout: Duration,
+    },
+}
 
 /// `Node` represents a single node in the distributed network. It handles
 /// network events, processes incoming requests, interacts with the data
@@ -214,6 +240,10 @@ pub(crate) struct Node {
     /// node owner's discord username, in readable format
     /// if not set, there will be no payment forward to be undertaken
     owner: Option<String>,
+    /// periodic replication is skipped while the host is in this window
+    maintenance_window: Option<MaintenanceWindow>,
+    /// the initial peers are redialed when the routing table drops below this many peers
+    min_connections: Option<usize>,
 }
 
 impl Node {
@@ -287,7 +317,7 @@ impl Node {
                                 let start = std::time::Instant::now();
                                 let event_string = format!("{event:?}");
 
-                                self.handle_network_event(event, peers_connected);
+                                let _handles = self.handle_network_event(event, peers_connected);
                                 trace!("Handled non-blocking network event in {:?}: {:?}", start.elapsed(), event_string);
 
                             }
@@ -300,6 +330,10 @@ impl Node {
                     }
                     // runs every replication_interval time
                     _ = replication_interval.tick() => {
+                        if self.maintenance_window.is_some_and(|window| window.is_active()) {
+                            trace!("Periodic replication skipped during the maintenance window");
+                            continue;
+                        }
                         let start = std::time::Instant::now();
                         trace!("Periodic replication triggered");
                         let network = self.network.clone();
@@ -354,8 +388,28 @@ impl Node {
                     }
                     node_cmd = cmds_receiver.recv() => {
                         match node_cmd {
-                            Ok(cmd) => {
-                                info!("{cmd:?} received... unhandled")
+                            Ok(NodeCmd::Shutdown { timeout }) => {
+                                info!("Shutting down, processing the queued network events for up to {timeout:?}");
+                                let report = drain_queue(&mut network_event_receiver, timeout, |event| {
+                                    let handles = self.handle_network_event(event, peers_connected);
+                                    async move {
+                                        for handle in handles {
+                                            if let Err(err) = handle.await {
+                                                error!("A queued network event failed to be handled: {err:?}");
+                                            }
+                                        }
+                                    }
+                                })
+                                .await;
+                                info!("Processed {} queued network events before shutting down", report.processed);
+                                if !report.undrained.is_empty() {
+                                    warn!(
+                                        "{} queued network events were not processed before shutting down: {:?}",
+                                        report.undrained.len(),
+                                        report.undrained
+                                    );
+                                }
+                                break;
                             }
                             Err(err) => error!("When trying to read from the NodeCmds channel/receiver: {err:?}")
                         }
@@ -377,12 +431,30 @@ impl Node {
 
     // **** Private helpers *****
 
+    /// Dial each of the peers the node was started with, in a spawned task whose handle is returned.
+    fn dial_initial_peers(&self) -> JoinHandle<()> {
+        let network = self.network.clone();
+        let peers = self.initial_peers.clone();
+        spawn(async move {
+            for addr in &*peers {
+                if let Err(err) = network.dial(addr.clone()).await {
+                    tracing::error!("Failed to dial {addr}: {err:?}");
+                };
+            }
+        })
+    }
+
     /// Handle a network event.
-    /// Spawns a thread for any likely long running tasks
-    fn handle_network_event(&self, event: NetworkEvent, peers_connected: &Arc<AtomicUsize>) {
+    /// Spawns a thread for any likely long running tasks, returning their handles.
+    fn handle_network_event(
+        &self,
+        event: NetworkEvent,
+        peers_connected: &Arc<AtomicUsize>,
+    ) -> Vec<JoinHandle<()>> {
         let start = std::time::Instant::now();
         let event_string = format!("{event:?}");
         let event_header;
+        let mut handles = vec![];
         trace!("Handling NetworkEvent {event_string:?}");
 
         match event {
@@ -400,20 +472,28 @@ impl Node {
                 // try replication here
                 let net_clone = self.network.clone();
                 self.record_metrics(Marker::IntervalReplicationTriggered);
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     Self::try_interval_replication(net_clone);
-                });
+                }));
             }
             NetworkEvent::PeerRemoved(peer_id, connected_peers) => {
                 event_header = "PeerRemoved";
                 self.record_metrics(Marker::PeersInRoutingTable(connected_peers));
                 self.record_metrics(Marker::PeerRemovedFromRoutingTable(peer_id));
 
+                if self
+                    .min_connections
+                    .is_some_and(|min_connections| connected_peers < min_connections)
+                {
+                    info!("Connected to {connected_peers} peers, below the minimum, so redialing the initial peers");
+                    handles.push(self.dial_initial_peers());
+                }
+
                 let net = self.network.clone();
                 self.record_metrics(Marker::IntervalReplicationTriggered);
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     Self::try_interval_replication(net);
-                });
+                }));
             }
             NetworkEvent::PeerWithUnsupportedProtocol { .. } => {
                 event_header = "PeerWithUnsupportedProtocol";
@@ -431,22 +511,14 @@ impl Node {
                 });
 
                 let network = self.network.clone();
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     network.send_req_ignore_reply(request, bad_peer);
-                });
+                }));
             }
             NetworkEvent::NewListenAddr(_) => {
                 event_header = "NewListenAddr";
                 if !cfg!(feature = "local-discovery") {
-                    let network = self.network.clone();
-                    let peers = self.initial_peers.clone();
-                    let _handle = spawn(async move {
-                        for addr in &*peers {
-                            if let Err(err) = network.dial(addr.clone()).await {
-                                tracing::error!("Failed to dial {addr}: {err:?}");
-                            };
-                        }
-                    });
+                    handles.push(self.dial_initial_peers());
                 }
             }
             NetworkEvent::ResponseReceived { res } => {
@@ -470,18 +542,18 @@ impl Node {
                 let network = self.network.clone();
                 let payment_address = *self.reward_address;
 
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     let res = Self::handle_query(&network, query, payment_address).await;
                     trace!("Sending response {res:?}");
 
                     network.send_response(res, channel);
-                });
+                }));
             }
             NetworkEvent::UnverifiedRecord(record) => {
                 event_header = "UnverifiedRecord";
                 // queries can be long running and require validation, so we spawn a task to handle them
                 let self_clone = self.clone();
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     let key = PrettyPrintRecordKey::from(&record.key).into_owned();
                     match self_clone.validate_and_store_record(record).await {
                         Ok(cmdok) => trace!("UnverifiedRecord {key} stored with {cmdok:?}."),
@@ -489,7 +561,7 @@ impl Node {
                             self_clone.record_metrics(Marker::RecordRejected(&key, &err));
                         }
                     }
-                });
+                }));
             }
 
             NetworkEvent::TerminateNode { reason } => {
@@ -505,30 +577,30 @@ impl Node {
                 //       any change to the keyword `failed to fetch` shall incur
                 //       correspondent CI script change as well.
                 error!("Received notification from replication_fetcher, notifying {bad_nodes:?} failed to fetch replication copies from.");
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     for peer_id in bad_nodes {
                         network.record_node_issues(peer_id, NodeIssue::ReplicationFailure);
                     }
-                });
+                }));
             }
             NetworkEvent::BadNodeVerification { peer_id } => {
                 event_header = "BadNodeVerification";
                 let network = self.network.clone();
 
                 trace!("Need to verify whether peer {peer_id:?} is a bad node");
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     if Self::close_nodes_shunning_peer(&network, peer_id).await {
                         network.record_node_issues(peer_id, NodeIssue::CloseNodesShunning);
                     }
-                });
+                }));
             }
             NetworkEvent::QuoteVerification { quotes } => {
                 event_header = "QuoteVerification";
                 let network = self.network.clone();
 
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     quotes_verification(&network, quotes).await;
-                });
+                }));
             }
             NetworkEvent::ChunkProofVerification {
                 peer_id,
@@ -539,7 +611,7 @@ impl Node {
 
                 trace!("Going to verify chunk {keys_to_verify:?} against peer {peer_id:?}");
 
-                let _handle = spawn(async move {
+                handles.push(spawn(async move {
                     // To avoid the peer is in the process of getting the copy via replication,
                     // repeat the verification for couple of times (in case of error).
                     // Only report the node as bad when ALL the verification attempts failed.
@@ -560,7 +632,7 @@ impl Node {
                     // As the chunk_proof_check will be triggered every periodical replication,
                     // a low performed or cheaty peer will raise multiple issue alerts during it.
                     network.record_node_issues(peer_id, NodeIssue::FailedChunkProofCheck);
-                });
+                }));
             }
         }
 
@@ -568,6 +640,7 @@ impl Node {
             "Network handling statistics, Event {event_header:?} handled in {:?} : {event_string:?}",
             start.elapsed()
         );
+        handles
     }
 
     // Query close_group peers to the target to verifify whether the target is bad_node
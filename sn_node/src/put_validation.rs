@@ -8,6 +8,7 @@
 
 use crate::{node::Node, quote::verify_quote_for_storecost, Error, Marker, Result};
 use libp2p::kad::{Record, RecordKey};
+use serde::de::DeserializeOwned;
 use sn_networking::{get_raw_signed_spends_from_record, GetRecordError, NetworkError};
 use sn_protocol::{
     messages::CmdOk,
@@ -20,7 +21,7 @@ use sn_protocol::{
 use sn_registers::SignedRegister;
 use sn_transfers::{
     calculate_royalties_fee, CashNote, CashNoteRedemption, HotWallet, NanoTokens, Payment,
-    SignedSpend, Transfer, UniquePubkey, WalletError, NETWORK_ROYALTIES_PK,
+    PaymentEnvelope, SignedSpend, Transfer, UniquePubkey, WalletError, NETWORK_ROYALTIES_PK,
 };
 use std::collections::BTreeSet;
 use tokio::task::JoinSet;
@@ -39,7 +40,7 @@ impl Node {
         match record_header.kind {
             RecordKind::ChunkWithPayment => {
                 let record_key = record.key.clone();
-                let (payment, chunk) = try_deserialize_record::<(Payment, Chunk)>(&record)?;
+                let (payment, chunk) = try_deserialize_record_with_payment::<Chunk>(&record)?;
                 let already_exists = self
                     .validate_key_and_existence(&chunk.network_address(), &record_key)
                     .await?;
@@ -125,7 +126,7 @@ impl Node {
             }
             RecordKind::RegisterWithPayment => {
                 let (payment, register) =
-                    try_deserialize_record::<(Payment, SignedRegister)>(&record)?;
+                    try_deserialize_record_with_payment::<SignedRegister>(&record)?;
 
                 // check if the deserialized value's RegisterAddress matches the record's key
                 let net_addr = NetworkAddress::from_register_address(*register.address());
@@ -252,6 +253,10 @@ impl Node {
     /// Store a `Chunk` to the RecordStore
     pub(crate) fn store_chunk(&self, chunk: &Chunk) -> Result<CmdOk> {
         let chunk_name = *chunk.name();
+        if !chunk.verify_name() {
+            warn!("Rejecting chunk whose name {chunk_name:?} does not match its content");
+            return Err(Error::ChunkNameMismatch(chunk_name));
+        }
         let chunk_addr = *chunk.address();
 
         let key = NetworkAddress::from_chunk_address(*chunk.address()).to_record_key();
@@ -479,7 +484,7 @@ impl Node {
     async fn payment_for_us_exists_and_is_still_valid(
         &self,
         address: &NetworkAddress,
-        payment: Payment,
+        payment: PaymentEnvelope,
     ) -> Result<()> {
         let key = address.to_record_key();
         let pretty_key = PrettyPrintRecordKey::from(&key).into_owned();
@@ -489,6 +494,9 @@ impl Node {
         let mut wallet = HotWallet::load_from(&self.network.root_dir_path)?;
         let old_balance = wallet.balance().as_nano();
 
+        // a payment encrypted to us can only be read with our key
+        let payment = wallet.open_payment(payment)?;
+
         // unpack transfer
         trace!("Unpacking incoming Transfers for record {pretty_key}");
         let (received_fee, cash_notes, royalties_cash_notes_r) = self
@@ -739,3 +747,15 @@ where
 
     Ok(received_fee)
 }
+
+// Helper to deserialize a record carrying a payment. Clients seal the payment in a
+// `PaymentEnvelope`; a plain `Payment` from an older client is still accepted.
+fn try_deserialize_record_with_payment<T: DeserializeOwned>(
+    record: &Record,
+) -> Result<(PaymentEnvelope, T)> {
+    if let Ok(sealed) = try_deserialize_record::<(PaymentEnvelope, T)>(record) {
+        return Ok(sealed);
+    }
+    let (payment, value) = try_deserialize_record::<(Payment, T)>(record)?;
+    Ok((PaymentEnvelope::Plain(payment), value))
+}
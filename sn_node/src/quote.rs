@@ -10,7 +10,9 @@ use crate::{node::Node, Error, Result};
 use libp2p::PeerId;
 use sn_networking::{calculate_cost_for_records, Network, NodeIssue};
 use sn_protocol::{error::Error as ProtocolError, storage::ChunkAddress, NetworkAddress};
-use sn_transfers::{NanoTokens, PaymentQuote, QuotingMetrics};
+use sn_transfers::{
+    NanoTokens, PaymentQuote, QuotingMetrics, QUOTE_PROTOCOL_VERSION, SUPPORTED_QUOTE_VERSIONS,
+};
 use std::time::Duration;
 
 impl Node {
@@ -22,7 +24,13 @@ impl Node {
     ) -> Result<PaymentQuote, ProtocolError> {
         let content = address.as_xorname().unwrap_or_default();
         let timestamp = std::time::SystemTime::now();
-        let bytes = PaymentQuote::bytes_for_signing(content, cost, timestamp, quoting_metrics);
+        let bytes = PaymentQuote::bytes_for_signing(
+            content,
+            cost,
+            timestamp,
+            quoting_metrics,
+            QUOTE_PROTOCOL_VERSION,
+        );
 
         let Ok(signature) = network.sign(&bytes) else {
             return Err(ProtocolError::QuoteGenerationFailed);
@@ -35,6 +43,7 @@ impl Node {
             quoting_metrics: quoting_metrics.clone(),
             pub_key: network.get_pub_key(),
             signature,
+            version: QUOTE_PROTOCOL_VERSION,
         };
 
         debug!("Created payment quote for {address:?}: {quote:?}");
@@ -59,12 +68,16 @@ pub(crate) fn verify_quote_for_storecost(
         return Err(Error::QuoteExpired(address.clone()));
     }
 
+    // check the quote is in a format we can interpret
+    quote.validate_version(SUPPORTED_QUOTE_VERSIONS)?;
+
     // check sig
     let bytes = PaymentQuote::bytes_for_signing(
         quote.content,
         quote.cost,
         quote.timestamp,
         &quote.quoting_metrics,
+        quote.version,
     );
     let signature = quote.signature;
     if !network.verify(&bytes, &signature) {
@@ -6,23 +6,95 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use chrono::NaiveTime;
 use color_eyre::{eyre::eyre, Result};
 use libp2p::Multiaddr;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use service_manager::{ServiceInstallCtx, ServiceLabel};
 use sn_logging::LogFormat;
+use sn_service_management::{
+    node::{with_log_level, EPHEMERAL_ROOT_DIR_NAME},
+    unit::{
+        add_unit_directives, cpu_quota_directive, env_file_directive, memory_max_directive,
+        ByteSize,
+    },
+};
+use sn_transfers::NanoTokens;
 use std::{
     ffi::OsString,
+    fmt,
     net::{Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    ops::RangeInclusive,
+    path::{Component, PathBuf},
     str::FromStr,
 };
+use thiserror::Error;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PortRange {
     Single(u16),
     Range(u16, u16),
 }
 
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortRange::Single(port) => write!(f, "{port}"),
+            PortRange::Range(start, end) => write!(f, "{start}-{end}"),
+        }
+    }
+}
+
+// A port range is (de)serialized in the same form it's given on the command line, e.g.,
+// "8080-8090".
+impl Serialize for PortRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PortRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_port_range(&s).map_err(DeserializeError::custom)
+    }
+}
+
+impl PortRange {
+    /// Describe the firewall rules required to open this port range for the given protocol.
+    ///
+    /// The rules are data only; rendering them for a particular firewall, e.g., `ufw` or
+    /// `iptables`, is left to the caller.
+    pub fn firewall_rules(&self, protocol: Protocol) -> Vec<FirewallRule> {
+        vec![FirewallRule {
+            protocol,
+            ports: self.clone(),
+        }]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Tcp => write!(f, "tcp"),
+            Protocol::Udp => write!(f, "udp"),
+        }
+    }
+}
+
+/// A rule allowing inbound traffic on a port, or a range of ports, for a protocol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirewallRule {
+    pub protocol: Protocol,
+    pub ports: PortRange,
+}
+
 pub fn parse_port_range(s: &str) -> Result<PortRange> {
     if let Ok(port) = u16::from_str(s) {
         Ok(PortRange::Single(port))
@@ -40,35 +112,219 @@ pub fn parse_port_range(s: &str) -> Result<PortRange> {
     }
 }
 
+/// Sort the peers by their byte representation and remove any duplicates, so the same set of peers
+/// always produces the same service definition.
+pub fn canonicalize_peers(peers: &mut Vec<Multiaddr>) {
+    peers.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    peers.dedup();
+}
+
+/// The range of connection targets a node can be asked to maintain. Fewer than one is meaningless,
+/// and far more than the routing table holds would keep the node dialling forever.
+pub const MIN_CONNECTIONS_RANGE: RangeInclusive<usize> = 1..=200;
+
+/// A problem found when validating an `InstallNodeServiceCtxBuilder`.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum BuilderError {
+    #[error("The environment file {0:?} must be absolute")]
+    EnvFileNotAbsolute(PathBuf),
+    #[error("Environment files are only supported for systemd services on Linux")]
+    EnvFileNotSupported,
+    #[error("An environment file cannot be used alongside inline environment variables")]
+    EnvFileWithEnvVariables,
+    #[error("A genesis node starts a new network, so it must be explicitly confirmed")]
+    GenesisNotConfirmed,
+    #[error("A genesis node cannot be given bootstrap peers")]
+    GenesisWithBootstrapPeers,
+    #[error("The health port {0} cannot be the same as the metrics port")]
+    HealthPortMatchesMetricsPort(u16),
+    #[error("The install path {0:?} is not under any of the allowed roots")]
+    InstallPathOutsideRoots(PathBuf),
+    #[error("The install path {0:?} must be absolute")]
+    InstallPathNotAbsolute(PathBuf),
+    #[error("The install path {0:?} must not contain '..'")]
+    InstallPathTraversal(PathBuf),
+    #[error("The CPU quota {0} must be a positive number of CPUs")]
+    InvalidCpuQuota(f32),
+    #[error("The minimum connection count {0} is outside the supported range of {min} to {max}", min = MIN_CONNECTIONS_RANGE.start(), max = MIN_CONNECTIONS_RANGE.end())]
+    InvalidMinConnections(usize),
+    #[error("The log level '{log_level}' is not valid: {reason}")]
+    InvalidLogLevel { log_level: String, reason: String },
+    #[error("The service name '{0}' is not valid")]
+    InvalidServiceName(String),
+    #[error("The maintenance window cannot start and end at the same time ({0})")]
+    MaintenanceWindowIsEmpty(NaiveTime),
+    #[error("The metrics port {0} cannot be the same as the RPC port")]
+    MetricsPortMatchesRpcPort(u16),
+    #[error("The node port {0} cannot be the same as the metrics port")]
+    NodePortMatchesMetricsPort(u16),
+    #[error("The node port {0} cannot be the same as the RPC port")]
+    NodePortMatchesRpcPort(u16),
+}
+
 #[derive(Debug, PartialEq)]
 pub struct InstallNodeServiceCtxBuilder {
     pub autostart: bool,
     pub bootstrap_peers: Vec<Multiaddr>,
+    /// Must be set for a genesis node, as a guard against accidentally starting a new network.
+    pub confirm_genesis: bool,
+    /// Cap the node's CPU time, as a number of CPUs, e.g., `1.5` for one and a half.
+    ///
+    /// Only applied on Linux, as a systemd `CPUQuota=` directive.
+    pub cpu_quota: Option<f32>,
     pub data_dir_path: PathBuf,
+    /// An environment file for the unit to load, e.g., to keep secrets out of the unit itself.
+    ///
+    /// Only supported on Linux, as a systemd `EnvironmentFile=` directive. The path must be
+    /// absolute, and `env_variables` must not also be set.
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
+    /// Run the node without persisting its data.
+    ///
+    /// The node's root directory is a directory of its own under `data_dir_path`, which the node
+    /// manager wipes when the service is stopped or removed, so the chunk store and wallet do not
+    /// outlive the run.
+    pub ephemeral: bool,
     pub genesis: bool,
+    /// The port for a minimal health endpoint, for load balancers that don't need the full metrics.
+    pub health_port: Option<u16>,
     pub home_network: bool,
     pub local: bool,
     pub log_dir_path: PathBuf,
     pub log_format: Option<LogFormat>,
+    /// Per-module log levels for the service, in the form accepted by `SN_LOG`, e.g.,
+    /// `sn_node=debug,libp2p=info`.
+    pub log_level: Option<String>,
+    /// The daily window, as start and end times, during which the node may restart itself or pause
+    /// replication. The window may wrap past midnight, e.g., 23:00 to 01:00.
+    pub maintenance_window: Option<(NaiveTime, NaiveTime)>,
+    /// Cap the node's memory use. Only applied on Linux, as a systemd `MemoryMax=` directive.
+    pub memory_max: Option<ByteSize>,
     pub name: String,
     pub metrics_port: Option<u16>,
+    /// The number of connections the node should try to keep, e.g., for a home node behind NAT.
+    pub min_connections: Option<usize>,
     pub node_port: Option<u16>,
     pub owner: Option<String>,
     pub rpc_socket_addr: SocketAddr,
     pub safenode_path: PathBuf,
     pub service_user: Option<String>,
     pub upnp: bool,
+    /// Whether the service is a user-mode service, which runs as the user who installed it.
+    pub user_mode: bool,
 }
 
 impl InstallNodeServiceCtxBuilder {
-    pub fn build(self) -> Result<ServiceInstallCtx> {
+    /// Check the builder for every problem that would make the service unusable, rather than
+    /// stopping at the first one.
+    pub fn validate_all(&self) -> Result<(), Vec<BuilderError>> {
+        let mut errors = vec![];
+
+        if self.name.parse::<ServiceLabel>().is_err() {
+            errors.push(BuilderError::InvalidServiceName(self.name.clone()));
+        }
+        let rpc_port = self.rpc_socket_addr.port();
+        if let Some(node_port) = self.node_port {
+            if node_port == rpc_port {
+                errors.push(BuilderError::NodePortMatchesRpcPort(node_port));
+            }
+            if self.metrics_port == Some(node_port) {
+                errors.push(BuilderError::NodePortMatchesMetricsPort(node_port));
+            }
+        }
+        if self.metrics_port == Some(rpc_port) {
+            errors.push(BuilderError::MetricsPortMatchesRpcPort(rpc_port));
+        }
+        if let Some(health_port) = self.health_port {
+            if self.metrics_port == Some(health_port) {
+                errors.push(BuilderError::HealthPortMatchesMetricsPort(health_port));
+            }
+        }
+        if let Some(log_level) = &self.log_level {
+            if let Err(err) = sn_logging::validate_log_targets(log_level) {
+                errors.push(BuilderError::InvalidLogLevel {
+                    log_level: log_level.clone(),
+                    reason: err.to_string(),
+                });
+            }
+        }
+        if let Some(cpu_quota) = self.cpu_quota {
+            if !cpu_quota.is_finite() || cpu_quota <= 0.0 {
+                errors.push(BuilderError::InvalidCpuQuota(cpu_quota));
+            }
+        }
+        if let Some(min_connections) = self.min_connections {
+            if !MIN_CONNECTIONS_RANGE.contains(&min_connections) {
+                errors.push(BuilderError::InvalidMinConnections(min_connections));
+            }
+        }
+        if let Some((start, end)) = self.maintenance_window {
+            if start == end {
+                errors.push(BuilderError::MaintenanceWindowIsEmpty(start));
+            }
+        }
+        errors.extend(validate_env_file(
+            self.env_file.as_ref(),
+            &self.env_variables,
+        ));
+        if self.genesis && !self.confirm_genesis {
+            errors.push(BuilderError::GenesisNotConfirmed);
+        }
+        // The `--first` and `--peer` arguments conflict, so safenode would refuse to start.
+        if self.genesis && !self.bootstrap_peers.is_empty() {
+            errors.push(BuilderError::GenesisWithBootstrapPeers);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check the data, log and binary paths are absolute, free of `..` components and, if any
+    /// roots are given, under one of them.
+    pub fn validate_install_paths(
+        &self,
+        allowed_roots: &[PathBuf],
+    ) -> Result<(), Vec<BuilderError>> {
+        let mut errors = vec![];
+        for path in [&self.data_dir_path, &self.log_dir_path, &self.safenode_path] {
+            if !path.is_absolute() {
+                errors.push(BuilderError::InstallPathNotAbsolute(path.clone()));
+            } else if path
+                .components()
+                .any(|component| component == Component::ParentDir)
+            {
+                errors.push(BuilderError::InstallPathTraversal(path.clone()));
+            } else if !allowed_roots.is_empty()
+                && !allowed_roots.iter().any(|root| path.starts_with(root))
+            {
+                errors.push(BuilderError::InstallPathOutsideRoots(path.clone()));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn build(mut self) -> Result<ServiceInstallCtx> {
+        self.validate_all().map_err(builder_errors_to_report)?;
+
         let label: ServiceLabel = self.name.parse()?;
+        let root_dir = if self.ephemeral {
+            self.data_dir_path.join(EPHEMERAL_ROOT_DIR_NAME)
+        } else {
+            self.data_dir_path.clone()
+        };
         let mut args = vec![
             OsString::from("--rpc"),
             OsString::from(self.rpc_socket_addr.to_string()),
             OsString::from("--root-dir"),
-            OsString::from(self.data_dir_path.to_string_lossy().to_string()),
+            OsString::from(root_dir.to_string_lossy().to_string()),
             OsString::from("--log-output-dest"),
             OsString::from(self.log_dir_path.to_string_lossy().to_string()),
         ];
@@ -97,11 +353,26 @@ impl InstallNodeServiceCtxBuilder {
             args.push(OsString::from("--metrics-server-port"));
             args.push(OsString::from(metrics_port.to_string()));
         }
+        if let Some(health_port) = self.health_port {
+            args.push(OsString::from("--health-port"));
+            args.push(OsString::from(health_port.to_string()));
+        }
+        if let Some(min_connections) = self.min_connections {
+            args.push(OsString::from("--min-connections"));
+            args.push(OsString::from(min_connections.to_string()));
+        }
+        if let Some((start, end)) = self.maintenance_window {
+            args.push(OsString::from("--maintenance-start"));
+            args.push(OsString::from(start.format("%H:%M").to_string()));
+            args.push(OsString::from("--maintenance-end"));
+            args.push(OsString::from(end.format("%H:%M").to_string()));
+        }
         if let Some(owner) = self.owner {
             args.push(OsString::from("--owner"));
             args.push(OsString::from(owner));
         }
 
+        canonicalize_peers(&mut self.bootstrap_peers);
         if !self.bootstrap_peers.is_empty() {
             let peers_str = self
                 .bootstrap_peers
@@ -113,34 +384,166 @@ impl InstallNodeServiceCtxBuilder {
             args.push(OsString::from(peers_str));
         }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: self.autostart,
             contents: None,
-            environment: self.env_variables,
+            environment: with_log_level(self.env_variables, self.log_level),
             label: label.clone(),
             program: self.safenode_path.to_path_buf(),
             username: self.service_user.clone(),
             working_directory: None,
-        })
+        };
+        let mut directives = vec![];
+        if let Some(env_file) = &self.env_file {
+            directives.push(env_file_directive(env_file));
+        }
+        if let Some(cpu_quota) = self.cpu_quota {
+            directives.push(cpu_quota_directive(cpu_quota));
+        }
+        if let Some(memory_max) = self.memory_max {
+            directives.push(memory_max_directive(memory_max));
+        }
+        add_unit_directives(&mut install_ctx, &directives, self.user_mode);
+        Ok(install_ctx)
+    }
+}
+
+/// Parse an amount of memory as a number of bytes, with an optional `K`, `M`, `G` or `T` suffix
+/// for a multiple of 1024, as systemd does.
+pub fn parse_byte_size(s: &str) -> Result<ByteSize> {
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&s[..index], 1024),
+        Some((index, 'M' | 'm')) => (&s[..index], 1024u64.pow(2)),
+        Some((index, 'G' | 'g')) => (&s[..index], 1024u64.pow(3)),
+        Some((index, 'T' | 't')) => (&s[..index], 1024u64.pow(4)),
+        _ => (s, 1),
+    };
+    let number = number
+        .parse::<u64>()
+        .map_err(|err| eyre!("The memory size '{s}' must be a number of bytes: {err}"))?;
+    number
+        .checked_mul(multiplier)
+        .map(ByteSize)
+        .ok_or_else(|| eyre!("The memory size '{s}' is too large"))
+}
+
+/// Check an environment file, if given, is absolute, is the only source of environment variables
+/// given, and can be referenced on this platform.
+fn validate_env_file(
+    env_file: Option<&PathBuf>,
+    env_variables: &Option<Vec<(String, String)>>,
+) -> Vec<BuilderError> {
+    let mut errors = vec![];
+    if let Some(env_file) = env_file {
+        if !env_file.is_absolute() {
+            errors.push(BuilderError::EnvFileNotAbsolute(env_file.clone()));
+        }
+        if env_variables.is_some() {
+            errors.push(BuilderError::EnvFileWithEnvVariables);
+        }
+        if !cfg!(target_os = "linux") {
+            errors.push(BuilderError::EnvFileNotSupported);
+        }
     }
+    errors
 }
 
+/// Combine builder errors into a single report, so every problem is shown at once.
+pub fn builder_errors_to_report(errors: Vec<BuilderError>) -> color_eyre::Report {
+    eyre!(errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// The ports assigned to a node service, as defined by the arguments in its install context.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeServicePorts {
+    pub metrics_port: Option<u16>,
+    pub node_port: Option<u16>,
+    pub rpc_port: Option<u16>,
+}
+
+/// Parse the ports back out of the arguments of a node service built by
+/// `InstallNodeServiceCtxBuilder`.
+///
+/// Any port that was not assigned, or whose value cannot be parsed, is returned as `None`.
+pub fn parse_node_service_ports(ctx: &ServiceInstallCtx) -> NodeServicePorts {
+    let mut ports = NodeServicePorts::default();
+    let mut args = ctx.args.iter().map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--rpc" => {
+                ports.rpc_port = args
+                    .next()
+                    .and_then(|value| value.parse::<SocketAddr>().ok())
+                    .map(|addr| addr.port());
+            }
+            "--metrics-server-port" => {
+                ports.metrics_port = args.next().and_then(|value| value.parse().ok());
+            }
+            "--port" => {
+                ports.node_port = args.next().and_then(|value| value.parse().ok());
+            }
+            _ => {}
+        }
+    }
+    ports
+}
+
+/// The options for adding node services.
+///
+/// They can be read from and written to YAML, so a file can fully describe an add operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AddNodeServiceOptions {
     pub auto_restart: bool,
     pub auto_set_nat_flags: bool,
     pub bootstrap_peers: Vec<Multiaddr>,
+    /// Must be set along with `genesis`, as a guard against accidentally starting a new network.
+    #[serde(default)]
+    pub confirm_genesis: bool,
     pub count: Option<u16>,
+    /// Cap each node's CPU time; see `InstallNodeServiceCtxBuilder::cpu_quota`.
+    #[serde(default)]
+    pub cpu_quota: Option<f32>,
     pub delete_safenode_src: bool,
     pub enable_metrics_server: bool,
+    /// An environment file for the nodes to load; see `InstallNodeServiceCtxBuilder::env_file`.
+    #[serde(default)]
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
+    /// Run the nodes without persisting their data; see `InstallNodeServiceCtxBuilder::ephemeral`.
+    #[serde(default)]
+    pub ephemeral: bool,
     pub genesis: bool,
+    /// The port, or a range of ports, for each node's health endpoint; see
+    /// `InstallNodeServiceCtxBuilder::health_port`.
+    #[serde(default)]
+    pub health_port: Option<PortRange>,
     pub home_network: bool,
     pub local: bool,
     pub log_format: Option<LogFormat>,
+    /// Per-module log levels for each node; see `InstallNodeServiceCtxBuilder::log_level`.
+    #[serde(default)]
+    pub log_level: Option<String>,
+    /// A daily window, in the host's local time, during which the nodes pause replication.
+    #[serde(default)]
+    pub maintenance_window: Option<(NaiveTime, NaiveTime)>,
+    pub max_concurrent_installs: usize,
+    /// Cap each node's memory use; see `InstallNodeServiceCtxBuilder::memory_max`.
+    #[serde(default)]
+    pub memory_max: Option<ByteSize>,
     pub metrics_port: Option<PortRange>,
+    /// The connected peer count below which the nodes redial their bootstrap peers.
+    #[serde(default)]
+    pub min_connections: Option<usize>,
     pub owner: Option<String>,
     pub node_port: Option<PortRange>,
+    /// Finish an interrupted add of the same count, rather than adding a new set of nodes.
+    #[serde(default)]
+    pub resume: bool,
     pub rpc_address: Option<Ipv4Addr>,
     pub rpc_port: Option<PortRange>,
     pub safenode_src_path: PathBuf,
@@ -153,11 +556,96 @@ pub struct AddNodeServiceOptions {
     pub version: String,
 }
 
+impl AddNodeServiceOptions {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml).map_err(|e| eyre!("Could not parse the node options: {e}"))
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(|e| eyre!("Could not serialize the node options: {e}"))
+    }
+
+    /// A multi-line summary of the options, for the operator to review before the nodes are added.
+    ///
+    /// The values of environment variables that look like secrets are redacted.
+    pub fn summary(&self) -> String {
+        let port_summary = |ports: &Option<PortRange>| match ports {
+            Some(ports) => ports.to_string(),
+            None => "automatic".to_string(),
+        };
+        let network = if self.local {
+            "local".to_string()
+        } else if self.genesis {
+            "new network (genesis)".to_string()
+        } else {
+            format!("{} bootstrap peer(s)", self.bootstrap_peers.len())
+        };
+
+        let mut lines = vec![
+            format!("Count: {}", self.count.unwrap_or(1)),
+            format!("Version: {}", self.version),
+            format!("Network: {network}"),
+            format!("Node ports: {}", port_summary(&self.node_port)),
+            format!("RPC ports: {}", port_summary(&self.rpc_port)),
+        ];
+        if self.enable_metrics_server {
+            lines.push(format!(
+                "Metrics ports: {}",
+                port_summary(&self.metrics_port)
+            ));
+        }
+        lines.push(format!(
+            "Owner: {}",
+            self.owner.as_deref().unwrap_or("not set")
+        ));
+        if let Some(env_variables) = &self.env_variables {
+            let env_variables = env_variables
+                .iter()
+                .map(|(name, value)| {
+                    if is_secret_env_variable(name) {
+                        format!("{name}=********")
+                    } else {
+                        format!("{name}={value}")
+                    }
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!("Environment: {}", env_variables.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The options needed to recreate each installed node, e.g., when migrating a fleet to a new host.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetManifest {
+    pub nodes: Vec<AddNodeServiceOptions>,
+}
+
+impl FleetManifest {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml).map_err(|e| eyre!("Could not parse the fleet manifest: {e}"))
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self)
+            .map_err(|e| eyre!("Could not serialize the fleet manifest: {e}"))
+    }
+}
+
+pub(crate) fn is_secret_env_variable(name: &str) -> bool {
+    let name = name.to_uppercase();
+    ["KEY", "SECRET", "TOKEN", "PASSWORD"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
 #[derive(Debug, PartialEq)]
 pub struct InstallAuditorServiceCtxBuilder {
     pub auditor_path: PathBuf,
     pub beta_encryption_key: Option<String>,
     pub bootstrap_peers: Vec<Multiaddr>,
+    /// An environment file for the unit to load, as for `InstallNodeServiceCtxBuilder`.
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
     pub log_dir_path: PathBuf,
     pub name: String,
@@ -165,12 +653,18 @@ pub struct InstallAuditorServiceCtxBuilder {
 }
 
 impl InstallAuditorServiceCtxBuilder {
-    pub fn build(self) -> Result<ServiceInstallCtx> {
+    pub fn build(mut self) -> Result<ServiceInstallCtx> {
+        let errors = validate_env_file(self.env_file.as_ref(), &self.env_variables);
+        if !errors.is_empty() {
+            return Err(builder_errors_to_report(errors));
+        }
+
         let mut args = vec![
             OsString::from("--log-output-dest"),
             OsString::from(self.log_dir_path.to_string_lossy().to_string()),
         ];
 
+        canonicalize_peers(&mut self.bootstrap_peers);
         if !self.bootstrap_peers.is_empty() {
             let peers_str = self
                 .bootstrap_peers
@@ -186,7 +680,7 @@ impl InstallAuditorServiceCtxBuilder {
             args.push(OsString::from(beta_encryption_key));
         }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: true,
             contents: None,
@@ -195,13 +689,20 @@ impl InstallAuditorServiceCtxBuilder {
             program: self.auditor_path.to_path_buf(),
             username: Some(self.service_user.to_string()),
             working_directory: None,
-        })
+        };
+        if let Some(env_file) = &self.env_file {
+            add_unit_directives(&mut install_ctx, &[env_file_directive(env_file)], false);
+        }
+        Ok(install_ctx)
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct InstallFaucetServiceCtxBuilder {
     pub bootstrap_peers: Vec<Multiaddr>,
+    pub dispense_amount: Option<NanoTokens>,
+    /// An environment file for the unit to load, as for `InstallNodeServiceCtxBuilder`.
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
     pub faucet_path: PathBuf,
     pub local: bool,
@@ -211,12 +712,18 @@ pub struct InstallFaucetServiceCtxBuilder {
 }
 
 impl InstallFaucetServiceCtxBuilder {
-    pub fn build(self) -> Result<ServiceInstallCtx> {
+    pub fn build(mut self) -> Result<ServiceInstallCtx> {
+        let errors = validate_env_file(self.env_file.as_ref(), &self.env_variables);
+        if !errors.is_empty() {
+            return Err(builder_errors_to_report(errors));
+        }
+
         let mut args = vec![
             OsString::from("--log-output-dest"),
             OsString::from(self.log_dir_path.to_string_lossy().to_string()),
         ];
 
+        canonicalize_peers(&mut self.bootstrap_peers);
         if !self.bootstrap_peers.is_empty() {
             let peers_str = self
                 .bootstrap_peers
@@ -229,8 +736,12 @@ impl InstallFaucetServiceCtxBuilder {
         }
 
         args.push(OsString::from("server"));
+        if let Some(dispense_amount) = self.dispense_amount {
+            args.push(OsString::from("--amount"));
+            args.push(OsString::from(dispense_amount.to_string()));
+        }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: true,
             contents: None,
@@ -239,7 +750,11 @@ impl InstallFaucetServiceCtxBuilder {
             program: self.faucet_path.to_path_buf(),
             username: Some(self.service_user.to_string()),
             working_directory: None,
-        })
+        };
+        if let Some(env_file) = &self.env_file {
+            add_unit_directives(&mut install_ctx, &[env_file_directive(env_file)], false);
+        }
+        Ok(install_ctx)
     }
 }
 
@@ -248,6 +763,7 @@ pub struct AddAuditorServiceOptions {
     pub auditor_src_bin_path: PathBuf,
     pub beta_encryption_key: Option<String>,
     pub bootstrap_peers: Vec<Multiaddr>,
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
     pub service_log_dir_path: PathBuf,
     pub user: String,
@@ -256,6 +772,8 @@ pub struct AddAuditorServiceOptions {
 
 pub struct AddFaucetServiceOptions {
     pub bootstrap_peers: Vec<Multiaddr>,
+    pub dispense_amount: Option<NanoTokens>,
+    pub env_file: Option<PathBuf>,
     pub env_variables: Option<Vec<(String, String)>>,
     pub faucet_install_bin_path: PathBuf,
     pub faucet_src_bin_path: PathBuf,
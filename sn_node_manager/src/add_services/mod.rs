@@ -10,12 +10,12 @@ pub mod config;
 mod tests;
 
 use self::config::{
-    AddAuditorServiceOptions, AddDaemonServiceOptions, AddFaucetServiceOptions,
-    AddNodeServiceOptions, InstallAuditorServiceCtxBuilder, InstallFaucetServiceCtxBuilder,
-    InstallNodeServiceCtxBuilder, PortRange,
+    builder_errors_to_report, AddAuditorServiceOptions, AddDaemonServiceOptions,
+    AddFaucetServiceOptions, AddNodeServiceOptions, FleetManifest, InstallAuditorServiceCtxBuilder,
+    InstallFaucetServiceCtxBuilder, InstallNodeServiceCtxBuilder, PortRange,
 };
 use crate::{
-    config::{create_owned_dir, get_user_safenode_data_dir},
+    config::{create_owned_dir, create_private_dir, get_user_safenode_data_dir},
     VerbosityLevel, DAEMON_SERVICE_NAME,
 };
 use color_eyre::{
@@ -25,12 +25,13 @@ use color_eyre::{
 use colored::Colorize;
 use service_manager::ServiceInstallCtx;
 use sn_service_management::{
-    auditor::AuditorServiceData, control::ServiceControl, DaemonServiceData, FaucetServiceData,
-    NatDetectionStatus, NodeRegistry, NodeServiceData, ServiceStatus,
+    auditor::AuditorServiceData, control::ServiceControl, node::EPHEMERAL_ROOT_DIR_NAME,
+    DaemonServiceData, FaucetServiceData, NatDetectionStatus, NodeRegistry, NodeServiceData,
+    PendingNodeAdd, ServiceStatus,
 };
 use std::{
     ffi::OsString,
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener},
 };
 
 /// Install safenode as a service.
@@ -48,6 +49,14 @@ pub async fn add_node(
     verbosity: VerbosityLevel,
 ) -> Result<Vec<String>> {
     if options.genesis {
+        if !options.confirm_genesis {
+            error!("A genesis node starts a new network, so it must be explicitly confirmed");
+            return Err(eyre!(
+                "A genesis node starts a new network, so it must be explicitly confirmed with \
+                 --confirm-genesis"
+            ));
+        }
+
         if let Some(count) = options.count {
             if count > 1 {
                 error!("A genesis node can only be added as a single node");
@@ -86,16 +95,88 @@ pub async fn add_node(
         }
     }
 
+    // An interrupted add is only resumed when asked to, using the node numbers it planned. Any
+    // other add is a new operation, so the interrupted one is discarded.
+    let count = options.count.unwrap_or(1);
+    let node_numbers = match (&node_registry.pending_node_add, options.resume) {
+        (Some(pending), true) => {
+            if pending.count != count {
+                error!(
+                    "The interrupted add was of {} nodes, not {count}",
+                    pending.count
+                );
+                return Err(eyre!(
+                    "The interrupted add was of {} nodes, not {count}",
+                    pending.count
+                ));
+            }
+            info!(
+                "Resuming an interrupted add of {count} nodes: {:?}",
+                pending.node_numbers
+            );
+            pending.node_numbers.clone()
+        }
+        (None, true) => {
+            error!("There is no interrupted add to resume");
+            return Err(eyre!("There is no interrupted add to resume"));
+        }
+        (pending, false) => {
+            if let Some(pending) = pending {
+                warn!(
+                    "Discarding an interrupted add of {} nodes: {:?}",
+                    pending.count, pending.node_numbers
+                );
+                if verbosity != VerbosityLevel::Minimal {
+                    println!(
+                        "[!] Discarding an interrupted add of {} nodes; use --resume to finish it",
+                        pending.count
+                    );
+                }
+            }
+            let next_node_number = node_registry
+                .nodes
+                .iter()
+                .map(|node| node.number)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            (next_node_number..next_node_number + count).collect::<Vec<_>>()
+        }
+    };
+    let is_installed = |node_number: u16| {
+        let service_name = format!("safenode{node_number}");
+        node_registry
+            .nodes
+            .iter()
+            .any(|node| node.service_name == service_name)
+    };
+
+    // Nodes that are part of the operation being resumed will hold some of the requested ports,
+    // so they are excluded from the check.
+    let other_nodes = node_registry
+        .nodes
+        .iter()
+        .filter(|node| !node_numbers.contains(&node.number))
+        .cloned()
+        .collect::<Vec<_>>();
+
     if let Some(port_option) = &options.node_port {
-        check_port_availability(port_option, &node_registry.nodes)?;
+        check_port_availability(port_option, &other_nodes)?;
     }
 
     if let Some(port_option) = &options.metrics_port {
-        check_port_availability(port_option, &node_registry.nodes)?;
+        check_port_availability(port_option, &other_nodes)?;
+        check_host_port_availability(port_option)?;
+    }
+
+    if let Some(port_option) = &options.health_port {
+        check_port_availability(port_option, &other_nodes)?;
+        check_host_port_availability(port_option)?;
     }
 
     if let Some(port_option) = &options.rpc_port {
-        check_port_availability(port_option, &node_registry.nodes)?;
+        check_port_availability(port_option, &other_nodes)?;
+        check_host_port_availability(port_option)?;
     }
 
     let safenode_file_name = options
@@ -137,139 +218,186 @@ pub async fn add_node(
     let mut added_service_data = vec![];
     let mut failed_service_data = vec![];
 
-    let current_node_count = node_registry.nodes.len() as u16;
-    let target_node_count = current_node_count + options.count.unwrap_or(1);
+    let max_concurrent_installs = options.max_concurrent_installs.max(1);
 
-    let mut node_number = current_node_count + 1;
+    // Ports are assigned by position in the plan, so a resumed node receives the same ports it
+    // would have received in the interrupted run.
+    let mut planned_nodes = vec![];
     let mut node_port = get_start_port_if_applicable(options.node_port);
     let mut metrics_port = get_start_port_if_applicable(options.metrics_port);
+    let mut health_port = get_start_port_if_applicable(options.health_port);
     let mut rpc_port = get_start_port_if_applicable(options.rpc_port);
-
-    while node_number <= target_node_count {
-        trace!("Adding node with node_number {node_number}");
-        let rpc_free_port = if let Some(port) = rpc_port {
-            port
-        } else {
-            service_control.get_available_port()?
-        };
-        let metrics_free_port = if let Some(port) = metrics_port {
-            Some(port)
-        } else if options.enable_metrics_server {
-            Some(service_control.get_available_port()?)
+    for node_number in node_numbers.iter().copied() {
+        if is_installed(node_number) {
+            debug!("Node {node_number} was already added by a previous run; skipping it");
         } else {
-            None
-        };
+            planned_nodes.push((node_number, node_port, metrics_port, health_port, rpc_port));
+        }
+        node_port = increment_port_option(node_port);
+        metrics_port = increment_port_option(metrics_port);
+        health_port = increment_port_option(health_port);
+        rpc_port = increment_port_option(rpc_port);
+    }
 
-        let rpc_socket_addr = if let Some(addr) = options.rpc_address {
-            SocketAddr::new(IpAddr::V4(addr), rpc_free_port)
-        } else {
-            SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), rpc_free_port)
-        };
+    node_registry.pending_node_add = Some(PendingNodeAdd {
+        count,
+        node_numbers,
+    });
+    node_registry.save()?;
+
+    let mut planned_nodes = planned_nodes.into_iter().peekable();
+    while planned_nodes.peek().is_some() {
+        // The names, ports and directories for each batch are assigned sequentially, so they are
+        // deterministic regardless of how many services are installed at the same time.
+        let mut pending_installs = Vec::with_capacity(max_concurrent_installs);
+        while pending_installs.len() < max_concurrent_installs {
+            let Some((node_number, node_port, metrics_port, health_port, rpc_port)) =
+                planned_nodes.next()
+            else {
+                break;
+            };
+            trace!("Adding node with node_number {node_number}");
+            let rpc_free_port = if let Some(port) = rpc_port {
+                port
+            } else {
+                service_control.get_available_port()?
+            };
+            let metrics_free_port = if let Some(port) = metrics_port {
+                Some(port)
+            } else if options.enable_metrics_server {
+                Some(service_control.get_available_port()?)
+            } else {
+                None
+            };
 
-        let service_name = format!("safenode{node_number}");
-        let service_data_dir_path = options.service_data_dir_path.join(service_name.clone());
-        let service_safenode_path = service_data_dir_path.join(safenode_file_name.clone());
-
-        // For a user mode service, if the user has *not* specified a custom directory and they are
-        // using the default, e.g., ~/.local/share/safe/node/<service-name>, an additional "logs"
-        // directory needs to be appended to the path, otherwise the log files will be output at
-        // the same directory where `secret-key` is, which is not what users expect.
-        let default_log_dir_path = get_user_safenode_data_dir()?;
-        let service_log_dir_path =
-            if options.user_mode && options.service_log_dir_path == default_log_dir_path {
-                options
-                    .service_log_dir_path
-                    .join(service_name.clone())
-                    .join("logs")
+            let rpc_socket_addr = if let Some(addr) = options.rpc_address {
+                SocketAddr::new(IpAddr::V4(addr), rpc_free_port)
             } else {
-                options.service_log_dir_path.join(service_name.clone())
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), rpc_free_port)
             };
 
-        if let Some(user) = &options.user {
-            debug!("Creating data_dir and log_dirs with user {user}");
-            create_owned_dir(service_data_dir_path.clone(), user)?;
-            create_owned_dir(service_log_dir_path.clone(), user)?;
-        } else {
-            debug!("Creating data_dir and log_dirs without user");
-            std::fs::create_dir_all(service_data_dir_path.clone())?;
-            std::fs::create_dir_all(service_log_dir_path.clone())?;
-        }
+            let service_name = format!("safenode{node_number}");
+            let service_data_dir_path = options.service_data_dir_path.join(service_name.clone());
+            let service_safenode_path = service_data_dir_path.join(safenode_file_name.clone());
+
+            // For a user mode service, if the user has *not* specified a custom directory and they
+            // are using the default, e.g., ~/.local/share/safe/node/<service-name>, an additional
+            // "logs" directory needs to be appended to the path, otherwise the log files will be
+            // output at the same directory where `secret-key` is, which is not what users expect.
+            let default_log_dir_path = get_user_safenode_data_dir()?;
+            let service_log_dir_path =
+                if options.user_mode && options.service_log_dir_path == default_log_dir_path {
+                    options
+                        .service_log_dir_path
+                        .join(service_name.clone())
+                        .join("logs")
+                } else {
+                    options.service_log_dir_path.join(service_name.clone())
+                };
+
+            if let Some(user) = &options.user {
+                debug!("Creating data_dir and log_dirs with user {user}");
+                create_owned_dir(service_data_dir_path.clone(), user)?;
+                create_owned_dir(service_log_dir_path.clone(), user)?;
+            } else {
+                debug!("Creating data_dir and log_dirs without user");
+                std::fs::create_dir_all(service_data_dir_path.clone())?;
+                std::fs::create_dir_all(service_log_dir_path.clone())?;
+            }
+            if options.ephemeral {
+                create_private_dir(
+                    service_data_dir_path.join(EPHEMERAL_ROOT_DIR_NAME),
+                    options.user.as_deref(),
+                )?;
+            }
 
-        debug!("Copying safenode binary to {service_safenode_path:?}");
-        std::fs::copy(
-            options.safenode_src_path.clone(),
-            service_safenode_path.clone(),
-        )?;
-
-        if options.auto_set_nat_flags {
-            let nat_status = node_registry
-                .nat_status
-                .clone()
-                .ok_or_eyre("NAT status has not been set. Run 'nat-detection' first")?;
-
-            match nat_status {
-                NatDetectionStatus::Public => {
-                    options.upnp = false;
-                    options.home_network = false;
-                }
-                NatDetectionStatus::UPnP => {
-                    options.upnp = true;
-                    options.home_network = false;
-                }
-                NatDetectionStatus::Private => {
-                    options.upnp = false;
-                    options.home_network = true;
+            debug!("Copying safenode binary to {service_safenode_path:?}");
+            std::fs::copy(
+                options.safenode_src_path.clone(),
+                service_safenode_path.clone(),
+            )?;
+
+            if options.auto_set_nat_flags {
+                let nat_status = node_registry
+                    .nat_status
+                    .clone()
+                    .ok_or_eyre("NAT status has not been set. Run 'nat-detection' first")?;
+
+                match nat_status {
+                    NatDetectionStatus::Public => {
+                        options.upnp = false;
+                        options.home_network = false;
+                    }
+                    NatDetectionStatus::UPnP => {
+                        options.upnp = true;
+                        options.home_network = false;
+                    }
+                    NatDetectionStatus::Private => {
+                        options.upnp = false;
+                        options.home_network = true;
+                    }
                 }
+                debug!(
+                    "Auto-setting NAT flags: upnp={}, home_network={}",
+                    options.upnp, options.home_network
+                );
             }
-            debug!(
-                "Auto-setting NAT flags: upnp={}, home_network={}",
-                options.upnp, options.home_network
-            );
-        }
-
-        let install_ctx = InstallNodeServiceCtxBuilder {
-            autostart: options.auto_restart,
-            bootstrap_peers: options.bootstrap_peers.clone(),
-            data_dir_path: service_data_dir_path.clone(),
-            env_variables: options.env_variables.clone(),
-            genesis: options.genesis,
-            home_network: options.home_network,
-            local: options.local,
-            log_dir_path: service_log_dir_path.clone(),
-            log_format: options.log_format,
-            metrics_port: metrics_free_port,
-            name: service_name.clone(),
-            node_port,
-            owner: options.owner.clone(),
-            rpc_socket_addr,
-            safenode_path: service_safenode_path.clone(),
-            service_user: options.user.clone(),
-            upnp: options.upnp,
-        }
-        .build()?;
-
-        match service_control.install(install_ctx, options.user_mode) {
-            Ok(()) => {
-                info!("Successfully added service {service_name}");
-                added_service_data.push((
-                    service_name.clone(),
-                    service_safenode_path.to_string_lossy().into_owned(),
-                    service_data_dir_path.to_string_lossy().into_owned(),
-                    service_log_dir_path.to_string_lossy().into_owned(),
-                    rpc_socket_addr,
-                ));
 
-                node_registry.nodes.push(NodeServiceData {
+            let builder = InstallNodeServiceCtxBuilder {
+                autostart: options.auto_restart,
+                bootstrap_peers: options.bootstrap_peers.clone(),
+                confirm_genesis: options.confirm_genesis,
+                cpu_quota: options.cpu_quota,
+                data_dir_path: service_data_dir_path.clone(),
+                env_file: options.env_file.clone(),
+                env_variables: options.env_variables.clone(),
+                ephemeral: options.ephemeral,
+                genesis: options.genesis,
+                health_port,
+                home_network: options.home_network,
+                local: options.local,
+                log_dir_path: service_log_dir_path.clone(),
+                log_format: options.log_format,
+                log_level: options.log_level.clone(),
+                maintenance_window: options.maintenance_window,
+                memory_max: options.memory_max,
+                metrics_port: metrics_free_port,
+                min_connections: options.min_connections,
+                name: service_name.clone(),
+                node_port,
+                owner: options.owner.clone(),
+                rpc_socket_addr,
+                safenode_path: service_safenode_path.clone(),
+                service_user: options.user.clone(),
+                upnp: options.upnp,
+                user_mode: options.user_mode,
+            };
+            builder
+                .validate_install_paths(&[])
+                .map_err(builder_errors_to_report)?;
+            let install_ctx = builder.build()?;
+
+            pending_installs.push((
+                install_ctx,
+                NodeServiceData {
                     auto_restart: options.auto_restart,
                     connected_peers: None,
-                    data_dir_path: service_data_dir_path.clone(),
+                    cpu_quota: options.cpu_quota,
+                    data_dir_path: service_data_dir_path,
+                    env_file: options.env_file.clone(),
+                    ephemeral: options.ephemeral,
                     genesis: options.genesis,
+                    health_port,
                     home_network: options.home_network,
                     listen_addr: None,
                     local: options.local,
-                    log_dir_path: service_log_dir_path.clone(),
+                    log_dir_path: service_log_dir_path,
                     log_format: options.log_format,
+                    log_level: options.log_level.clone(),
+                    maintenance_window: options.maintenance_window,
+                    memory_max: options.memory_max,
                     metrics_port: metrics_free_port,
+                    min_connections: options.min_connections,
                     node_port,
                     number: node_number,
                     reward_balance: None,
@@ -284,21 +412,40 @@ pub async fn add_node(
                     user: options.user.clone(),
                     user_mode: options.user_mode,
                     version: options.version.clone(),
-                });
-                // We save the node registry for each service because it's possible any number of
-                // services could fail to be added.
-                node_registry.save()?;
-            }
-            Err(e) => {
-                error!("Failed to add service {service_name}: {e}");
-                failed_service_data.push((service_name.clone(), e.to_string()));
+                },
+            ));
+        }
+
+        let install_results =
+            install_services_concurrently(&pending_installs, service_control, options.user_mode);
+        for ((_, node), result) in pending_installs.into_iter().zip(install_results) {
+            match result {
+                Ok(()) => {
+                    info!("Successfully added service {}", node.service_name);
+                    added_service_data.push((
+                        node.service_name.clone(),
+                        node.safenode_path.to_string_lossy().into_owned(),
+                        node.data_dir_path.to_string_lossy().into_owned(),
+                        node.log_dir_path.to_string_lossy().into_owned(),
+                        node.rpc_socket_addr,
+                    ));
+
+                    node_registry.nodes.push(node);
+                    // We save the node registry for each service because it's possible any number
+                    // of services could fail to be added.
+                    node_registry.save()?;
+                }
+                Err(e) => {
+                    error!("Failed to add service {}: {e}", node.service_name);
+                    failed_service_data.push((node.service_name, e));
+                }
             }
         }
+    }
 
-        node_number += 1;
-        node_port = increment_port_option(node_port);
-        metrics_port = increment_port_option(metrics_port);
-        rpc_port = increment_port_option(rpc_port);
+    if failed_service_data.is_empty() {
+        node_registry.pending_node_add = None;
+        node_registry.save()?;
     }
 
     if options.delete_safenode_src {
@@ -332,8 +479,19 @@ pub async fn add_node(
                 println!("{} {}: {}", "✕".red(), failed.0, failed.1);
             }
         }
-        return Err(eyre!("Failed to add one or more services")
-            .suggestion("However, any services that were successfully added will be usable."));
+        let added_services_names = added_service_data
+            .iter()
+            .map(|(name, ..)| name.as_str())
+            .collect::<Vec<_>>();
+        let suggestion = if added_services_names.is_empty() {
+            "No services were added.".to_string()
+        } else {
+            format!(
+                "However, the services that were successfully added will be usable: {}",
+                added_services_names.join(", ")
+            )
+        };
+        return Err(eyre!("Failed to add one or more services").suggestion(suggestion));
     }
 
     let added_services_names = added_service_data
@@ -383,6 +541,7 @@ pub fn add_auditor(
         auditor_path: install_options.auditor_install_bin_path.clone(),
         beta_encryption_key: install_options.beta_encryption_key.clone(),
         bootstrap_peers: install_options.bootstrap_peers.clone(),
+        env_file: install_options.env_file.clone(),
         env_variables: install_options.env_variables.clone(),
         log_dir_path: install_options.service_log_dir_path.clone(),
         name: "auditor".to_string(),
@@ -527,6 +686,8 @@ pub fn add_faucet(
 
     let install_ctx = InstallFaucetServiceCtxBuilder {
         bootstrap_peers: install_options.bootstrap_peers.clone(),
+        dispense_amount: install_options.dispense_amount,
+        env_file: install_options.env_file.clone(),
         env_variables: install_options.env_variables.clone(),
         faucet_path: install_options.faucet_install_bin_path.clone(),
         local: install_options.local,
@@ -539,6 +700,7 @@ pub fn add_faucet(
     match service_control.install(install_ctx, false) {
         Ok(()) => {
             node_registry.faucet = Some(FaucetServiceData {
+                dispense_amount: install_options.dispense_amount,
                 faucet_path: install_options.faucet_install_bin_path.clone(),
                 local: false,
                 log_dir_path: install_options.service_log_dir_path.clone(),
@@ -577,6 +739,33 @@ pub fn add_faucet(
     }
 }
 
+/// Install a batch of services in parallel, one thread per service.
+///
+/// The caller bounds the concurrency through the size of the batch. The results are returned in
+/// the same order as the batch.
+fn install_services_concurrently(
+    pending_installs: &[(ServiceInstallCtx, NodeServiceData)],
+    service_control: &dyn ServiceControl,
+    user_mode: bool,
+) -> Vec<Result<(), String>> {
+    std::thread::scope(|scope| {
+        let handles = pending_installs
+            .iter()
+            .map(|(install_ctx, _)| {
+                let install_ctx = install_ctx.clone();
+                scope.spawn(move || service_control.install(install_ctx, user_mode))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result.map_err(|err| err.to_string()),
+                Err(_) => Err("The service install thread panicked".to_string()),
+            })
+            .collect()
+    })
+}
+
 fn get_start_port_if_applicable(range: Option<PortRange>) -> Option<u16> {
     if let Some(port) = range {
         match port {
@@ -604,6 +793,9 @@ fn check_port_availability(port_option: &PortRange, nodes: &[NodeServiceData]) -
         if let Some(port) = node.node_port {
             all_ports.push(port);
         }
+        if let Some(port) = node.health_port {
+            all_ports.push(port);
+        }
         all_ports.push(node.rpc_socket_addr.port());
     }
 
@@ -625,3 +817,95 @@ fn check_port_availability(port_option: &PortRange, nodes: &[NodeServiceData]) -
     }
     Ok(())
 }
+
+/// Reconstruct the options each installed node was added with, so the fleet can be recreated.
+///
+/// Each node gets its own entry with a count of one and its exact ports. Removed nodes are not
+/// included. The installed binary of each node is given as its source binary.
+///
+/// A genesis node is not confirmed in the manifest, so recreating it has to be confirmed again.
+pub fn export_fleet_manifest(node_registry: &NodeRegistry) -> Result<FleetManifest> {
+    let parent_dir = |path: &std::path::Path| {
+        path.parent()
+            .map(|parent| parent.to_path_buf())
+            .ok_or_else(|| eyre!("The path {path:?} has no parent directory"))
+    };
+
+    let nodes = node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .map(|node| {
+            let service_data_dir_path = parent_dir(&node.data_dir_path)?;
+            let rpc_address = match node.rpc_socket_addr.ip() {
+                IpAddr::V4(ip) => Some(ip),
+                IpAddr::V6(_) => None,
+            };
+            Ok(AddNodeServiceOptions {
+                auto_restart: node.auto_restart,
+                auto_set_nat_flags: false,
+                bootstrap_peers: node_registry.bootstrap_peers.clone(),
+                confirm_genesis: false,
+                count: Some(1),
+                cpu_quota: node.cpu_quota,
+                delete_safenode_src: false,
+                enable_metrics_server: node.metrics_port.is_some(),
+                env_file: node.env_file.clone(),
+                env_variables: node_registry.environment_variables.clone(),
+                ephemeral: node.ephemeral,
+                genesis: node.genesis,
+                health_port: node.health_port.map(PortRange::Single),
+                home_network: node.home_network,
+                local: node.local,
+                log_format: node.log_format,
+                log_level: node.log_level.clone(),
+                maintenance_window: node.maintenance_window,
+                max_concurrent_installs: 1,
+                memory_max: node.memory_max,
+                metrics_port: node.metrics_port.map(PortRange::Single),
+                min_connections: node.min_connections,
+                owner: node.owner.clone(),
+                node_port: node.node_port.map(PortRange::Single),
+                resume: false,
+                rpc_address,
+                rpc_port: Some(PortRange::Single(node.rpc_socket_addr.port())),
+                safenode_dir_path: service_data_dir_path.clone(),
+                safenode_src_path: node.safenode_path.clone(),
+                service_data_dir_path,
+                service_log_dir_path: parent_dir(&node.log_dir_path)?,
+                upnp: node.upnp,
+                user: node.user.clone(),
+                user_mode: node.user_mode,
+                version: node.version.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(FleetManifest { nodes })
+}
+
+/// Probe each of the candidate ports by binding a TCP listener to it, returning the ones that are
+/// not already in use on this host.
+pub fn filter_available_ports(candidates: impl Iterator<Item = u16>) -> Vec<u16> {
+    candidates
+        .filter(|port| TcpListener::bind((Ipv4Addr::UNSPECIFIED, *port)).is_ok())
+        .collect()
+}
+
+/// The RPC and metrics servers listen on TCP, so a port that is already bound by another process
+/// would allow the service to be installed but prevent it from starting.
+fn check_host_port_availability(port_option: &PortRange) -> Result<()> {
+    let candidates = match port_option {
+        PortRange::Single(port) => *port..=*port,
+        PortRange::Range(start, end) => *start..=*end,
+    };
+    let available_ports = filter_available_ports(candidates.clone());
+    if let Some(port) = candidates
+        .into_iter()
+        .find(|port| !available_ports.contains(port))
+    {
+        error!("Port {port} is already in use on this host");
+        return Err(eyre!("Port {port} is already in use on this host"));
+    }
+    Ok(())
+}
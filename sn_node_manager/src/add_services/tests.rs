@@ -10,23 +10,32 @@ use crate::{
     add_services::{
         add_auditor, add_daemon, add_faucet, add_node,
         config::{
+            canonicalize_peers, parse_byte_size, parse_node_service_ports,
             AddAuditorServiceOptions, AddDaemonServiceOptions, AddFaucetServiceOptions,
-            AddNodeServiceOptions, InstallNodeServiceCtxBuilder, PortRange,
+            AddNodeServiceOptions, BuilderError, FirewallRule, FleetManifest,
+            InstallNodeServiceCtxBuilder, NodeServicePorts, PortRange, Protocol,
         },
+        export_fleet_manifest, filter_available_ports,
     },
     VerbosityLevel,
 };
 use assert_fs::prelude::*;
 use assert_matches::assert_matches;
+use chrono::NaiveTime;
 use color_eyre::Result;
 use libp2p::Multiaddr;
 use mockall::{mock, predicate::*, Sequence};
 use predicates::prelude::*;
 use service_manager::ServiceInstallCtx;
+use sn_logging::LogFormat;
 use sn_service_management::{auditor::AuditorServiceData, control::ServiceControl};
-use sn_service_management::{error::Result as ServiceControlResult, NatDetectionStatus};
 use sn_service_management::{
-    DaemonServiceData, FaucetServiceData, NodeRegistry, NodeServiceData, ServiceStatus,
+    error::{Error as ServiceControlError, Result as ServiceControlResult},
+    NatDetectionStatus,
+};
+use sn_service_management::{
+    unit::ByteSize, DaemonServiceData, FaucetServiceData, NodeRegistry, NodeServiceData,
+    PendingNodeAdd, ServiceStatus,
 };
 use sn_transfers::NanoTokens;
 use std::{
@@ -34,6 +43,10 @@ use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Barrier,
+    },
 };
 
 #[cfg(not(target_os = "windows"))]
@@ -68,6 +81,82 @@ mock! {
     }
 }
 
+/// Records how many installs run at the same time, which can't be observed through the mock
+/// because its expectations are evaluated one call at a time.
+///
+/// Each install waits on the barrier with the others in its batch before it returns, so every
+/// install in a batch is in flight at the same time. The service with `unpaired_service_name` is
+/// alone in the last batch, so it doesn't wait.
+///
+/// Only installs are expected while adding services. Any other call returns an error, so it fails
+/// the operation under test rather than panicking on one of the install threads.
+struct ConcurrencyTrackingServiceControl {
+    batch_barrier: Barrier,
+    failing_service_name: String,
+    in_flight: AtomicUsize,
+    install_count: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    unpaired_service_name: String,
+}
+
+impl ServiceControl for ConcurrencyTrackingServiceControl {
+    fn create_service_user(&self, _username: &str) -> ServiceControlResult<()> {
+        Ok(())
+    }
+
+    fn get_available_port(&self) -> ServiceControlResult<u16> {
+        Err(unexpected_call("get_available_port"))
+    }
+
+    fn install(
+        &self,
+        install_ctx: ServiceInstallCtx,
+        _user_mode: bool,
+    ) -> ServiceControlResult<()> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        if install_ctx.label.to_string() != self.unpaired_service_name {
+            self.batch_barrier.wait();
+        }
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.install_count.fetch_add(1, Ordering::SeqCst);
+
+        if install_ctx.label.to_string() == self.failing_service_name {
+            return Err(ServiceControlError::ServiceUserAccountCreationFailed);
+        }
+        Ok(())
+    }
+
+    fn get_process_pid(&self, _bin_path: &Path) -> ServiceControlResult<u32> {
+        Err(unexpected_call("get_process_pid"))
+    }
+
+    fn is_service_process_running(&self, _pid: u32) -> bool {
+        false
+    }
+
+    fn start(&self, _service_name: &str, _user_mode: bool) -> ServiceControlResult<()> {
+        Err(unexpected_call("start"))
+    }
+
+    fn stop(&self, _service_name: &str, _user_mode: bool) -> ServiceControlResult<()> {
+        Err(unexpected_call("stop"))
+    }
+
+    fn uninstall(&self, _service_name: &str, _user_mode: bool) -> ServiceControlResult<()> {
+        Err(unexpected_call("uninstall"))
+    }
+
+    fn wait(&self, _delay: u64) {}
+}
+
+fn unexpected_call(method: &str) -> ServiceControlError {
+    ServiceControlError::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("'{method}' is not expected to be called while adding services"),
+    ))
+}
+
 #[cfg(target_os = "windows")]
 fn get_username() -> String {
     std::env::var("USERNAME").expect("Failed to get username")
@@ -95,6 +184,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -114,14 +204,23 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: true,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: true,
+        health_port: None,
         home_network: false,
         local: true,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -132,6 +231,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
     mock_service_control
@@ -146,17 +246,28 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: true,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: true,
+            health_port: None,
             home_network: false,
             local: true,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -214,19 +325,28 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: true,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             pid: None,
@@ -262,17 +382,28 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: true,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: true,
+            health_port: None,
             home_network: false,
             local: true,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: Some(custom_rpc_address),
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -308,6 +439,7 @@ async fn add_genesis_node_should_return_an_error_if_count_is_greater_than_1() ->
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -330,17 +462,28 @@ async fn add_genesis_node_should_return_an_error_if_count_is_greater_than_1() ->
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: true,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: true,
+            health_port: None,
             home_network: false,
             local: true,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -366,6 +509,86 @@ async fn add_genesis_node_should_return_an_error_if_count_is_greater_than_1() ->
     Ok(())
 }
 
+#[tokio::test]
+async fn add_genesis_node_should_return_an_error_if_it_is_not_confirmed() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mock_service_control = MockServiceControl::new();
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("safenode1");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: None,
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: true,
+            health_port: None,
+            home_network: false,
+            local: true,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: None,
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    assert_eq!(
+        Err("A genesis node starts a new network, so it must be explicitly confirmed with --confirm-genesis".to_string()),
+        result.map_err(|e| e.to_string())
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn add_node_should_use_latest_version_and_add_three_services() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
@@ -376,6 +599,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -405,14 +629,23 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -423,6 +656,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -442,14 +676,23 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode2"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode2".to_string(),
         node_port: None,
         owner: None,
@@ -460,6 +703,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -480,13 +724,22 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         autostart: false,
         data_dir_path: node_data_dir.to_path_buf().join("safenode3"),
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode3"),
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode3".to_string(),
         node_port: None,
         owner: None,
@@ -497,6 +750,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -512,17 +766,28 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -609,6 +874,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -636,14 +902,23 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: new_peers.clone(),
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -654,6 +929,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
     mock_service_control
@@ -668,17 +944,28 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: new_peers.clone(),
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
             local: false,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -740,6 +1027,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -766,14 +1054,23 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: env_variables.clone(),
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -784,6 +1081,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
     mock_service_control
@@ -798,17 +1096,28 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: env_variables.clone(),
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -865,19 +1174,28 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: true,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -914,14 +1232,23 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode2"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode2".to_string(),
         node_port: None,
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8083),
@@ -932,6 +1259,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -947,17 +1275,28 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_src_path: safenode_download_path.to_path_buf(),
@@ -1008,6 +1347,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -1036,14 +1376,23 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: Some(custom_port),
         owner: None,
@@ -1054,6 +1403,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -1069,17 +1419,28 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: Some(PortRange::Single(custom_port)),
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -1117,6 +1478,7 @@ async fn add_node_should_use_a_custom_port_range() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -1287,17 +1649,28 @@ async fn add_node_should_use_a_custom_port_range() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: Some(PortRange::Range(12000, 12002)),
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -1326,42 +1699,35 @@ async fn add_node_should_use_a_custom_port_range() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn filter_available_ports_should_exclude_a_port_that_is_in_use() -> Result<()> {
+    let listener = std::net::TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    let used_port = listener.local_addr()?.port();
+    let free_port = std::net::TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?
+        .local_addr()?
+        .port();
+
+    let available_ports = filter_available_ports([used_port, free_port].into_iter());
+    assert_eq!(available_ports, vec![free_port]);
+
+    drop(listener);
+    Ok(())
+}
+
 #[tokio::test]
-async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> Result<()> {
+async fn add_node_should_return_an_error_if_the_rpc_port_is_in_use_on_the_host() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
     let node_reg_path = tmp_data_dir.child("node_reg.json");
 
+    let mock_service_control = MockServiceControl::new();
+
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
-        nodes: vec![NodeServiceData {
-            auto_restart: false,
-            connected_peers: None,
-            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
-            genesis: false,
-            home_network: false,
-            listen_addr: None,
-            local: false,
-            log_format: None,
-            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
-            metrics_port: None,
-            node_port: Some(12000),
-            number: 1,
-            owner: None,
-            peer_id: None,
-            pid: None,
-            reward_balance: Some(NanoTokens::zero()),
-            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
-            service_name: "safenode1".to_string(),
-            status: ServiceStatus::Added,
-            upnp: false,
-            user: Some("safe".to_string()),
-            user_mode: false,
-            version: "0.98.1".to_string(),
-        }],
+        nodes: vec![],
         bootstrap_peers: vec![],
         environment_variables: None,
         daemon: None,
@@ -1375,24 +1741,38 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> R
     let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
     safenode_download_path.write_binary(b"fake safenode bin")?;
 
+    let listener = std::net::TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    let used_port = listener.local_addr()?.port();
+
     let result = add_node(
         AddNodeServiceOptions {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
-            node_port: Some(PortRange::Single(12000)),
+            node_port: None,
+            resume: false,
             rpc_address: None,
-            rpc_port: None,
+            rpc_port: Some(PortRange::Single(used_port)),
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
@@ -1403,56 +1783,40 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> R
             version: latest_version.to_string(),
         },
         &mut node_registry,
-        &MockServiceControl::new(),
+        &mock_service_control,
         VerbosityLevel::Normal,
     )
     .await;
 
     match result {
-        Ok(_) => panic!("This test is supposed to result in a failure"),
-        Err(e) => {
-            assert_eq!(e.to_string(), "Port 12000 is being used by another service");
-            Ok(())
-        }
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            format!("Port {used_port} is already in use on this host"),
+            e.to_string()
+        ),
     }
+    assert!(node_registry.nodes.is_empty());
+
+    drop(listener);
+    Ok(())
 }
 
 #[tokio::test]
-async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_used() -> Result<()> {
+async fn add_node_should_bound_concurrent_installs_and_report_partial_failure() -> Result<()> {
+    // The suggestion is only attached to the report when the color-eyre handler is installed. It
+    // may already have been installed by another test.
+    let _ = color_eyre::install();
+
     let tmp_data_dir = assert_fs::TempDir::new()?;
     let node_reg_path = tmp_data_dir.child("node_reg.json");
 
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
-        nodes: vec![NodeServiceData {
-            auto_restart: false,
-            connected_peers: None,
-            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
-            genesis: false,
-            home_network: false,
-            listen_addr: None,
-            local: false,
-            log_format: None,
-            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
-            metrics_port: None,
-            node_port: Some(12000),
-            number: 1,
-            owner: None,
-            peer_id: None,
-            pid: None,
-            reward_balance: Some(NanoTokens::zero()),
-            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
-            service_name: "safenode1".to_string(),
-            status: ServiceStatus::Added,
-            upnp: false,
-            user: Some("safe".to_string()),
-            user_mode: false,
-            version: "0.98.1".to_string(),
-        }],
+        nodes: vec![],
         bootstrap_peers: vec![],
         environment_variables: None,
         daemon: None,
@@ -1466,24 +1830,44 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_us
     let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
     safenode_download_path.write_binary(b"fake safenode bin")?;
 
+    let service_control = ConcurrencyTrackingServiceControl {
+        batch_barrier: Barrier::new(2),
+        failing_service_name: "safenode3".to_string(),
+        in_flight: AtomicUsize::new(0),
+        install_count: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
+        unpaired_service_name: "safenode5".to_string(),
+    };
+
     let result = add_node(
         AddNodeServiceOptions {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
-            count: Some(3),
+            confirm_genesis: false,
+            count: Some(5),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 2,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
-            node_port: Some(PortRange::Range(12000, 12002)),
+            node_port: Some(PortRange::Range(12000, 12004)),
+            resume: false,
             rpc_address: None,
-            rpc_port: None,
+            rpc_port: Some(PortRange::Range(15000, 15004)),
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
@@ -1494,34 +1878,65 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_us
             version: latest_version.to_string(),
         },
         &mut node_registry,
-        &MockServiceControl::new(),
+        &service_control,
         VerbosityLevel::Normal,
     )
     .await;
 
     match result {
-        Ok(_) => panic!("This test is supposed to result in a failure"),
+        Ok(_) => panic!("This test should result in an error"),
         Err(e) => {
-            assert_eq!(e.to_string(), "Port 12000 is being used by another service");
-            Ok(())
+            assert_eq!("Failed to add one or more services", e.to_string());
+            assert!(format!("{e:?}").contains(
+                "the services that were successfully added will be usable: \
+                safenode1, safenode2, safenode4, safenode5"
+            ));
         }
     }
+
+    assert_eq!(service_control.install_count.load(Ordering::SeqCst), 5);
+    assert_eq!(service_control.max_in_flight.load(Ordering::SeqCst), 2);
+    assert_eq!(node_registry.nodes.len(), 4);
+    let added = node_registry
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.service_name.as_str(),
+                node.node_port,
+                node.rpc_socket_addr.port(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        added,
+        vec![
+            ("safenode1", Some(12000), 15000),
+            ("safenode2", Some(12001), 15001),
+            ("safenode4", Some(12003), 15003),
+            ("safenode5", Some(12004), 15004),
+        ]
+    );
+
+    Ok(())
 }
 
 #[tokio::test]
-async fn add_node_should_return_an_error_if_port_and_node_count_do_not_match() -> Result<()> {
+async fn add_node_should_resume_an_interrupted_add_by_adding_only_the_missing_nodes() -> Result<()>
+{
     let tmp_data_dir = assert_fs::TempDir::new()?;
     let node_reg_path = tmp_data_dir.child("node_reg.json");
 
     let mut node_registry = NodeRegistry {
         auditor: None,
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
         faucet: None,
-        save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
-        bootstrap_peers: vec![],
-        environment_variables: None,
-        daemon: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
     };
     let latest_version = "0.96.4";
     let temp_dir = assert_fs::TempDir::new()?;
@@ -1532,24 +1947,41 @@ async fn add_node_should_return_an_error_if_port_and_node_count_do_not_match() -
     let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
     safenode_download_path.write_binary(b"fake safenode bin")?;
 
+    let interrupted_service_control = ConcurrencyTrackingServiceControl {
+        failing_service_name: "safenode3".to_string(),
+        in_flight: AtomicUsize::new(0),
+        install_count: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
+    };
     let result = add_node(
         AddNodeServiceOptions {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
-            count: Some(2),
-            delete_safenode_src: true,
+            confirm_genesis: false,
+            count: Some(5),
+            cpu_quota: None,
+            delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
-            node_port: Some(PortRange::Range(12000, 12002)),
+            node_port: Some(PortRange::Range(12000, 12004)),
+            resume: false,
             rpc_address: None,
-            rpc_port: None,
+            rpc_port: Some(PortRange::Range(15000, 15004)),
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
@@ -1560,67 +1992,55 @@ async fn add_node_should_return_an_error_if_port_and_node_count_do_not_match() -
             version: latest_version.to_string(),
         },
         &mut node_registry,
-        &MockServiceControl::new(),
+        &interrupted_service_control,
         VerbosityLevel::Normal,
     )
     .await;
+    assert!(result.is_err());
+    assert_eq!(node_registry.nodes.len(), 4);
+    assert_eq!(
+        node_registry.pending_node_add,
+        Some(PendingNodeAdd {
+            count: 5,
+            node_numbers: vec![1, 2, 3, 4, 5],
+        })
+    );
 
-    match result {
-        Ok(_) => panic!("This test should result in an error"),
-        Err(e) => {
-            assert_eq!(
-                format!("The number of services to add (2) does not match the number of ports (3)"),
-                e.to_string()
-            )
-        }
-    }
-
-    Ok(())
-}
-
-#[tokio::test]
-async fn add_node_should_return_an_error_if_multiple_services_are_specified_with_a_single_port(
-) -> Result<()> {
-    let tmp_data_dir = assert_fs::TempDir::new()?;
-    let node_reg_path = tmp_data_dir.child("node_reg.json");
-
-    let mut node_registry = NodeRegistry {
-        auditor: None,
-        faucet: None,
-        save_path: node_reg_path.to_path_buf(),
-        nat_status: None,
-        nodes: vec![],
-        bootstrap_peers: vec![],
-        environment_variables: None,
-        daemon: None,
+    let resumed_service_control = ConcurrencyTrackingServiceControl {
+        failing_service_name: String::new(),
+        in_flight: AtomicUsize::new(0),
+        install_count: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
     };
-    let latest_version = "0.96.4";
-    let temp_dir = assert_fs::TempDir::new()?;
-    let node_data_dir = temp_dir.child("data");
-    node_data_dir.create_dir_all()?;
-    let node_logs_dir = temp_dir.child("logs");
-    node_logs_dir.create_dir_all()?;
-    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
-    safenode_download_path.write_binary(b"fake safenode bin")?;
-
-    let result = add_node(
+    let added_services = add_node(
         AddNodeServiceOptions {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
-            count: Some(2),
-            delete_safenode_src: true,
+            confirm_genesis: false,
+            count: Some(5),
+            cpu_quota: None,
+            delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
-            node_port: Some(PortRange::Single(12000)),
+            node_port: Some(PortRange::Range(12000, 12004)),
+            resume: true,
             rpc_address: None,
-            rpc_port: None,
+            rpc_port: Some(PortRange::Range(15000, 15004)),
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
@@ -1631,14 +2051,558 @@ async fn add_node_should_return_an_error_if_multiple_services_are_specified_with
             version: latest_version.to_string(),
         },
         &mut node_registry,
-        &MockServiceControl::new(),
+        &resumed_service_control,
         VerbosityLevel::Normal,
     )
-    .await;
+    .await?;
 
-    match result {
-        Ok(_) => panic!("This test should result in an error"),
-        Err(e) => {
+    assert_eq!(added_services, vec!["safenode3".to_string()]);
+    assert_eq!(
+        resumed_service_control.install_count.load(Ordering::SeqCst),
+        1
+    );
+    assert_eq!(node_registry.nodes.len(), 5);
+    let resumed_node = node_registry
+        .nodes
+        .iter()
+        .find(|node| node.service_name == "safenode3")
+        .expect("safenode3 should have been added");
+    assert_eq!(resumed_node.number, 3);
+    assert_eq!(resumed_node.node_port, Some(12002));
+    assert_eq!(resumed_node.rpc_socket_addr.port(), 15002);
+    assert!(node_registry.pending_node_add.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_node_should_discard_an_interrupted_add_that_is_not_resumed() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nat_status: None,
+        nodes: vec![],
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let interrupted_service_control = ConcurrencyTrackingServiceControl {
+        failing_service_name: "safenode3".to_string(),
+        in_flight: AtomicUsize::new(0),
+        install_count: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
+    };
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: Some(5),
+            cpu_quota: None,
+            delete_safenode_src: false,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Range(12000, 12004)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: Some(PortRange::Range(15000, 15004)),
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &interrupted_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!(node_registry.nodes.len(), 4);
+    assert_eq!(
+        node_registry.pending_node_add,
+        Some(PendingNodeAdd {
+            count: 5,
+            node_numbers: vec![1, 2, 3, 4, 5],
+        })
+    );
+
+    let new_service_control = ConcurrencyTrackingServiceControl {
+        failing_service_name: String::new(),
+        in_flight: AtomicUsize::new(0),
+        install_count: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
+    };
+    let added_services = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: Some(5),
+            cpu_quota: None,
+            delete_safenode_src: false,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Range(12005, 12009)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: Some(PortRange::Range(15005, 15009)),
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &new_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await?;
+
+    assert_eq!(
+        added_services,
+        (6..=10)
+            .map(|number| format!("safenode{number}"))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(new_service_control.install_count.load(Ordering::SeqCst), 5);
+    assert_eq!(node_registry.nodes.len(), 9);
+    assert!(!node_registry
+        .nodes
+        .iter()
+        .any(|node| node.service_name == "safenode3"));
+    assert!(node_registry.pending_node_add.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_format: None,
+            log_level: None,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: Some(12000),
+            number: 1,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Added,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: None,
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Single(12000)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &MockServiceControl::new(),
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test is supposed to result in a failure"),
+        Err(e) => {
+            assert_eq!(e.to_string(), "Port 12000 is being used by another service");
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_used() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_format: None,
+            log_level: None,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: Some(12000),
+            number: 1,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Added,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: Some(3),
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Range(12000, 12002)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &MockServiceControl::new(),
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test is supposed to result in a failure"),
+        Err(e) => {
+            assert_eq!(e.to_string(), "Port 12000 is being used by another service");
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn add_node_should_return_an_error_if_port_and_node_count_do_not_match() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: Some(2),
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Range(12000, 12002)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &MockServiceControl::new(),
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => {
+            assert_eq!(
+                format!("The number of services to add (2) does not match the number of ports (3)"),
+                e.to_string()
+            )
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_node_should_return_an_error_if_multiple_services_are_specified_with_a_single_port(
+) -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: Some(2),
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: Some(PortRange::Single(12000)),
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &MockServiceControl::new(),
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => {
             assert_eq!(
                 format!("The number of services to add (2) does not match the number of ports (1)"),
                 e.to_string()
@@ -1659,6 +2623,7 @@ async fn add_node_should_set_random_ports_if_enable_metrics_server_is_true() ->
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -1732,17 +2697,28 @@ async fn add_node_should_set_random_ports_if_enable_metrics_server_is_true() ->
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: true,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -1774,6 +2750,7 @@ async fn add_node_should_use_a_custom_port_range_for_metrics_server() -> Result<
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -1944,17 +2921,28 @@ async fn add_node_should_use_a_custom_port_range_for_metrics_server() -> Result<
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: Some(PortRange::Range(12000, 12002)),
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -1988,19 +2976,139 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_is_use
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: Some(12000),
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Added,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: None,
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            local: false,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: Some(PortRange::Single(12000)),
+            min_connections: None,
+            owner: None,
+            node_port: None,
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &MockServiceControl::new(),
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test is supposed to result in a failure"),
+        Err(e) => {
+            assert_eq!(e.to_string(), "Port 12000 is being used by another service");
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn add_node_should_return_an_error_if_a_port_is_another_node_health_port() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: None,
+        nodes: vec![NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: Some(12000),
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2034,17 +3142,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_is_use
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: Some(PortRange::Single(12000)),
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -2080,19 +3199,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_in_ran
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: Some(12000),
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2126,17 +3254,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_in_ran
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: Some(PortRange::Range(12000, 12002)),
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -2173,6 +3312,7 @@ async fn add_node_should_use_a_custom_port_range_for_the_rpc_server() -> Result<
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -2322,17 +3462,28 @@ async fn add_node_should_use_a_custom_port_range_for_the_rpc_server() -> Result<
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(3),
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: Some(PortRange::Range(20000, 20002)),
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -2377,19 +3528,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_is_used()
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2423,17 +3583,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_is_used()
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: Some(PortRange::Single(8081)),
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -2469,19 +3640,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_in_range_i
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2515,17 +3695,28 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_in_range_i
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: Some(PortRange::Range(8081, 8082)),
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -2562,6 +3753,7 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: Some(NatDetectionStatus::Public),
         nodes: vec![],
@@ -2589,14 +3781,148 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
+        data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
+        env_variables: None,
+        ephemeral: false,
+        genesis: false,
+        health_port: None,
+        home_network: false,
+        local: false,
+        log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
+        metrics_port: None,
+        min_connections: None,
+        name: "safenode1".to_string(),
+        node_port: None,
+        owner: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
+        safenode_path: node_data_dir
+            .to_path_buf()
+            .join("safenode1")
+            .join(SAFENODE_FILE_NAME),
+        service_user: Some(get_username()),
+        upnp: false,
+        user_mode: false,
+    }
+    .build()?;
+    mock_service_control
+        .expect_install()
+        .times(1)
+        .with(eq(install_ctx), eq(false))
+        .returning(|_, _| Ok(()))
+        .in_sequence(&mut seq);
+
+    add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: true,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: None,
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            local: false,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: true,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: None,
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: true,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await?;
+
+    assert!(!node_registry.nodes[0].upnp);
+    assert!(!node_registry.nodes[0].home_network);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let mut mock_service_control = MockServiceControl::new();
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        faucet: None,
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+        nat_status: Some(NatDetectionStatus::UPnP),
+        nodes: vec![],
+        bootstrap_peers: vec![],
+        environment_variables: None,
+        daemon: None,
+    };
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let mut seq = Sequence::new();
+
+    mock_service_control
+        .expect_get_available_port()
+        .times(1)
+        .returning(|| Ok(12001))
+        .in_sequence(&mut seq);
+
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        autostart: false,
+        bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -2606,7 +3932,8 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
             .join("safenode1")
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
-        upnp: false,
+        upnp: true,
+        user_mode: false,
     }
     .build()?;
     mock_service_control
@@ -2621,24 +3948,35 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
             auto_restart: false,
             auto_set_nat_flags: true,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
             local: false,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: true,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
             service_log_dir_path: node_logs_dir.to_path_buf(),
-            upnp: true,
+            upnp: false,
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
@@ -2649,14 +3987,14 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
     )
     .await?;
 
-    assert!(!node_registry.nodes[0].upnp);
+    assert!(node_registry.nodes[0].upnp);
     assert!(!node_registry.nodes[0].home_network);
 
     Ok(())
 }
 
 #[tokio::test]
-async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
+async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
     let node_reg_path = tmp_data_dir.child("node_reg.json");
 
@@ -2665,8 +4003,9 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
-        nat_status: Some(NatDetectionStatus::UPnP),
+        nat_status: Some(NatDetectionStatus::Private),
         nodes: vec![],
         bootstrap_peers: vec![],
         environment_variables: None,
@@ -2692,14 +4031,23 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
-        home_network: false,
+        health_port: None,
+        home_network: true,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -2709,7 +4057,8 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
             .join("safenode1")
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
-        upnp: true,
+        upnp: false,
+        user_mode: false,
     }
     .build()?;
     mock_service_control
@@ -2724,24 +4073,35 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: true,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
             local: false,
+            ephemeral: false,
             genesis: false,
-            home_network: true,
+            health_port: None,
+            home_network: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
             safenode_src_path: safenode_download_path.to_path_buf(),
             service_data_dir_path: node_data_dir.to_path_buf(),
             service_log_dir_path: node_logs_dir.to_path_buf(),
-            upnp: false,
+            upnp: true,
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
@@ -2752,14 +4112,15 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
     )
     .await?;
 
-    assert!(node_registry.nodes[0].upnp);
-    assert!(!node_registry.nodes[0].home_network);
+    assert!(!node_registry.nodes[0].upnp);
+    assert!(node_registry.nodes[0].home_network);
 
     Ok(())
 }
 
 #[tokio::test]
-async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Result<()> {
+async fn add_node_should_return_an_error_if_nat_status_is_none_but_auto_set_nat_flags_is_enabled(
+) -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
     let node_reg_path = tmp_data_dir.child("node_reg.json");
 
@@ -2768,8 +4129,9 @@ async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Resul
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
-        nat_status: Some(NatDetectionStatus::Private),
+        nat_status: None,
         nodes: vec![],
         bootstrap_peers: vec![],
         environment_variables: None,
@@ -2792,156 +4154,934 @@ async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Resul
         .returning(|| Ok(12001))
         .in_sequence(&mut seq);
 
-    let install_ctx = InstallNodeServiceCtxBuilder {
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: true,
+            bootstrap_peers: vec![],
+            confirm_genesis: false,
+            count: None,
+            cpu_quota: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_file: None,
+            env_variables: None,
+            local: false,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: true,
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            owner: None,
+            node_port: None,
+            resume: false,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => {
+            assert_eq!(
+                format!("NAT status has not been set. Run 'nat-detection' first"),
+                e.to_string()
+            )
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse_node_service_ports_should_return_the_ports_of_a_fully_configured_node() -> Result<()> {
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        autostart: true,
+        bootstrap_peers: vec![
+            "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+                .parse()?,
+        ],
+        confirm_genesis: false,
+        cpu_quota: None,
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        env_file: None,
+        env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
+        ephemeral: false,
+        genesis: false,
+        health_port: None,
+        home_network: true,
+        local: true,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
+        metrics_port: Some(14000),
+        min_connections: None,
+        name: "safenode1".to_string(),
+        node_port: Some(12000),
+        owner: Some("discord_username".to_string()),
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 13000),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        service_user: Some("safe".to_string()),
+        upnp: true,
+        user_mode: false,
+    }
+    .build()?;
+
+    assert_eq!(
+        parse_node_service_ports(&install_ctx),
+        NodeServicePorts {
+            metrics_port: Some(14000),
+            node_port: Some(12000),
+            rpc_port: Some(13000),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parse_node_service_ports_should_return_none_for_unassigned_ports() -> Result<()> {
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        autostart: false,
+        bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        env_file: None,
+        env_variables: None,
+        ephemeral: false,
+        genesis: false,
+        health_port: None,
+        home_network: false,
+        local: false,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
+        metrics_port: None,
+        min_connections: None,
+        name: "safenode1".to_string(),
+        node_port: None,
+        owner: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 13000),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        service_user: None,
+        upnp: false,
+        user_mode: false,
+    }
+    .build()?;
+
+    assert_eq!(
+        parse_node_service_ports(&install_ctx),
+        NodeServicePorts {
+            metrics_port: None,
+            node_port: None,
+            rpc_port: Some(13000),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_use_a_separate_root_dir_for_an_ephemeral_node(
+) -> Result<()> {
+    let install_ctx = InstallNodeServiceCtxBuilder {
+        autostart: false,
+        bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
+        data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+        env_file: None,
+        env_variables: None,
+        ephemeral: true,
+        genesis: false,
+        health_port: None,
+        home_network: false,
+        local: false,
+        log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
+        metrics_port: None,
+        min_connections: None,
+        name: "safenode1".to_string(),
+        node_port: None,
+        owner: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 13000),
+        safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        service_user: None,
+        upnp: false,
+        user_mode: false,
+    }
+    .build()?;
+
+    assert_eq!(
+        install_ctx.args,
+        vec![
+            OsString::from("--rpc"),
+            OsString::from("127.0.0.1:13000"),
+            OsString::from("--root-dir"),
+            OsString::from("/var/safenode-manager/services/safenode1/ephemeral"),
+            OsString::from("--log-output-dest"),
+            OsString::from("/var/log/safenode/safenode1"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_return_an_error_if_the_node_port_is_the_rpc_port(
+) -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.node_port = Some(13000);
+    let result = builder.build();
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            "The node port 13000 cannot be the same as the RPC port",
+            e.to_string()
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_return_an_error_if_the_node_port_is_the_metrics_port(
+) -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.metrics_port = Some(12000);
+    builder.node_port = Some(12000);
+    let result = builder.build();
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            "The node port 12000 cannot be the same as the metrics port",
+            e.to_string()
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_validate_all_should_report_every_problem() -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.bootstrap_peers = vec![
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+            .parse()?,
+    ];
+    builder.confirm_genesis = true;
+    builder.genesis = true;
+    builder.metrics_port = Some(13000);
+    builder.node_port = Some(13000);
+
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![
+            BuilderError::NodePortMatchesRpcPort(13000),
+            BuilderError::NodePortMatchesMetricsPort(13000),
+            BuilderError::MetricsPortMatchesRpcPort(13000),
+            BuilderError::GenesisWithBootstrapPeers,
+        ])
+    );
+
+    match builder.build() {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            "The node port 13000 cannot be the same as the RPC port; \
+             The node port 13000 cannot be the same as the metrics port; \
+             The metrics port 13000 cannot be the same as the RPC port; \
+             A genesis node cannot be given bootstrap peers",
+            e.to_string()
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_set_the_log_level_as_the_sn_log_variable() -> Result<()>
+{
+    let mut builder = default_node_builder();
+    builder.env_variables = Some(vec![
+        ("SN_LOG".to_string(), "all".to_string()),
+        ("OTHER".to_string(), "value".to_string()),
+    ]);
+    builder.log_level = Some("sn_node=debug,libp2p=info".to_string());
+    let install_ctx = builder.build()?;
+
+    assert_eq!(
+        install_ctx.environment,
+        Some(vec![
+            ("OTHER".to_string(), "value".to_string()),
+            (
+                "SN_LOG".to_string(),
+                "sn_node=debug,libp2p=info".to_string()
+            ),
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_reject_a_malformed_log_level() {
+    let mut builder = default_node_builder();
+    builder.log_level = Some("sn_node=loud".to_string());
+
+    assert_matches!(
+        builder.validate_all(),
+        Err(errors) if matches!(
+            errors.as_slice(),
+            [BuilderError::InvalidLogLevel { log_level, .. }] if log_level == "sn_node=loud"
+        )
+    );
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_return_an_error_for_an_unconfirmed_genesis_node() {
+    let mut builder = default_node_builder();
+    builder.genesis = true;
+    let result = builder.build();
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            "A genesis node starts a new network, so it must be explicitly confirmed",
+            e.to_string()
+        ),
+    }
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_add_the_first_flag_for_a_confirmed_genesis_node(
+) -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.confirm_genesis = true;
+    builder.genesis = true;
+    let install_ctx = builder.build()?;
+
+    assert!(install_ctx.args.contains(&OsString::from("--first")));
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_add_the_health_port_alongside_the_metrics_port(
+) -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.health_port = Some(13002);
+    builder.metrics_port = Some(13001);
+    let install_ctx = builder.build()?;
+
+    let args = install_ctx
+        .args
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["--metrics-server-port", "13001"]));
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["--health-port", "13002"]));
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_return_an_error_for_a_health_port_matching_the_metrics_port(
+) {
+    let mut builder = default_node_builder();
+    builder.health_port = Some(13001);
+    builder.metrics_port = Some(13001);
+    let result = builder.build();
+
+    match result {
+        Ok(_) => panic!("This test should result in an error"),
+        Err(e) => assert_eq!(
+            "The health port 13001 cannot be the same as the metrics port",
+            e.to_string()
+        ),
+    }
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_add_the_maintenance_window_flags() -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.maintenance_window = Some((
+        NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+        NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+    ));
+    let install_ctx = builder.build()?;
+
+    let args = install_ctx
+        .args
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    assert!(args
+        .windows(4)
+        .any(|window| window == ["--maintenance-start", "23:00", "--maintenance-end", "01:30"]));
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_return_an_error_for_an_empty_maintenance_window() {
+    let time = NaiveTime::from_hms_opt(2, 0, 0).unwrap();
+    let mut builder = default_node_builder();
+    builder.maintenance_window = Some((time, time));
+
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![BuilderError::MaintenanceWindowIsEmpty(time)])
+    );
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_add_the_min_connections_flag() -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.min_connections = Some(25);
+
+    let args = builder
+        .build()?
+        .args
+        .iter()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["--min-connections", "25"]));
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_reject_an_absurd_min_connections() {
+    let mut builder = default_node_builder();
+    builder.min_connections = Some(100_000);
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![BuilderError::InvalidMinConnections(100_000)])
+    );
+    builder.min_connections = Some(0);
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![BuilderError::InvalidMinConnections(0)])
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_node_service_ctx_builder_should_add_resource_limits_to_the_unit_contents() -> Result<()>
+{
+    let mut builder = default_node_builder();
+    builder.cpu_quota = Some(1.5);
+    builder.memory_max = Some(ByteSize(2 * 1024 * 1024 * 1024));
+    builder.service_user = Some("safe".to_string());
+
+    let install_ctx = builder.build()?;
+    let contents = install_ctx
+        .contents
+        .expect("the unit contents to be generated");
+    let lines = contents.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"CPUQuota=150%"));
+    assert!(lines.contains(&"MemoryMax=2147483648"));
+    assert!(lines.contains(&"User=safe"));
+    assert!(lines.contains(&"WantedBy=multi-user.target"));
+    assert!(lines.iter().any(
+        |line| line.starts_with("ExecStart=") && line.contains(r#""--rpc" "127.0.0.1:13000""#)
+    ));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_node_service_ctx_builder_should_write_a_user_mode_unit_without_a_user() -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.memory_max = Some(ByteSize(1024 * 1024 * 1024));
+    builder.service_user = Some("bee".to_string());
+    builder.user_mode = true;
+
+    let contents = builder
+        .build()?
+        .contents
+        .expect("the unit contents to be generated");
+    let lines = contents.lines().collect::<Vec<_>>();
+    assert!(!lines.iter().any(|line| line.starts_with("User=")));
+    assert!(lines.contains(&"WantedBy=default.target"));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_node_service_ctx_builder_should_escape_the_values_in_the_unit_contents() -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.cpu_quota = Some(1.0);
+    builder.env_variables = Some(vec![(
+        "SN_NOTE".to_string(),
+        r#"say "hi" \ at 50%"#.to_string(),
+    )]);
+    builder.owner = Some(r#"the "owner""#.to_string());
+
+    let contents = builder
+        .build()?
+        .contents
+        .expect("the unit contents to be generated");
+    let lines = contents.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&r#"Environment="SN_NOTE=say \"hi\" \\ at 50%%""#));
+    assert!(lines.iter().any(
+        |line| line.starts_with("ExecStart=") && line.ends_with(r#""--owner" "the \"owner\"""#)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn parse_byte_size_should_accept_a_number_of_bytes_with_an_optional_suffix() -> Result<()> {
+    assert_eq!(parse_byte_size("4096")?, ByteSize(4096));
+    assert_eq!(parse_byte_size("512K")?, ByteSize(512 * 1024));
+    assert_eq!(parse_byte_size("2G")?, ByteSize(2 * 1024 * 1024 * 1024));
+    assert!(parse_byte_size("2GB").is_err());
+    assert!(parse_byte_size("").is_err());
+    assert!(parse_byte_size("16777216T").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_omit_the_unit_contents_without_resource_limits(
+) -> Result<()> {
+    let builder = default_node_builder();
+
+    assert_eq!(builder.build()?.contents, None);
+
+    Ok(())
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_reject_a_non_positive_cpu_quota() {
+    let mut builder = default_node_builder();
+    builder.cpu_quota = Some(0.0);
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![BuilderError::InvalidCpuQuota(0.0)])
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_node_service_ctx_builder_should_reference_the_env_file_in_the_unit_contents(
+) -> Result<()> {
+    let mut builder = default_node_builder();
+    builder.env_file = Some(PathBuf::from("/etc/safenode/safenode1.env"));
+
+    let install_ctx = builder.build()?;
+    assert_eq!(install_ctx.environment, None);
+    let contents = install_ctx
+        .contents
+        .expect("the unit contents to be generated");
+    let lines = contents.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"EnvironmentFile=\"/etc/safenode/safenode1.env\""));
+    assert!(!lines.iter().any(|line| line.starts_with("Environment=")));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn install_node_service_ctx_builder_should_reject_an_env_file_alongside_env_variables() {
+    let mut builder = default_node_builder();
+    builder.env_file = Some(PathBuf::from("safenode1.env"));
+    builder.env_variables = Some(vec![("SN_LOG".to_string(), "all".to_string())]);
+
+    assert_eq!(
+        builder.validate_all(),
+        Err(vec![
+            BuilderError::EnvFileNotAbsolute(PathBuf::from("safenode1.env")),
+            BuilderError::EnvFileWithEnvVariables,
+        ])
+    );
+    assert!(builder.build().is_err());
+}
+
+fn default_node_builder() -> InstallNodeServiceCtxBuilder {
+    let root = std::env::temp_dir().join("safenode-manager");
+    InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
-        data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        confirm_genesis: false,
+        cpu_quota: None,
+        data_dir_path: root.join("services").join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
-        home_network: true,
+        health_port: None,
+        home_network: false,
         local: false,
-        log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
+        log_dir_path: root.join("logs").join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
-        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
-        safenode_path: node_data_dir
-            .to_path_buf()
-            .join("safenode1")
-            .join(SAFENODE_FILE_NAME),
-        service_user: Some(get_username()),
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 13000),
+        safenode_path: root.join("services").join("safenode1").join("safenode"),
+        service_user: None,
         upnp: false,
+        user_mode: false,
     }
-    .build()?;
-    mock_service_control
-        .expect_install()
-        .times(1)
-        .with(eq(install_ctx), eq(false))
-        .returning(|_, _| Ok(()))
-        .in_sequence(&mut seq);
+}
 
-    add_node(
-        AddNodeServiceOptions {
-            auto_restart: false,
-            auto_set_nat_flags: true,
-            bootstrap_peers: vec![],
-            count: None,
-            delete_safenode_src: true,
-            enable_metrics_server: false,
-            env_variables: None,
-            local: false,
-            genesis: false,
-            home_network: false,
-            log_format: None,
-            metrics_port: None,
-            owner: None,
-            node_port: None,
-            rpc_address: None,
-            rpc_port: None,
-            safenode_dir_path: temp_dir.to_path_buf(),
-            safenode_src_path: safenode_download_path.to_path_buf(),
-            service_data_dir_path: node_data_dir.to_path_buf(),
-            service_log_dir_path: node_logs_dir.to_path_buf(),
-            upnp: true,
-            user: Some(get_username()),
-            user_mode: false,
-            version: latest_version.to_string(),
-        },
-        &mut node_registry,
-        &mock_service_control,
-        VerbosityLevel::Normal,
-    )
-    .await?;
+#[test]
+fn install_node_service_ctx_builder_validate_install_paths_should_accept_clean_absolute_paths() {
+    let root = std::env::temp_dir().join("safenode-manager");
+    let builder = default_node_builder();
 
-    assert!(!node_registry.nodes[0].upnp);
-    assert!(node_registry.nodes[0].home_network);
+    assert_eq!(builder.validate_install_paths(&[]), Ok(()));
+    assert_eq!(builder.validate_install_paths(&[root]), Ok(()));
+    assert_eq!(
+        builder.validate_install_paths(&[std::env::temp_dir().join("elsewhere")]),
+        Err(vec![
+            BuilderError::InstallPathOutsideRoots(builder.data_dir_path.clone()),
+            BuilderError::InstallPathOutsideRoots(builder.log_dir_path.clone()),
+            BuilderError::InstallPathOutsideRoots(builder.safenode_path.clone()),
+        ])
+    );
+}
+
+#[test]
+fn install_node_service_ctx_builder_validate_install_paths_should_reject_relative_and_traversal_paths(
+) {
+    let root = std::env::temp_dir().join("safenode-manager");
+    let relative = PathBuf::from("services").join("safenode1");
+    let traversal = root.join("..").join("..").join("safenode1");
+    let mut builder = default_node_builder();
+    builder.data_dir_path = relative.clone();
+    builder.log_dir_path = traversal.clone();
+
+    assert_eq!(
+        builder.validate_install_paths(&[root]),
+        Err(vec![
+            BuilderError::InstallPathNotAbsolute(relative),
+            BuilderError::InstallPathTraversal(traversal),
+        ])
+    );
+}
+
+#[test]
+fn install_node_service_ctx_builder_should_accept_distinct_node_rpc_and_metrics_ports() -> Result<()>
+{
+    let mut builder = default_node_builder();
+    builder.metrics_port = Some(14000);
+    builder.node_port = Some(12000);
+    let result = builder.build();
+
+    let install_ctx = result?;
+    assert_eq!(
+        parse_node_service_ports(&install_ctx),
+        NodeServicePorts {
+            metrics_port: Some(14000),
+            node_port: Some(12000),
+            rpc_port: Some(13000),
+        }
+    );
 
     Ok(())
 }
 
-#[tokio::test]
-async fn add_node_should_return_an_error_if_nat_status_is_none_but_auto_set_nat_flags_is_enabled(
-) -> Result<()> {
-    let tmp_data_dir = assert_fs::TempDir::new()?;
-    let node_reg_path = tmp_data_dir.child("node_reg.json");
+#[test]
+fn add_node_service_options_should_round_trip_through_yaml() -> Result<()> {
+    let yaml = r#"auto_restart: true
+auto_set_nat_flags: false
+bootstrap_peers:
+- /ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE
+count: 3
+delete_safenode_src: false
+enable_metrics_server: true
+env_variables:
+- - SN_LOG
+  - all
+genesis: false
+home_network: false
+local: false
+log_format: Json
+max_concurrent_installs: 2
+metrics_port: 14000-14002
+owner: discord_username
+node_port: 12000-12002
+rpc_address: 127.0.0.1
+rpc_port: null
+safenode_src_path: /tmp/safenode
+safenode_dir_path: /tmp
+service_data_dir_path: /var/safenode-manager/services
+service_log_dir_path: /var/log/safenode
+upnp: false
+user: safe
+user_mode: false
+version: 0.98.1
+"#;
+
+    let options = AddNodeServiceOptions::from_yaml(yaml)?;
+    assert_eq!(options.count, Some(3));
+    assert_eq!(options.log_format, Some(LogFormat::Json));
+    assert_eq!(options.metrics_port, Some(PortRange::Range(14000, 14002)));
+    assert_eq!(options.node_port, Some(PortRange::Range(12000, 12002)));
+    assert_eq!(options.rpc_port, None);
+    assert_eq!(options.rpc_address, Some(Ipv4Addr::new(127, 0, 0, 1)));
+    assert_eq!(
+        options.env_variables,
+        Some(vec![("SN_LOG".to_string(), "all".to_string())])
+    );
 
-    let mut mock_service_control = MockServiceControl::new();
+    let serialized = options.to_yaml()?;
+    assert!(serialized.contains("node_port: 12000-12002"));
+    assert_eq!(AddNodeServiceOptions::from_yaml(&serialized)?, options);
 
-    let mut node_registry = NodeRegistry {
+    Ok(())
+}
+
+#[test]
+fn add_node_service_options_summary_should_redact_secret_env_variables() -> Result<()> {
+    let options = AddNodeServiceOptions {
+        auto_restart: false,
+        auto_set_nat_flags: false,
+        bootstrap_peers: vec![
+            "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+                .parse()?,
+        ],
+        confirm_genesis: false,
+        count: Some(3),
+        cpu_quota: None,
+        delete_safenode_src: true,
+        enable_metrics_server: false,
+        env_file: None,
+        env_variables: Some(vec![
+            ("SN_LOG".to_string(), "all".to_string()),
+            ("BETA_SECRET_KEY".to_string(), "hunter2".to_string()),
+        ]),
+        ephemeral: false,
+        genesis: false,
+        health_port: None,
+        home_network: false,
+        local: false,
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        max_concurrent_installs: 1,
+        memory_max: None,
+        metrics_port: None,
+        min_connections: None,
+        owner: Some("discord_username".to_string()),
+        node_port: Some(PortRange::Range(12000, 12002)),
+        resume: false,
+        rpc_address: None,
+        rpc_port: None,
+        safenode_dir_path: PathBuf::from("/var/safenode-manager/services"),
+        safenode_src_path: PathBuf::from("/tmp/safenode"),
+        service_data_dir_path: PathBuf::from("/var/safenode-manager/services"),
+        service_log_dir_path: PathBuf::from("/var/log/safenode"),
+        upnp: false,
+        user: None,
+        user_mode: false,
+        version: "0.98.0".to_string(),
+    };
+
+    let summary = options.summary();
+    assert!(summary.contains("Count: 3"));
+    assert!(summary.contains("Network: 1 bootstrap peer(s)"));
+    assert!(summary.contains("Node ports: 12000-12002"));
+    assert!(summary.contains("SN_LOG=all"));
+    assert!(summary.contains("BETA_SECRET_KEY=********"));
+    assert!(!summary.contains("hunter2"));
+
+    Ok(())
+}
+
+#[test]
+fn export_fleet_manifest_should_capture_each_node_and_round_trip_through_yaml() -> Result<()> {
+    let peer: Multiaddr =
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+            .parse()?;
+    let node = |number: u16| NodeServiceData {
+        auto_restart: false,
+        connected_peers: None,
+        cpu_quota: None,
+        data_dir_path: PathBuf::from(format!("/var/safenode-manager/services/safenode{number}")),
+        env_file: None,
+        ephemeral: false,
+        genesis: false,
+        health_port: None,
+        home_network: false,
+        listen_addr: None,
+        local: false,
+        log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+        log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
+        metrics_port: None,
+        min_connections: None,
+        node_port: Some(12000 + number),
+        number,
+        owner: Some("discord_username".to_string()),
+        peer_id: None,
+        pid: None,
+        reward_balance: None,
+        rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 13000 + number),
+        safenode_path: PathBuf::from(format!(
+            "/var/safenode-manager/services/safenode{number}/safenode"
+        )),
+        service_name: format!("safenode{number}"),
+        status: ServiceStatus::Running,
+        upnp: false,
+        user: Some("safe".to_string()),
+        user_mode: false,
+        version: "0.98.1".to_string(),
+    };
+    let node_registry = NodeRegistry {
         auditor: None,
+        bootstrap_peers: vec![peer.clone()],
+        daemon: None,
+        environment_variables: None,
         faucet: None,
-        save_path: node_reg_path.to_path_buf(),
         nat_status: None,
-        nodes: vec![],
-        bootstrap_peers: vec![],
-        environment_variables: None,
-        daemon: None,
+        nodes: vec![node(1), node(2)],
+        pending_node_add: None,
+        save_path: PathBuf::from("/var/safenode-manager/node_registry.json"),
     };
-    let latest_version = "0.96.4";
-    let temp_dir = assert_fs::TempDir::new()?;
-    let node_data_dir = temp_dir.child("data");
-    node_data_dir.create_dir_all()?;
-    let node_logs_dir = temp_dir.child("logs");
-    node_logs_dir.create_dir_all()?;
-    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
-    safenode_download_path.write_binary(b"fake safenode bin")?;
 
-    let mut seq = Sequence::new();
+    let manifest = export_fleet_manifest(&node_registry)?;
+
+    assert_eq!(manifest.nodes.len(), 2);
+    assert_eq!(manifest.nodes[0].node_port, Some(PortRange::Single(12001)));
+    assert_eq!(manifest.nodes[1].node_port, Some(PortRange::Single(12002)));
+    assert_eq!(manifest.nodes[0].rpc_port, Some(PortRange::Single(13001)));
+    assert_eq!(manifest.nodes[1].rpc_port, Some(PortRange::Single(13002)));
+    for options in &manifest.nodes {
+        assert_eq!(options.bootstrap_peers, vec![peer.clone()]);
+        assert_eq!(options.count, Some(1));
+        assert_eq!(
+            options.service_data_dir_path,
+            PathBuf::from("/var/safenode-manager/services")
+        );
+        assert_eq!(
+            options.service_log_dir_path,
+            PathBuf::from("/var/log/safenode")
+        );
+    }
+    assert_eq!(FleetManifest::from_yaml(&manifest.to_yaml()?)?, manifest);
 
-    mock_service_control
-        .expect_get_available_port()
-        .times(1)
-        .returning(|| Ok(12001))
-        .in_sequence(&mut seq);
+    Ok(())
+}
 
-    let result = add_node(
-        AddNodeServiceOptions {
-            auto_restart: false,
-            auto_set_nat_flags: true,
-            bootstrap_peers: vec![],
-            count: None,
-            delete_safenode_src: true,
-            enable_metrics_server: false,
-            env_variables: None,
-            local: false,
-            genesis: false,
-            home_network: true,
-            log_format: None,
-            metrics_port: None,
-            owner: None,
-            node_port: None,
-            rpc_address: None,
-            rpc_port: None,
-            safenode_dir_path: temp_dir.to_path_buf(),
-            safenode_src_path: safenode_download_path.to_path_buf(),
-            service_data_dir_path: node_data_dir.to_path_buf(),
-            service_log_dir_path: node_logs_dir.to_path_buf(),
-            upnp: false,
-            user: Some(get_username()),
-            user_mode: false,
-            version: latest_version.to_string(),
-        },
-        &mut node_registry,
-        &mock_service_control,
-        VerbosityLevel::Normal,
-    )
-    .await;
+#[test]
+fn port_range_should_deserialize_from_its_string_form() {
+    assert_eq!(
+        serde_yaml::from_str::<PortRange>("12000").ok(),
+        Some(PortRange::Single(12000))
+    );
+    assert_eq!(
+        serde_yaml::from_str::<PortRange>("12000-12002").ok(),
+        Some(PortRange::Range(12000, 12002))
+    );
+    assert!(serde_yaml::from_str::<PortRange>("12002-12000").is_err());
+}
 
-    match result {
-        Ok(_) => panic!("This test should result in an error"),
-        Err(e) => {
-            assert_eq!(
-                format!("NAT status has not been set. Run 'nat-detection' first"),
-                e.to_string()
-            )
-        }
-    }
+#[test]
+fn canonicalize_peers_should_sort_and_dedup_the_peers() -> Result<()> {
+    let peer_a: Multiaddr =
+        "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+            .parse()?;
+    let peer_b: Multiaddr =
+        "/ip4/127.0.0.2/udp/12001/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+            .parse()?;
+    let peer_c: Multiaddr =
+        "/ip4/127.0.0.3/udp/12002/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE"
+            .parse()?;
+
+    let mut peers = vec![
+        peer_c.clone(),
+        peer_a.clone(),
+        peer_b.clone(),
+        peer_a.clone(),
+        peer_c.clone(),
+    ];
+    canonicalize_peers(&mut peers);
+    assert_eq!(peers, vec![peer_a.clone(), peer_b.clone(), peer_c.clone()]);
+
+    let mut other_order = vec![peer_b, peer_c, peer_a];
+    canonicalize_peers(&mut other_order);
+    assert_eq!(peers, other_order);
 
     Ok(())
 }
 
+#[test]
+fn port_range_should_describe_its_firewall_rules() {
+    assert_eq!(
+        PortRange::Range(12000, 12004).firewall_rules(Protocol::Udp),
+        vec![FirewallRule {
+            protocol: Protocol::Udp,
+            ports: PortRange::Range(12000, 12004),
+        }]
+    );
+
+    let rules = [PortRange::Single(12000), PortRange::Single(12005)]
+        .iter()
+        .flat_map(|ports| ports.firewall_rules(Protocol::Tcp))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rules,
+        vec![
+            FirewallRule {
+                protocol: Protocol::Tcp,
+                ports: PortRange::Single(12000),
+            },
+            FirewallRule {
+                protocol: Protocol::Tcp,
+                ports: PortRange::Single(12005),
+            },
+        ]
+    );
+}
+
 #[tokio::test]
 async fn add_auditor_should_add_an_auditor_service() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
@@ -2965,6 +5105,7 @@ async fn add_auditor_should_add_an_auditor_service() -> Result<()> {
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -2995,6 +5136,7 @@ async fn add_auditor_should_add_an_auditor_service() -> Result<()> {
         AddAuditorServiceOptions {
             bootstrap_peers: vec![],
             beta_encryption_key: None,
+            env_file: None,
             env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
             auditor_src_bin_path: auditor_download_path.to_path_buf(),
             auditor_install_bin_path: auditor_install_path.to_path_buf(),
@@ -3060,6 +5202,7 @@ async fn add_auditor_should_return_an_error_if_a_auditor_service_was_already_cre
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3067,6 +5210,7 @@ async fn add_auditor_should_return_an_error_if_a_auditor_service_was_already_cre
         AddAuditorServiceOptions {
             bootstrap_peers: vec![],
             beta_encryption_key: None,
+            env_file: None,
             env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
             auditor_src_bin_path: auditor_download_path.to_path_buf(),
             auditor_install_bin_path: auditor_install_path.to_path_buf(),
@@ -3115,6 +5259,7 @@ async fn add_auditor_should_include_beta_encryption_key_if_specified() -> Result
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3147,6 +5292,7 @@ async fn add_auditor_should_include_beta_encryption_key_if_specified() -> Result
         AddAuditorServiceOptions {
             bootstrap_peers: vec![],
             beta_encryption_key: Some("test".to_string()),
+            env_file: None,
             env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
             auditor_src_bin_path: auditor_download_path.to_path_buf(),
             auditor_install_bin_path: auditor_install_path.to_path_buf(),
@@ -3205,6 +5351,7 @@ async fn add_faucet_should_add_a_faucet_service() -> Result<()> {
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3235,6 +5382,8 @@ async fn add_faucet_should_add_a_faucet_service() -> Result<()> {
     add_faucet(
         AddFaucetServiceOptions {
             bootstrap_peers: vec![],
+            dispense_amount: None,
+            env_file: None,
             env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
             faucet_src_bin_path: faucet_download_path.to_path_buf(),
             faucet_install_bin_path: faucet_install_path.to_path_buf(),
@@ -3268,6 +5417,89 @@ async fn add_faucet_should_add_a_faucet_service() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn add_faucet_should_add_a_faucet_service_with_a_custom_dispense_amount() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let faucet_logs_dir = temp_dir.child("logs");
+    faucet_logs_dir.create_dir_all()?;
+    let faucet_data_dir = temp_dir.child("data");
+    faucet_data_dir.create_dir_all()?;
+    let faucet_install_dir = temp_dir.child("install");
+    faucet_install_dir.create_dir_all()?;
+    let faucet_install_path = faucet_install_dir.child(FAUCET_FILE_NAME);
+    let faucet_download_path = temp_dir.child(FAUCET_FILE_NAME);
+    faucet_download_path.write_binary(b"fake faucet bin")?;
+
+    let mut node_registry = NodeRegistry {
+        bootstrap_peers: vec![],
+        daemon: None,
+        auditor: None,
+        faucet: None,
+        environment_variables: None,
+        nat_status: None,
+        nodes: vec![],
+        pending_node_add: None,
+        save_path: node_reg_path.to_path_buf(),
+    };
+
+    let mut mock_service_control = MockServiceControl::new();
+
+    mock_service_control
+        .expect_install()
+        .times(1)
+        .with(
+            eq(ServiceInstallCtx {
+                args: vec![
+                    OsString::from("--log-output-dest"),
+                    OsString::from(faucet_logs_dir.to_path_buf().as_os_str()),
+                    OsString::from("server"),
+                    OsString::from("--amount"),
+                    OsString::from("0.005000000"),
+                ],
+                autostart: true,
+                contents: None,
+                environment: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
+                label: "faucet".parse()?,
+                program: faucet_install_path.to_path_buf(),
+                username: Some(get_username()),
+                working_directory: None,
+            }),
+            eq(false),
+        )
+        .returning(|_, _| Ok(()));
+
+    add_faucet(
+        AddFaucetServiceOptions {
+            bootstrap_peers: vec![],
+            dispense_amount: Some(NanoTokens::from(5000000)),
+            env_file: None,
+            env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
+            faucet_src_bin_path: faucet_download_path.to_path_buf(),
+            faucet_install_bin_path: faucet_install_path.to_path_buf(),
+            local: false,
+            service_data_dir_path: faucet_data_dir.to_path_buf(),
+            service_log_dir_path: faucet_logs_dir.to_path_buf(),
+            user: get_username(),
+            version: latest_version.to_string(),
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )?;
+
+    let saved_faucet = node_registry.faucet.unwrap();
+    assert_eq!(
+        saved_faucet.dispense_amount,
+        Some(NanoTokens::from(5000000))
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn add_faucet_should_return_an_error_if_a_faucet_service_was_already_created() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
@@ -3290,6 +5522,7 @@ async fn add_faucet_should_return_an_error_if_a_faucet_service_was_already_creat
         daemon: None,
         auditor: None,
         faucet: Some(FaucetServiceData {
+            dispense_amount: None,
             faucet_path: faucet_download_path.to_path_buf(),
             local: false,
             log_dir_path: PathBuf::from("/var/log/faucet"),
@@ -3302,12 +5535,15 @@ async fn add_faucet_should_return_an_error_if_a_faucet_service_was_already_creat
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
     let result = add_faucet(
         AddFaucetServiceOptions {
             bootstrap_peers: vec![],
+            dispense_amount: None,
+            env_file: None,
             env_variables: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
             faucet_src_bin_path: faucet_download_path.to_path_buf(),
             faucet_install_bin_path: faucet_install_path.to_path_buf(),
@@ -3356,6 +5592,7 @@ async fn add_daemon_should_add_a_daemon_service() -> Result<()> {
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3444,6 +5681,7 @@ async fn add_daemon_should_return_an_error_if_a_daemon_service_was_already_creat
         environment_variables: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3484,6 +5722,7 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -3513,14 +5752,23 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: false,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -3531,6 +5779,7 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -3546,17 +5795,28 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(1),
+            cpu_quota: None,
             delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -3589,6 +5849,7 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -3618,14 +5879,23 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: true,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -3636,6 +5906,7 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -3651,17 +5922,28 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(1),
+            cpu_quota: None,
             delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: true,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -3694,6 +5976,7 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -3723,14 +6006,23 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: true,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -3741,6 +6033,7 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: false,
+        user_mode: false,
     }
     .build()?;
 
@@ -3756,17 +6049,28 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(1),
+            cpu_quota: None,
             delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: true,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -3797,6 +6101,7 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
     let mut node_registry = NodeRegistry {
         auditor: None,
         faucet: None,
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
         nat_status: None,
         nodes: vec![],
@@ -3825,14 +6130,23 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
         bootstrap_peers: vec![],
+        confirm_genesis: false,
+        cpu_quota: None,
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
+        env_file: None,
         env_variables: None,
+        ephemeral: false,
         genesis: false,
+        health_port: None,
         home_network: true,
         local: false,
         log_dir_path: node_logs_dir.to_path_buf().join("safenode1"),
         log_format: None,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
@@ -3843,6 +6157,7 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
             .join(SAFENODE_FILE_NAME),
         service_user: Some(get_username()),
         upnp: true,
+        user_mode: false,
     }
     .build()?;
 
@@ -3858,17 +6173,28 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: Some(1),
+            cpu_quota: None,
             delete_safenode_src: false,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: true,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: None,
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -3914,6 +6240,7 @@ async fn add_node_should_assign_an_owner() -> Result<()> {
         faucet: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -3973,17 +6300,28 @@ async fn add_node_should_assign_an_owner() -> Result<()> {
             auto_restart: false,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: Some("discord_username".to_string()),
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
@@ -4031,6 +6369,7 @@ async fn add_node_should_auto_restart() -> Result<()> {
         faucet: None,
         nat_status: None,
         nodes: vec![],
+        pending_node_add: None,
         save_path: node_reg_path.to_path_buf(),
     };
 
@@ -4090,17 +6429,28 @@ async fn add_node_should_auto_restart() -> Result<()> {
             auto_restart: true,
             auto_set_nat_flags: false,
             bootstrap_peers: vec![],
+            confirm_genesis: false,
             count: None,
+            cpu_quota: None,
             delete_safenode_src: true,
             enable_metrics_server: false,
+            env_file: None,
             env_variables: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             local: false,
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            max_concurrent_installs: 1,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             owner: Some("discord_username".to_string()),
             node_port: None,
+            resume: false,
             rpc_address: None,
             rpc_port: None,
             safenode_dir_path: temp_dir.to_path_buf(),
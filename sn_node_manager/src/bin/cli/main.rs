@@ -6,17 +6,20 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use chrono::NaiveTime;
 use clap::{Parser, Subcommand};
 use color_eyre::{eyre::eyre, Result};
 use libp2p::Multiaddr;
 use sn_logging::{LogBuilder, LogFormat};
 use sn_node_manager::{
-    add_services::config::{parse_port_range, PortRange},
+    add_services::config::{parse_byte_size, parse_port_range, PortRange},
     cmd::{self},
     VerbosityLevel,
 };
 use sn_peers_acquisition::PeersArgs;
-use std::{net::Ipv4Addr, path::PathBuf};
+use sn_service_management::unit::ByteSize;
+use sn_transfers::NanoTokens;
+use std::{net::Ipv4Addr, path::PathBuf, str::FromStr};
 use tracing::Level;
 
 const DEFAULT_NODE_COUNT: u16 = 25;
@@ -67,12 +70,23 @@ pub enum SubCmd {
         /// This will override any --upnp or --home-network options.
         #[clap(long, default_value_t = false)]
         auto_set_nat_flags: bool,
+        /// Confirm that the node added with --first should start a new network.
+        ///
+        /// A genesis node will not be added without it, as a guard against accidentally starting a
+        /// new network rather than joining an existing one.
+        #[clap(long, requires = "first")]
+        confirm_genesis: bool,
         /// The number of service instances.
         ///
         /// If the --first argument is used, the count has to be one, so --count and --first are
         /// mutually exclusive.
         #[clap(long, conflicts_with = "first")]
         count: Option<u16>,
+        /// Cap the CPU time of each node, as a number of CPUs, e.g., 1.5 for one and a half.
+        ///
+        /// Only supported on Linux, where it is applied as the systemd CPUQuota= directive.
+        #[clap(long)]
+        cpu_quota: Option<f32>,
         /// Provide the path for the data directory for the installed node.
         ///
         /// This path is a prefix. Each installed node will have its own directory underneath it.
@@ -88,6 +102,13 @@ pub enum SubCmd {
         /// If you want to specify the ports, use the --metrics-port argument.
         #[clap(long)]
         enable_metrics_server: bool,
+        /// Provide an environment file for each safenode service to load.
+        ///
+        /// Useful to keep secrets out of the service definition.
+        ///
+        /// Only supported on Linux. The path must be absolute, and --env must not also be used.
+        #[clap(long, conflicts_with = "env")]
+        env_file: Option<PathBuf>,
         /// Provide environment variables for the safenode service.
         ///
         /// Useful to set log levels. Variables should be comma separated without spaces.
@@ -95,6 +116,23 @@ pub enum SubCmd {
         /// Example: --env SN_LOG=all,RUST_LOG=libp2p=debug
         #[clap(name = "env", long, use_value_delimiter = true, value_parser = parse_environment_variables)]
         env_variables: Option<Vec<(String, String)>>,
+        /// Set this flag to run the nodes without persisting their data.
+        ///
+        /// Each node keeps its keys, wallet and chunk store in a directory of its own under its
+        /// data directory, which is wiped when the service is stopped or removed. This is useful
+        /// for short-lived nodes, e.g., in CI.
+        #[clap(long)]
+        ephemeral: bool,
+        /// Specify a port for the minimal health endpoint of the safenode service(s).
+        ///
+        /// This is for load balancers that only need to know the node is up, without the full
+        /// metrics. If not used, the health endpoint is not started.
+        ///
+        /// If multiple services are being added and this argument is used, you must specify a
+        /// range. For example, '12000-12004'. The length of the range must match the number of
+        /// services, which in this case would be 5. The range must also go from lower to higher.
+        #[clap(long, value_parser = parse_port_range)]
+        health_port: Option<PortRange>,
         /// Set this flag to use the safenode '--home-network' feature.
         ///
         /// This enables the use of safenode services from a home network with a router.
@@ -122,6 +160,35 @@ pub enum SubCmd {
         /// If the argument is not used, the default format will be applied.
         #[clap(long, value_parser = LogFormat::parse_from_str, verbatim_doc_comment)]
         log_format: Option<LogFormat>,
+        /// Specify the log levels for the safenode service(s), in the form accepted by SN_LOG.
+        ///
+        /// For example, 'sn_node=debug,libp2p=info'. This replaces any SN_LOG value given with
+        /// --env.
+        #[clap(long)]
+        log_level: Option<String>,
+        /// Specify the start of a daily maintenance window for the nodes, in the host's local time
+        /// as HH:MM.
+        ///
+        /// The nodes pause their periodic replication from --maintenance-start until
+        /// --maintenance-end. A window that ends before it starts spans midnight.
+        #[clap(long, requires = "maintenance_end", value_parser = parse_time_of_day)]
+        maintenance_start: Option<NaiveTime>,
+        /// Specify the end of the daily maintenance window for the nodes, in the host's local time
+        /// as HH:MM.
+        #[clap(long, requires = "maintenance_start", value_parser = parse_time_of_day)]
+        maintenance_end: Option<NaiveTime>,
+        /// The maximum number of services to install at the same time.
+        ///
+        /// The names and ports of the services are assigned in the same order regardless of this
+        /// value. If not used, the services are installed one at a time.
+        #[clap(long, default_value_t = 1)]
+        max_concurrent_installs: usize,
+        /// Cap the memory use of each node, in bytes, with an optional K, M, G or T suffix for a
+        /// multiple of 1024, e.g., 2G.
+        ///
+        /// Only supported on Linux, where it is applied as the systemd MemoryMax= directive.
+        #[clap(long, value_parser = parse_byte_size)]
+        memory_max: Option<ByteSize>,
         /// Specify a port for the open metrics server.
         ///
         /// This argument should only be used with a safenode binary that has the open-metrics
@@ -135,6 +202,12 @@ pub enum SubCmd {
         /// services, which in this case would be 5. The range must also go from lower to higher.
         #[clap(long, value_parser = parse_port_range)]
         metrics_port: Option<PortRange>,
+        /// Specify the number of connected peers below which the nodes redial their bootstrap
+        /// peers.
+        ///
+        /// The value must be between 1 and 200.
+        #[clap(long)]
+        min_connections: Option<usize>,
         /// Specify a port for the safenode service(s).
         ///
         /// If not used, ports will be selected at random.
@@ -160,6 +233,12 @@ pub enum SubCmd {
         /// run as normal.
         #[clap(long)]
         owner: Option<String>,
+        /// Finish an add that was interrupted, e.g., by a failed install, rather than adding a new
+        /// set of nodes.
+        ///
+        /// The same count must be given. Without this flag, the interrupted add is discarded.
+        #[clap(long)]
+        resume: bool,
         /// Specify an Ipv4Addr for the node's RPC server to run on.
         ///
         /// Useful if you want to expose the RPC server pubilcly. Ports are assigned automatically.
@@ -399,6 +478,13 @@ pub enum AuditorSubCmd {
         /// discord usernames of the beta participants
         #[clap(short = 'k', long, value_name = "hex_secret_key")]
         beta_encryption_key: Option<String>,
+        /// Provide an environment file for the auditor service to load.
+        ///
+        /// Useful to keep secrets out of the service definition.
+        ///
+        /// Only supported on Linux. The path must be absolute, and --env must not also be used.
+        #[clap(long, conflicts_with = "env")]
+        env_file: Option<PathBuf>,
         /// Provide environment variables for the auditor service.
         ///
         /// Useful to set log levels. Variables should be comma separated without spaces.
@@ -564,6 +650,18 @@ pub enum FaucetSubCmd {
     /// Windows is not supported for running a faucet.
     #[clap(name = "add")]
     Add {
+        /// The amount of tokens the faucet should send for each request, e.g., `0.5`.
+        ///
+        /// If not used, the faucet's own default is applied.
+        #[clap(long, value_parser = NanoTokens::from_str)]
+        amount: Option<NanoTokens>,
+        /// Provide an environment file for the faucet service to load.
+        ///
+        /// Useful to keep secrets out of the service definition.
+        ///
+        /// Only supported on Linux. The path must be absolute, and --env must not also be used.
+        #[clap(long, conflicts_with = "env")]
+        env_file: Option<PathBuf>,
         /// Provide environment variables for the faucet service.
         ///
         /// Useful to set log levels. Variables should be comma separated without spaces.
@@ -878,19 +976,31 @@ async fn main() -> Result<()> {
         SubCmd::Add {
             auto_restart,
             auto_set_nat_flags,
+            confirm_genesis,
             count,
+            cpu_quota,
             data_dir_path,
             enable_metrics_server,
+            env_file,
             env_variables,
+            ephemeral,
+            health_port,
             home_network,
             local,
             log_dir_path,
             log_format,
+            log_level,
+            maintenance_end,
+            maintenance_start,
+            max_concurrent_installs,
+            memory_max,
             metrics_port,
+            min_connections,
             node_port,
             owner,
             path,
             peers,
+            resume,
             rpc_address,
             rpc_port,
             url,
@@ -901,18 +1011,29 @@ async fn main() -> Result<()> {
             let _ = cmd::node::add(
                 auto_restart,
                 auto_set_nat_flags,
+                confirm_genesis,
                 count,
+                cpu_quota,
                 data_dir_path,
                 enable_metrics_server,
+                env_file,
                 env_variables,
+                ephemeral,
+                health_port,
                 home_network,
                 local,
                 log_dir_path,
                 log_format,
+                log_level,
+                maintenance_start.zip(maintenance_end),
+                max_concurrent_installs,
+                memory_max,
                 metrics_port,
+                min_connections,
                 node_port,
                 owner,
                 peers,
+                resume,
                 rpc_address,
                 rpc_port,
                 path,
@@ -927,6 +1048,7 @@ async fn main() -> Result<()> {
         }
         SubCmd::Auditor(AuditorSubCmd::Add {
             beta_encryption_key,
+            env_file,
             env_variables,
             log_dir_path,
             path,
@@ -936,6 +1058,7 @@ async fn main() -> Result<()> {
         }) => {
             cmd::auditor::add(
                 beta_encryption_key,
+                env_file,
                 env_variables,
                 log_dir_path,
                 *peers,
@@ -973,6 +1096,8 @@ async fn main() -> Result<()> {
         SubCmd::Daemon(DaemonSubCmd::Stop {}) => cmd::daemon::stop(verbosity).await,
         SubCmd::Faucet(faucet_command) => match faucet_command {
             FaucetSubCmd::Add {
+                amount,
+                env_file,
                 env_variables,
                 log_dir_path,
                 path,
@@ -981,6 +1106,8 @@ async fn main() -> Result<()> {
                 version,
             } => {
                 cmd::faucet::add(
+                    amount,
+                    env_file,
                     env_variables,
                     log_dir_path,
                     peers,
@@ -1161,6 +1288,11 @@ fn get_log_builder() -> Result<LogBuilder> {
     Ok(log_builder)
 }
 
+fn parse_time_of_day(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|err| eyre!("The time '{time}' must be given as HH:MM: {err}"))
+}
+
 // Since delimiter is on, we get element of the csv and not the entire csv.
 fn parse_environment_variables(env_var: &str) -> Result<(String, String)> {
     let parts: Vec<&str> = env_var.splitn(2, '=').collect();
@@ -9,7 +9,7 @@
 use crate::{
     add_services::{add_daemon, config::AddDaemonServiceOptions},
     config::{self, is_running_as_root},
-    helpers::{download_and_extract_release, get_bin_version},
+    helpers::{download_and_extract_release, get_bin_version, get_username, resolve_service_user},
     print_banner, ServiceManager, VerbosityLevel,
 };
 use color_eyre::{eyre::eyre, Result};
@@ -38,10 +38,10 @@ pub async fn add(
         print_banner("Add Daemon Service");
     }
 
-    let service_user = "safe";
+    let service_user = resolve_service_user(None, false, get_username)?;
     let service_manager = ServiceController {};
     debug!("Trying to create service user '{service_user}' for the daemon");
-    service_manager.create_service_user(service_user)?;
+    service_manager.create_service_user(&service_user)?;
 
     let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
     let release_repo = <dyn SafeReleaseRepoActions>::default_config();
@@ -10,7 +10,7 @@ use super::{download_and_get_upgrade_bin_path, print_upgrade_summary};
 use crate::{
     add_services::{add_faucet, config::AddFaucetServiceOptions},
     config::{self, is_running_as_root},
-    helpers::{download_and_extract_release, get_bin_version},
+    helpers::{download_and_extract_release, get_bin_version, get_username, resolve_service_user},
     print_banner, ServiceManager, VerbosityLevel,
 };
 use color_eyre::{eyre::eyre, Result};
@@ -22,10 +22,13 @@ use sn_service_management::{
     control::{ServiceControl, ServiceController},
     FaucetService, NodeRegistry, UpgradeOptions,
 };
-use sn_transfers::get_faucet_data_dir;
+use sn_transfers::{get_faucet_data_dir, NanoTokens};
 use std::path::PathBuf;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn add(
+    dispense_amount: Option<NanoTokens>,
+    env_file: Option<PathBuf>,
     env_variables: Option<Vec<(String, String)>>,
     log_dir_path: Option<PathBuf>,
     peers_args: PeersArgs,
@@ -43,14 +46,14 @@ pub async fn add(
         print_banner("Add Faucet Service");
     }
 
-    let service_user = "safe";
+    let service_user = resolve_service_user(None, false, get_username)?;
     let service_manager = ServiceController {};
-    service_manager.create_service_user(service_user)?;
+    service_manager.create_service_user(&service_user)?;
 
     let service_log_dir_path = config::get_service_log_dir_path(
         ReleaseType::Faucet,
         log_dir_path,
-        Some(service_user.to_string()),
+        Some(service_user.clone()),
     )?;
 
     let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
@@ -75,13 +78,15 @@ pub async fn add(
     add_faucet(
         AddFaucetServiceOptions {
             bootstrap_peers: peers_args.get_peers().await?,
+            dispense_amount,
+            env_file,
             env_variables,
             faucet_src_bin_path,
             faucet_install_bin_path: PathBuf::from("/usr/local/bin/faucet"),
             local: false,
             service_data_dir_path: get_faucet_data_dir(),
             service_log_dir_path,
-            user: service_user.to_string(),
+            user: service_user,
             version,
         },
         &mut node_registry,
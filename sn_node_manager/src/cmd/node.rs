@@ -15,9 +15,10 @@ use crate::{
         config::{AddNodeServiceOptions, PortRange},
     },
     config::{self, is_running_as_root},
-    helpers::{download_and_extract_release, get_bin_version},
+    helpers::{download_and_extract_release, get_bin_version, get_username, resolve_service_user},
     print_banner, refresh_node_registry, status_report, ServiceManager, VerbosityLevel,
 };
+use chrono::NaiveTime;
 use color_eyre::{eyre::eyre, Help, Result};
 use colored::Colorize;
 use libp2p_identity::PeerId;
@@ -28,6 +29,7 @@ use sn_releases::{ReleaseType, SafeReleaseRepoActions};
 use sn_service_management::{
     control::{ServiceControl, ServiceController},
     rpc::RpcClient,
+    unit::ByteSize,
     NodeRegistry, NodeService, ServiceStateActions, ServiceStatus, UpgradeOptions, UpgradeResult,
 };
 use sn_transfers::HotWallet;
@@ -38,18 +40,29 @@ use tracing::debug;
 pub async fn add(
     auto_restart: bool,
     auto_set_nat_flags: bool,
+    confirm_genesis: bool,
     count: Option<u16>,
+    cpu_quota: Option<f32>,
     data_dir_path: Option<PathBuf>,
     enable_metrics_server: bool,
+    env_file: Option<PathBuf>,
     env_variables: Option<Vec<(String, String)>>,
+    ephemeral: bool,
+    health_port: Option<PortRange>,
     home_network: bool,
     local: bool,
     log_dir_path: Option<PathBuf>,
     log_format: Option<LogFormat>,
+    log_level: Option<String>,
+    maintenance_window: Option<(NaiveTime, NaiveTime)>,
+    max_concurrent_installs: usize,
+    memory_max: Option<ByteSize>,
     metrics_port: Option<PortRange>,
+    min_connections: Option<usize>,
     node_port: Option<PortRange>,
     owner: Option<String>,
     peers_args: PeersArgs,
+    resume: bool,
     rpc_address: Option<Ipv4Addr>,
     rpc_port: Option<PortRange>,
     src_path: Option<PathBuf>,
@@ -67,21 +80,20 @@ pub async fn add(
     }
 
     let service_manager = ServiceController {};
-    let service_user = if user_mode {
+    let service_user = resolve_service_user(user.as_deref(), user_mode, get_username)?;
+    // A user mode service is run by the current user's service manager, so there is no account to
+    // create and its directories are kept under the user's home directory.
+    let dir_owner = if user_mode {
         None
     } else {
-        let service_user = user.unwrap_or_else(|| "safe".to_string());
         service_manager.create_service_user(&service_user)?;
-        Some(service_user)
+        Some(service_user.clone())
     };
 
     let service_data_dir_path =
-        config::get_service_data_dir_path(data_dir_path, service_user.clone())?;
-    let service_log_dir_path = config::get_service_log_dir_path(
-        ReleaseType::Safenode,
-        log_dir_path,
-        service_user.clone(),
-    )?;
+        config::get_service_data_dir_path(data_dir_path, dir_owner.clone())?;
+    let service_log_dir_path =
+        config::get_service_log_dir_path(ReleaseType::Safenode, log_dir_path, dir_owner)?;
 
     let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
     let release_repo = <dyn SafeReleaseRepoActions>::default_config();
@@ -136,17 +148,28 @@ pub async fn add(
         auto_restart,
         auto_set_nat_flags,
         bootstrap_peers,
+        confirm_genesis,
         count,
+        cpu_quota,
         delete_safenode_src: src_path.is_none(),
         enable_metrics_server,
+        env_file,
         env_variables,
+        ephemeral,
         genesis: is_first,
+        health_port,
         home_network,
         local,
         log_format,
+        log_level,
+        maintenance_window,
+        max_concurrent_installs,
+        memory_max,
         metrics_port,
+        min_connections,
         owner,
         node_port,
+        resume,
         rpc_address,
         rpc_port,
         safenode_src_path,
@@ -154,7 +177,7 @@ pub async fn add(
         service_data_dir_path,
         service_log_dir_path,
         upnp,
-        user: service_user,
+        user: Some(service_user),
         user_mode,
         version,
     };
@@ -649,18 +672,29 @@ pub async fn maintain_n_running_nodes(
                 let added_service_list = add(
                     auto_restart,
                     auto_set_nat_flags,
+                    false,
                     Some(to_add_count as u16),
+                    None,
                     data_dir_path,
                     enable_metrics_server,
+                    None,
                     env_variables,
+                    false,
+                    None,
                     home_network,
                     local,
                     log_dir_path,
                     log_format,
+                    None,
+                    None,
+                    1,
+                    None,
                     metrics_port,
+                    None,
                     node_port,
                     owner,
                     peers,
+                    false,
                     rpc_address,
                     rpc_port,
                     src_path,
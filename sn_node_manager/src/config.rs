@@ -242,6 +242,27 @@ pub fn create_owned_dir(path: PathBuf, _owner: &str) -> Result<()> {
     Ok(())
 }
 
+/// Create a directory only its owner can access, e.g., the root dir of an ephemeral node.
+#[cfg(unix)]
+pub fn create_private_dir(path: PathBuf, owner: Option<&str>) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    match owner {
+        Some(owner) => create_owned_dir(path.clone(), owner)?,
+        None => std::fs::create_dir_all(&path)?,
+    }
+    debug!("Restricting the permissions of {path:?} to its owner");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn create_private_dir(path: PathBuf, _owner: Option<&str>) -> Result<()> {
+    debug!("Creating private dir: {path:?}");
+    std::fs::create_dir_all(path)?;
+    Ok(())
+}
+
 #[cfg(unix)]
 pub fn is_running_as_root() -> bool {
     use nix::unistd::geteuid;
@@ -25,6 +25,8 @@ pub enum Error {
     SemverError(#[from] semver::Error),
     #[error("The service(s) is already running: {0:?}")]
     ServiceAlreadyRunning(Vec<String>),
+    #[error("The service '{0}' was not found in the node registry")]
+    ServiceNotFound(String),
     #[error("The service(s) is not running: {0:?}")]
     ServiceNotRunning(Vec<String>),
     #[error(transparent)]
@@ -33,4 +35,8 @@ pub enum Error {
     ServiceStatusMismatch {
         expected: sn_service_management::ServiceStatus,
     },
+    #[error("The service '{0}' was upgraded but could not be started: {1}")]
+    ServiceUpgradedButNotStarted(String, String),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
 }
@@ -11,19 +11,29 @@ use color_eyre::{
     Result,
 };
 use indicatif::{ProgressBar, ProgressStyle};
+use libp2p::{
+    futures::StreamExt,
+    swarm::{dial_opts::DialOpts, dummy, ConnectionId, SwarmEvent},
+    Multiaddr, SwarmBuilder,
+};
 use semver::Version;
 use sn_releases::{get_running_platform, ArchiveType, ReleaseType, SafeReleaseRepoActions};
 use std::{
+    collections::HashMap,
     io::Read,
     path::{Path, PathBuf},
     process::{Command, Stdio},
     sync::Arc,
+    time::Duration,
 };
 
 use crate::{config, VerbosityLevel};
 
 const MAX_DOWNLOAD_RETRIES: u8 = 3;
 
+/// The account system-wide services run as when no user is specified.
+pub const DEFAULT_SERVICE_USER: &str = "safe";
+
 #[cfg(windows)]
 pub async fn configure_winsw(dest_path: &Path, verbosity: VerbosityLevel) -> Result<()> {
     if which::which("winsw.exe").is_ok() {
@@ -274,6 +284,61 @@ pub async fn download_and_extract_release(
     Ok((binary_download_path, bin_version))
 }
 
+/// Attempt a quick dial to each of the `peers`, reporting whether a connection to it could be
+/// established within the `timeout`.
+///
+/// A node given only unreachable bootstrap peers will never connect to the network, so this can
+/// be used as a sanity check before installing nodes.
+pub async fn check_peers_reachable(
+    peers: &[Multiaddr],
+    timeout: Duration,
+) -> Result<Vec<(Multiaddr, bool)>> {
+    let mut swarm = SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_quic()
+        .with_behaviour(|_| dummy::Behaviour)?
+        .with_swarm_config(|c| c.with_idle_connection_timeout(timeout))
+        .build();
+
+    let mut reachable = vec![false; peers.len()];
+    let mut pending_dials: HashMap<ConnectionId, usize> = HashMap::new();
+    for (index, peer) in peers.iter().enumerate() {
+        let dial_opts = DialOpts::from(peer.clone());
+        let connection_id = dial_opts.connection_id();
+        match swarm.dial(dial_opts) {
+            Ok(()) => {
+                let _ = pending_dials.insert(connection_id, index);
+            }
+            Err(err) => debug!("Could not dial peer {peer}: {err}"),
+        }
+    }
+
+    let _ = tokio::time::timeout(timeout, async {
+        while !pending_dials.is_empty() {
+            match swarm.select_next_some().await {
+                SwarmEvent::ConnectionEstablished { connection_id, .. } => {
+                    if let Some(index) = pending_dials.remove(&connection_id) {
+                        reachable[index] = true;
+                    }
+                }
+                SwarmEvent::OutgoingConnectionError {
+                    connection_id,
+                    error,
+                    ..
+                } => {
+                    if let Some(index) = pending_dials.remove(&connection_id) {
+                        debug!("Peer {} is not reachable: {error}", peers[index]);
+                    }
+                }
+                _ => {}
+            }
+        }
+    })
+    .await;
+
+    Ok(peers.iter().cloned().zip(reachable).collect())
+}
+
 pub fn get_bin_version(bin_path: &PathBuf) -> Result<String> {
     trace!("Obtaining version of binary {bin_path:?}");
     let mut cmd = Command::new(bin_path)
@@ -315,6 +380,22 @@ pub fn get_username() -> Result<String> {
     Ok(std::env::var("USER")?)
 }
 
+/// Determine the account a service should be installed to run as.
+///
+/// An explicit user always wins. Otherwise a user mode service runs as the current user, which is
+/// only looked up in that case, and a system service runs as `DEFAULT_SERVICE_USER`.
+pub fn resolve_service_user(
+    user: Option<&str>,
+    user_mode: bool,
+    current_user: impl FnOnce() -> Result<String>,
+) -> Result<String> {
+    match user {
+        Some(user) => Ok(user.to_string()),
+        None if user_mode => current_user(),
+        None => Ok(DEFAULT_SERVICE_USER.to_string()),
+    }
+}
+
 /// There is a `tempdir` crate that provides the same kind of functionality, but it was flagged for
 /// a security vulnerability.
 pub fn create_temp_dir() -> Result<PathBuf> {
@@ -325,3 +406,78 @@ pub fn create_temp_dir() -> Result<PathBuf> {
         .inspect_err(|err| error!("Failed to crete temp dir: {err:?}"))?;
     Ok(new_temp_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::multiaddr::Protocol;
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    fn current_user() -> Result<String> {
+        Ok("bee".to_string())
+    }
+
+    #[test]
+    fn resolve_service_user_should_prefer_an_explicit_user() -> Result<()> {
+        assert_eq!(
+            resolve_service_user(Some("ant"), false, current_user)?,
+            "ant"
+        );
+        assert_eq!(
+            resolve_service_user(Some("ant"), true, current_user)?,
+            "ant"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_service_user_should_use_the_default_user_for_a_system_service() -> Result<()> {
+        assert_eq!(
+            resolve_service_user(None, false, current_user)?,
+            DEFAULT_SERVICE_USER
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_service_user_should_run_a_user_mode_service_as_the_current_user() -> Result<()> {
+        assert_eq!(resolve_service_user(None, true, current_user)?, "bee");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn check_peers_reachable_should_report_a_listening_peer_and_a_closed_port() -> Result<()>
+    {
+        let mut listener = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_quic()
+            .with_behaviour(|_| dummy::Behaviour)?
+            .build();
+        let _ = listener.listen_on("/ip4/127.0.0.1/udp/0/quic-v1".parse()?)?;
+        let listen_addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                break address.with(Protocol::P2p(*listener.local_peer_id()));
+            }
+        };
+        let listener_task = tokio::spawn(async move {
+            loop {
+                let _ = listener.select_next_some().await;
+            }
+        });
+
+        let closed_port = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?
+            .local_addr()?
+            .port();
+        let closed_addr: Multiaddr = format!("/ip4/127.0.0.1/udp/{closed_port}/quic-v1").parse()?;
+
+        let results = check_peers_reachable(
+            &[listen_addr.clone(), closed_addr.clone()],
+            Duration::from_secs(3),
+        )
+        .await?;
+        listener_task.abort();
+
+        assert_eq!(results, vec![(listen_addr, true), (closed_addr, false)]);
+        Ok(())
+    }
+}
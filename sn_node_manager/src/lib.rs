@@ -36,20 +36,31 @@ impl From<u8> for VerbosityLevel {
     }
 }
 
-use crate::error::{Error, Result};
+use crate::{
+    add_services::config::is_secret_env_variable,
+    error::{Error, Result},
+};
 use colored::Colorize;
 use semver::Version;
 use sn_service_management::{
-    control::ServiceControl, error::Error as ServiceError, rpc::RpcClient, NodeRegistry,
-    NodeService, NodeServiceData, ServiceStateActions, ServiceStatus, UpgradeOptions,
+    control::ServiceControl,
+    error::Error as ServiceError,
+    rpc::{RpcActions, RpcClient},
+    NodeRegistry, NodeService, NodeServiceData, ServiceStateActions, ServiceStatus, UpgradeOptions,
     UpgradeResult,
 };
 use sn_transfers::HotWallet;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use tracing::debug;
 
 pub const DAEMON_DEFAULT_PORT: u16 = 12500;
 pub const DAEMON_SERVICE_NAME: &str = "safenodemand";
 
+const NODE_LOG_FILE_NAME: &str = "safenode.log";
 const RPC_START_UP_DELAY_MS: u64 = 3000;
 
 pub struct ServiceManager<T: ServiceStateActions + Send> {
@@ -602,6 +613,390 @@ async fn refresh_single_node<'a>(
     Ok(())
 }
 
+/// Return the last `lines` lines of the current log file for the named node service.
+///
+/// The log directory is resolved from the node's service record, so the caller does not need to
+/// know the path that was composed when the service was added.
+pub fn tail_node_logs(
+    node_registry: &NodeRegistry,
+    service_name: &str,
+    lines: usize,
+) -> Result<Vec<String>> {
+    let node = node_registry
+        .nodes
+        .iter()
+        .find(|node| node.service_name == service_name)
+        .ok_or_else(|| Error::ServiceNotFound(service_name.to_string()))?;
+
+    let log_path = node.log_dir_path.join(NODE_LOG_FILE_NAME);
+    debug!("Reading the last {lines} lines of {log_path:?} for {service_name}");
+    let contents = std::fs::read_to_string(log_path)?;
+    let all_lines = contents.lines().collect::<Vec<_>>();
+    let start = all_lines.len().saturating_sub(lines);
+    Ok(all_lines[start..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Bundle the logs, a redacted config summary and version info for the named nodes into a zip
+/// archive in `output_dir`, for attaching to a support ticket.
+///
+/// Each node's current log file is resolved from its service record. Environment variable values
+/// are replaced with a placeholder, since they may hold secrets, both in the config summary and
+/// wherever they appear in the logs. So are the values of `NAME=value` pairs in the logs whose name
+/// looks like a secret.
+pub fn collect_diagnostics(
+    node_registry: &NodeRegistry,
+    node_names: &[&str],
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    let nodes = node_names
+        .iter()
+        .map(|name| {
+            node_registry
+                .nodes
+                .iter()
+                .find(|node| node.service_name == *name)
+                .ok_or_else(|| Error::ServiceNotFound(name.to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    std::fs::create_dir_all(output_dir)?;
+    let archive_path = output_dir.join(format!(
+        "diagnostics-{}.zip",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+    debug!("Collecting diagnostics for {node_names:?} into {archive_path:?}");
+
+    let mut archive = zip::ZipWriter::new(std::fs::File::create(&archive_path)?);
+    let options = zip::write::FileOptions::default();
+
+    let mut versions = format!("safenode-manager {}\n", env!("CARGO_PKG_VERSION"));
+    for node in nodes.iter() {
+        versions.push_str(&format!("{} {}\n", node.service_name, node.version));
+    }
+    archive.start_file("version.txt", options)?;
+    archive.write_all(versions.as_bytes())?;
+
+    archive.start_file("config.txt", options)?;
+    archive.write_all(diagnostics_config_summary(node_registry, &nodes).as_bytes())?;
+
+    let secrets = node_registry
+        .environment_variables
+        .iter()
+        .flatten()
+        .map(|(_, value)| value.clone())
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>();
+    for node in nodes.iter() {
+        let log_path = node.log_dir_path.join(NODE_LOG_FILE_NAME);
+        if !log_path.exists() {
+            warn!(
+                "No log file found at {log_path:?} for {}",
+                node.service_name
+            );
+            continue;
+        }
+        archive.start_file(
+            format!("{}/{NODE_LOG_FILE_NAME}", node.service_name),
+            options,
+        )?;
+        let mut writer = RedactingWriter {
+            inner: &mut archive,
+            secrets: &secrets,
+            line: Vec::new(),
+        };
+        std::io::copy(&mut std::fs::File::open(log_path)?, &mut writer)?;
+        writer.flush()?;
+    }
+
+    archive.finish()?;
+    Ok(archive_path)
+}
+
+const REDACTED: &str = "<redacted>";
+
+/// Writes through to `inner` a line at a time, with the secrets redacted from each line.
+struct RedactingWriter<'a, W: Write> {
+    inner: W,
+    secrets: &'a [String],
+    line: Vec<u8>,
+}
+
+impl<W: Write> RedactingWriter<'_, W> {
+    fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        let line = redact_secrets(&String::from_utf8_lossy(line), self.secrets);
+        self.inner.write_all(line.as_bytes())
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        while let Some(index) = self.line.iter().position(|byte| *byte == b'\n') {
+            let line = self.line.drain(..=index).collect::<Vec<_>>();
+            self.write_line(&line)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Writes out any partial last line before flushing `inner`.
+    fn flush(&mut self) -> std::io::Result<()> {
+        let line = std::mem::take(&mut self.line);
+        self.write_line(&line)?;
+        self.inner.flush()
+    }
+}
+
+/// Replace each of the `secrets`, and the value of any `NAME=value` pair whose name looks like a
+/// secret, with a placeholder.
+fn redact_secrets(line: &str, secrets: &[String]) -> String {
+    let mut line = line.to_string();
+    for secret in secrets {
+        line = line.replace(secret.as_str(), REDACTED);
+    }
+
+    let mut redacted = String::with_capacity(line.len());
+    let mut rest = line.as_str();
+    while let Some(index) = rest.find('=') {
+        let (before, after) = rest.split_at(index + 1);
+        redacted.push_str(before);
+        let name = before[..index]
+            .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+        let value_len = after
+            .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '"' | '\'' | ')' | '}'))
+            .unwrap_or(after.len());
+        if !name.is_empty() && is_secret_env_variable(name) && value_len > 0 {
+            redacted.push_str(REDACTED);
+            rest = &after[value_len..];
+        } else {
+            rest = after;
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn diagnostics_config_summary(node_registry: &NodeRegistry, nodes: &[&NodeServiceData]) -> String {
+    let mut summary = format!("Bootstrap peers: {}\n", node_registry.bootstrap_peers.len());
+    for (key, _) in node_registry.environment_variables.iter().flatten() {
+        summary.push_str(&format!("Environment variable: {key}=<redacted>\n"));
+    }
+
+    for node in nodes {
+        summary.push_str(&format!("\n[{}]\n", node.service_name));
+        summary.push_str(&format!("Status: {:?}\n", node.status));
+        summary.push_str(&format!("Peer ID: {:?}\n", node.peer_id));
+        summary.push_str(&format!("RPC socket: {}\n", node.rpc_socket_addr));
+        summary.push_str(&format!("Node port: {:?}\n", node.node_port));
+        summary.push_str(&format!("Metrics port: {:?}\n", node.metrics_port));
+        summary.push_str(&format!("Home network: {}\n", node.home_network));
+        summary.push_str(&format!("UPnP: {}\n", node.upnp));
+        summary.push_str(&format!("Log format: {:?}\n", node.log_format));
+        summary.push_str(&format!("User mode: {}\n", node.user_mode));
+    }
+    summary
+}
+
+/// Return the names of the installed node services whose owner differs from the expected owner.
+///
+/// In a fleet where every node should report to the same owner, a node added with the wrong owner
+/// has its rewards attributed elsewhere. Removed nodes are ignored.
+pub fn find_owner_drift(node_registry: &NodeRegistry, expected_owner: &str) -> Vec<String> {
+    node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .filter(|node| node.owner.as_deref() != Some(expected_owner))
+        .map(|node| node.service_name.clone())
+        .collect()
+}
+
+/// The role a port plays for a node service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortKind {
+    Health,
+    Metrics,
+    Node,
+    Rpc,
+}
+
+impl std::fmt::Display for PortKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortKind::Health => write!(f, "health"),
+            PortKind::Metrics => write!(f, "metrics"),
+            PortKind::Node => write!(f, "node"),
+            PortKind::Rpc => write!(f, "RPC"),
+        }
+    }
+}
+
+/// A port assigned to more than one node service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortConflict {
+    pub port: u16,
+    /// The services using the port, and what they use it for.
+    pub services: Vec<(String, PortKind)>,
+}
+
+/// Find the ports assigned to more than one installed node service, ordered by port.
+///
+/// Each add operation checks its own ports, but separate adds can still assign the same port to
+/// different nodes. Removed nodes are ignored.
+pub fn find_port_conflicts_in_fleet(node_registry: &NodeRegistry) -> Vec<PortConflict> {
+    let mut services_by_port: BTreeMap<u16, Vec<(String, PortKind)>> = BTreeMap::new();
+    for node in node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+    {
+        let ports = [
+            (node.node_port, PortKind::Node),
+            (Some(node.rpc_socket_addr.port()), PortKind::Rpc),
+            (node.metrics_port, PortKind::Metrics),
+            (node.health_port, PortKind::Health),
+        ];
+        for (port, kind) in ports {
+            if let Some(port) = port {
+                services_by_port
+                    .entry(port)
+                    .or_default()
+                    .push((node.service_name.clone(), kind));
+            }
+        }
+    }
+
+    services_by_port
+        .into_iter()
+        .filter(|(_, services)| services.iter().any(|(name, _)| *name != services[0].0))
+        .map(|(port, services)| PortConflict { port, services })
+        .collect()
+}
+
+/// Stop and start a single node service by name.
+///
+/// The service is restarted from its installed definition, so the node keeps the exact arguments
+/// it was added with.
+pub async fn restart_node(
+    node_registry: &mut NodeRegistry,
+    service_name: &str,
+    service_control: Box<dyn ServiceControl + Send>,
+    rpc_client: Box<dyn RpcActions + Send>,
+    verbosity: VerbosityLevel,
+) -> Result<()> {
+    let node = node_registry
+        .nodes
+        .iter_mut()
+        .find(|node| node.service_name == service_name)
+        .ok_or_else(|| Error::ServiceNotFound(service_name.to_string()))?;
+
+    info!("Restarting the {service_name} service");
+    let service = NodeService::new(node, rpc_client);
+    let mut service_manager = ServiceManager::new(service, service_control, verbosity);
+    service_manager.stop().await?;
+    service_manager.start().await?;
+    node_registry.save()?;
+    Ok(())
+}
+
+/// Change the owner a single node reports its rewards to, restarting only that node.
+///
+/// The service is reinstalled from its registry entry, as an upgrade would, with only the `--owner`
+/// argument changed. It keeps every other argument and unit directive it was added with, e.g., its
+/// environment file, resource limits, maintenance window and minimum connection count.
+pub async fn set_node_owner(
+    node_registry: &mut NodeRegistry,
+    service_name: &str,
+    owner: String,
+    service_control: Box<dyn ServiceControl + Send>,
+    rpc_client: Box<dyn RpcActions + Send>,
+    verbosity: VerbosityLevel,
+) -> Result<()> {
+    let bootstrap_peers = node_registry.bootstrap_peers.clone();
+    let env_variables = node_registry.environment_variables.clone();
+    let node = node_registry
+        .nodes
+        .iter_mut()
+        .find(|node| node.service_name == service_name)
+        .ok_or_else(|| Error::ServiceNotFound(service_name.to_string()))?;
+    let options = UpgradeOptions {
+        auto_restart: node.auto_restart,
+        bootstrap_peers,
+        env_variables,
+        force: false,
+        start_service: true,
+        target_bin_path: node.safenode_path.clone(),
+        target_version: Version::parse(&node.version)?,
+    };
+
+    info!("Setting the owner of the {service_name} service to {owner}");
+    let service = NodeService::new(node, rpc_client);
+    let mut service_manager = ServiceManager::new(service, service_control, verbosity);
+    service_manager.stop().await?;
+    service_manager.service.service_data.owner = Some(owner);
+    service_manager
+        .service_control
+        .uninstall(service_name, service_manager.service.is_user_mode())?;
+    service_manager.service_control.install(
+        service_manager
+            .service
+            .build_upgrade_install_context(options)?,
+        service_manager.service.is_user_mode(),
+    )?;
+    service_manager.start().await?;
+    node_registry.save()?;
+    Ok(())
+}
+
+/// Upgrade a single node in place, replacing its binary with `target_bin_path` and restarting it.
+///
+/// The service is reinstalled from its registry entry, so it keeps its program path, every argument
+/// it was added with and the directives of its unit, e.g., its environment file and resource
+/// limits. Nothing is done if the node is already at `target_version`.
+pub async fn upgrade_node(
+    node_registry: &mut NodeRegistry,
+    service_name: &str,
+    target_version: Version,
+    target_bin_path: PathBuf,
+    service_control: Box<dyn ServiceControl + Send>,
+    rpc_client: Box<dyn RpcActions + Send>,
+    verbosity: VerbosityLevel,
+) -> Result<()> {
+    let bootstrap_peers = node_registry.bootstrap_peers.clone();
+    let env_variables = node_registry.environment_variables.clone();
+    let node = node_registry
+        .nodes
+        .iter_mut()
+        .find(|node| node.service_name == service_name)
+        .ok_or_else(|| Error::ServiceNotFound(service_name.to_string()))?;
+    let options = UpgradeOptions {
+        auto_restart: node.auto_restart,
+        bootstrap_peers,
+        env_variables,
+        force: false,
+        start_service: true,
+        target_bin_path,
+        target_version,
+    };
+
+    info!("Upgrading the {service_name} service in place");
+    let service = NodeService::new(node, rpc_client);
+    let mut service_manager = ServiceManager::new(service, service_control, verbosity);
+    let result = service_manager.upgrade(options).await?;
+    node_registry.save()?;
+
+    match result {
+        UpgradeResult::UpgradedButNotStarted(_, _, err) => Err(
+            Error::ServiceUpgradedButNotStarted(service_name.to_string(), err),
+        ),
+        _ => Ok(()),
+    }
+}
+
 pub fn print_banner(text: &str) {
     let padding = 2;
     let text_width = text.len() + padding * 2;
@@ -638,9 +1033,10 @@ mod tests {
     use assert_fs::prelude::*;
     use assert_matches::assert_matches;
     use async_trait::async_trait;
+    use chrono::NaiveTime;
     use color_eyre::eyre::Result;
     use libp2p_identity::PeerId;
-    use mockall::{mock, predicate::*};
+    use mockall::{mock, predicate::*, Sequence};
     use predicates::prelude::*;
     use service_manager::ServiceInstallCtx;
     use sn_logging::LogFormat;
@@ -648,6 +1044,7 @@ mod tests {
         error::{Error as ServiceControlError, Result as ServiceControlResult},
         node::{NodeService, NodeServiceData},
         rpc::{NetworkInfo, NodeInfo, RecordAddress, RpcActions},
+        unit::ByteSize,
         UpgradeOptions, UpgradeResult,
     };
     use sn_transfers::NanoTokens;
@@ -733,14 +1130,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -826,14 +1231,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -889,14 +1302,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -989,14 +1410,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1067,14 +1496,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1155,14 +1592,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1208,14 +1653,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1256,14 +1709,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1302,14 +1763,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1351,14 +1820,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1411,14 +1888,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1535,14 +2020,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1622,14 +2115,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1752,14 +2253,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -1895,14 +2404,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2033,14 +2550,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2172,14 +2697,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2340,14 +2873,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2491,14 +3032,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: Some(LogFormat::Json),
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             owner: None,
             number: 1,
@@ -2645,14 +3194,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: true,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -2796,14 +3353,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             number: 1,
             node_port: Some(12000),
             owner: None,
@@ -2947,14 +3512,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: Some(12000),
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -3101,14 +3674,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: Some(12000),
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
@@ -3255,14 +3836,22 @@ mod tests {
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: Some("discord_username".to_string()),
@@ -3308,7 +3897,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn upgrade_should_retain_auto_restart() -> Result<()> {
+    async fn upgrade_should_retain_the_maintenance_window() -> Result<()> {
         let current_version = "0.1.0";
         let target_version = "0.2.0";
 
@@ -3353,10 +3942,12 @@ mod tests {
                         OsString::from("/var/safenode-manager/services/safenode1"),
                         OsString::from("--log-output-dest"),
                         OsString::from("/var/log/safenode/safenode1"),
-                        OsString::from("--owner"),
-                        OsString::from("discord_username"),
+                        OsString::from("--maintenance-start"),
+                        OsString::from("23:00"),
+                        OsString::from("--maintenance-end"),
+                        OsString::from("01:00"),
                     ],
-                    autostart: true,
+                    autostart: false,
                     contents: None,
                     environment: None,
                     label: "safenode1".parse()?,
@@ -3407,19 +3998,30 @@ mod tests {
             });
 
         let mut service_data = NodeServiceData {
-            auto_restart: true,
+            auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: Some((
+                NaiveTime::from_hms_opt(23, 0, 0).expect("a valid time"),
+                NaiveTime::from_hms_opt(1, 0, 0).expect("a valid time"),
+            )),
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
-            owner: Some("discord_username".to_string()),
+            owner: None,
             peer_id: Some(PeerId::from_str(
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
@@ -3443,7 +4045,7 @@ mod tests {
 
         service_manager
             .upgrade(UpgradeOptions {
-                auto_restart: true,
+                auto_restart: false,
                 bootstrap_peers: Vec::new(),
                 env_variables: None,
                 force: false,
@@ -3453,314 +4055,2334 @@ mod tests {
             })
             .await?;
 
-        assert!(service_manager.service.service_data.auto_restart,);
+        assert_eq!(
+            service_manager.service.service_data.maintenance_window,
+            Some((
+                NaiveTime::from_hms_opt(23, 0, 0).expect("a valid time"),
+                NaiveTime::from_hms_opt(1, 0, 0).expect("a valid time"),
+            ))
+        );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn remove_should_remove_an_added_node() -> Result<()> {
-        let temp_dir = assert_fs::TempDir::new()?;
-        let log_dir = temp_dir.child("safenode1-logs");
-        log_dir.create_dir_all()?;
-        let data_dir = temp_dir.child("safenode1-data");
-        data_dir.create_dir_all()?;
-        let safenode_bin = data_dir.child("safenode");
-        safenode_bin.write_binary(b"fake safenode binary")?;
+    async fn upgrade_should_retain_the_min_connections() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
 
         let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after binary upgrade
         mock_service_control
             .expect_uninstall()
             .with(eq("safenode1"), eq(false))
             .times(1)
             .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .with(
+                eq(ServiceInstallCtx {
+                    args: vec![
+                        OsString::from("--rpc"),
+                        OsString::from("127.0.0.1:8081"),
+                        OsString::from("--root-dir"),
+                        OsString::from("/var/safenode-manager/services/safenode1"),
+                        OsString::from("--log-output-dest"),
+                        OsString::from("/var/log/safenode/safenode1"),
+                        OsString::from("--min-connections"),
+                        OsString::from("25"),
+                    ],
+                    autostart: false,
+                    contents: None,
+                    environment: None,
+                    label: "safenode1".parse()?,
+                    program: current_node_bin.to_path_buf(),
+                    username: Some("safe".to_string()),
+                    working_directory: None,
+                }),
+                eq(false),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after service restart
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
-            data_dir_path: data_dir.to_path_buf(),
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
-            log_dir_path: log_dir.to_path_buf(),
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: Some(25),
             node_port: None,
             number: 1,
             owner: None,
-            pid: None,
-            peer_id: None,
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            pid: Some(1000),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: safenode_bin.to_path_buf(),
-            status: ServiceStatus::Stopped,
+            safenode_path: current_node_bin.to_path_buf(),
             service_name: "safenode1".to_string(),
-            version: "0.98.1".to_string(),
+            status: ServiceStatus::Running,
             upnp: false,
             user: Some("safe".to_string()),
             user_mode: false,
+            version: current_version.to_string(),
         };
-        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
         let mut service_manager = ServiceManager::new(
             service,
             Box::new(mock_service_control),
             VerbosityLevel::Normal,
         );
 
-        service_manager.remove(false).await?;
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: false,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
 
-        assert_matches!(
-            service_manager.service.service_data.status,
-            ServiceStatus::Removed
+        assert_eq!(
+            service_manager.service.service_data.min_connections,
+            Some(25)
         );
-        log_dir.assert(predicate::path::missing());
-        data_dir.assert(predicate::path::missing());
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn remove_should_return_an_error_if_attempting_to_remove_a_running_node() -> Result<()> {
+    async fn upgrade_should_retain_the_health_port() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
+
         let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
         mock_service_control
             .expect_is_service_process_running()
             .with(eq(1000))
             .times(1)
             .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
 
-        let mut service_data = NodeServiceData {
-            auto_restart: false,
-            connected_peers: None,
-            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
-            genesis: false,
-            home_network: false,
-            listen_addr: None,
-            local: false,
-            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
-            log_format: None,
-            metrics_port: None,
-            node_port: None,
-            number: 1,
-            owner: None,
-            pid: Some(1000),
-            peer_id: Some(PeerId::from_str(
-                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
-            )?),
-            reward_balance: Some(NanoTokens::zero()),
-            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
-            service_name: "safenode1".to_string(),
-            status: ServiceStatus::Running,
-            upnp: false,
-            user: Some("safe".to_string()),
-            user_mode: false,
-            version: "0.98.1".to_string(),
-        };
-        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
-        let mut service_manager = ServiceManager::new(
-            service,
-            Box::new(mock_service_control),
-            VerbosityLevel::Normal,
-        );
-
-        let result = service_manager.remove(false).await;
-        match result {
-            Ok(_) => panic!("This test should result in an error"),
-            Err(e) => assert_eq!(
-                "The service(s) is already running: [\"safenode1\"]",
-                e.to_string()
-            ),
-        }
-
-        Ok(())
-    }
-
-    #[tokio::test]
-    async fn remove_should_return_an_error_for_a_node_that_was_marked_running_but_was_not_actually_running(
-    ) -> Result<()> {
-        let temp_dir = assert_fs::TempDir::new()?;
-        let log_dir = temp_dir.child("safenode1-logs");
-        log_dir.create_dir_all()?;
-        let data_dir = temp_dir.child("safenode1-data");
-        data_dir.create_dir_all()?;
-        let safenode_bin = data_dir.child("safenode");
-        safenode_bin.write_binary(b"fake safenode binary")?;
+        // after binary upgrade
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .with(
+                eq(ServiceInstallCtx {
+                    args: vec![
+                        OsString::from("--rpc"),
+                        OsString::from("127.0.0.1:8081"),
+                        OsString::from("--root-dir"),
+                        OsString::from("/var/safenode-manager/services/safenode1"),
+                        OsString::from("--log-output-dest"),
+                        OsString::from("/var/log/safenode/safenode1"),
+                        OsString::from("--health-port"),
+                        OsString::from("14001"),
+                    ],
+                    autostart: false,
+                    contents: None,
+                    environment: None,
+                    label: "safenode1".parse()?,
+                    program: current_node_bin.to_path_buf(),
+                    username: Some("safe".to_string()),
+                    working_directory: None,
+                }),
+                eq(false),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
 
-        let mut mock_service_control = MockServiceControl::new();
+        // after service restart
         mock_service_control
-            .expect_is_service_process_running()
-            .with(eq(1000))
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
             .times(1)
-            .returning(|_| false);
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
+            cpu_quota: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: Some(14001),
             home_network: false,
             listen_addr: None,
             local: false,
             log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
-            pid: Some(1000),
             peer_id: Some(PeerId::from_str(
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
+            pid: Some(1000),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            safenode_path: current_node_bin.to_path_buf(),
             service_name: "safenode1".to_string(),
             status: ServiceStatus::Running,
             upnp: false,
             user: Some("safe".to_string()),
             user_mode: false,
-            version: "0.98.1".to_string(),
+            version: current_version.to_string(),
         };
-        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
         let mut service_manager = ServiceManager::new(
             service,
             Box::new(mock_service_control),
             VerbosityLevel::Normal,
         );
 
-        let result = service_manager.remove(false).await;
-        match result {
-            Ok(_) => panic!("This test should result in an error"),
-            Err(e) => assert_eq!(
-                "The service status is not as expected. Expected: Running",
-                e.to_string()
-            ),
-        }
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: false,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
+
+        assert_eq!(
+            service_manager.service.service_data.health_port,
+            Some(14001)
+        );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn remove_should_remove_an_added_node_and_keep_directories() -> Result<()> {
-        let temp_dir = assert_fs::TempDir::new()?;
-        let log_dir = temp_dir.child("safenode1-logs");
-        log_dir.create_dir_all()?;
-        let data_dir = temp_dir.child("safenode1-data");
-        data_dir.create_dir_all()?;
-        let safenode_bin = data_dir.child("safenode");
-        safenode_bin.write_binary(b"fake safenode binary")?;
+    async fn upgrade_should_retain_the_log_level() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
 
         let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after binary upgrade
         mock_service_control
             .expect_uninstall()
             .with(eq("safenode1"), eq(false))
             .times(1)
             .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .with(
+                eq(ServiceInstallCtx {
+                    args: vec![
+                        OsString::from("--rpc"),
+                        OsString::from("127.0.0.1:8081"),
+                        OsString::from("--root-dir"),
+                        OsString::from("/var/safenode-manager/services/safenode1"),
+                        OsString::from("--log-output-dest"),
+                        OsString::from("/var/log/safenode/safenode1"),
+                    ],
+                    autostart: false,
+                    contents: None,
+                    environment: Some(vec![("SN_LOG".to_string(), "sn_node=debug".to_string())]),
+                    label: "safenode1".parse()?,
+                    program: current_node_bin.to_path_buf(),
+                    username: Some("safe".to_string()),
+                    working_directory: None,
+                }),
+                eq(false),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after service restart
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
             connected_peers: None,
-            data_dir_path: data_dir.to_path_buf(),
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
-            log_dir_path: log_dir.to_path_buf(),
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: Some("sn_node=debug".to_string()),
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
             owner: None,
-            pid: None,
-            peer_id: None,
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            pid: Some(1000),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: safenode_bin.to_path_buf(),
+            safenode_path: current_node_bin.to_path_buf(),
             service_name: "safenode1".to_string(),
-            status: ServiceStatus::Stopped,
+            status: ServiceStatus::Running,
             upnp: false,
             user: Some("safe".to_string()),
             user_mode: false,
-            version: "0.98.1".to_string(),
+            version: current_version.to_string(),
         };
-        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
         let mut service_manager = ServiceManager::new(
             service,
             Box::new(mock_service_control),
             VerbosityLevel::Normal,
         );
 
-        service_manager.remove(true).await?;
-
-        assert_matches!(
-            service_manager.service.service_data.status,
-            ServiceStatus::Removed
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: false,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
+
+        assert_eq!(
+            service_manager.service.service_data.log_level,
+            Some("sn_node=debug".to_string())
         );
-        log_dir.assert(predicate::path::is_dir());
-        data_dir.assert(predicate::path::is_dir());
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn remove_should_remove_a_user_mode_service() -> Result<()> {
-        let temp_dir = assert_fs::TempDir::new()?;
-        let log_dir = temp_dir.child("safenode1-logs");
-        log_dir.create_dir_all()?;
-        let data_dir = temp_dir.child("safenode1-data");
-        data_dir.create_dir_all()?;
-        let safenode_bin = data_dir.child("safenode");
-        safenode_bin.write_binary(b"fake safenode binary")?;
+    async fn upgrade_should_retain_auto_restart() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
 
         let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after binary upgrade
         mock_service_control
             .expect_uninstall()
-            .with(eq("safenode1"), eq(true))
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .with(
+                eq(ServiceInstallCtx {
+                    args: vec![
+                        OsString::from("--rpc"),
+                        OsString::from("127.0.0.1:8081"),
+                        OsString::from("--root-dir"),
+                        OsString::from("/var/safenode-manager/services/safenode1"),
+                        OsString::from("--log-output-dest"),
+                        OsString::from("/var/log/safenode/safenode1"),
+                        OsString::from("--owner"),
+                        OsString::from("discord_username"),
+                    ],
+                    autostart: true,
+                    contents: None,
+                    environment: None,
+                    label: "safenode1".parse()?,
+                    program: current_node_bin.to_path_buf(),
+                    username: Some("safe".to_string()),
+                    working_directory: None,
+                }),
+                eq(false),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after service restart
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
             .times(1)
             .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
 
         let mut service_data = NodeServiceData {
-            auto_restart: false,
+            auto_restart: true,
             connected_peers: None,
-            data_dir_path: data_dir.to_path_buf(),
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
             genesis: false,
+            health_port: None,
             home_network: false,
             listen_addr: None,
             local: false,
-            log_dir_path: log_dir.to_path_buf(),
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
             log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
             metrics_port: None,
+            min_connections: None,
             node_port: None,
             number: 1,
-            owner: None,
-            pid: None,
-            peer_id: None,
+            owner: Some("discord_username".to_string()),
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            pid: Some(1000),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
-            safenode_path: safenode_bin.to_path_buf(),
-            status: ServiceStatus::Stopped,
+            safenode_path: current_node_bin.to_path_buf(),
             service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
             upnp: false,
-            user: None,
-            user_mode: true,
-            version: "0.98.1".to_string(),
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: current_version.to_string(),
         };
-        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
         let mut service_manager = ServiceManager::new(
             service,
             Box::new(mock_service_control),
             VerbosityLevel::Normal,
         );
 
-        service_manager.remove(false).await?;
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: true,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
 
-        assert_matches!(
-            service_manager.service.service_data.status,
-            ServiceStatus::Removed
-        );
-        log_dir.assert(predicate::path::missing());
-        data_dir.assert(predicate::path::missing());
+        assert!(service_manager.service.service_data.auto_restart,);
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn upgrade_should_retain_the_env_file() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after binary upgrade
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .withf(|install_ctx, user_mode| {
+                install_ctx.contents.as_deref().is_some_and(|contents| {
+                    contents
+                        .lines()
+                        .any(|line| line == "EnvironmentFile=\"/etc/safenode/safenode1.env\"")
+                }) && !user_mode
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after service restart
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: Some(PathBuf::from("/etc/safenode/safenode1.env")),
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            pid: Some(1000),
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: current_node_bin.to_path_buf(),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: current_version.to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: false,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
+
+        assert_eq!(
+            service_manager.service.service_data.env_file,
+            Some(PathBuf::from("/etc/safenode/safenode1.env"))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn upgrade_should_retain_the_resource_limits() -> Result<()> {
+        let current_version = "0.1.0";
+        let target_version = "0.2.0";
+
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let current_install_dir = tmp_data_dir.child("safenode_install");
+        current_install_dir.create_dir_all()?;
+
+        let current_node_bin = current_install_dir.child("safenode");
+        current_node_bin.write_binary(b"fake safenode binary")?;
+        let target_node_bin = tmp_data_dir.child("safenode");
+        target_node_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+
+        // before binary upgrade
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after binary upgrade
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_install()
+            .withf(|install_ctx, user_mode| {
+                install_ctx.contents.as_deref().is_some_and(|contents| {
+                    contents.lines().any(|line| line == "CPUQuota=150%")
+                        && contents.lines().any(|line| line == "MemoryMax=1073741824")
+                }) && !user_mode
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // after service restart
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ());
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf().clone()))
+            .times(1)
+            .returning(|_| Ok(100));
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 2000,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: target_version.to_string(),
+                uptime: std::time::Duration::from_secs(1), // the service was just started
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: Some(1.5),
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: Some(ByteSize(1024 * 1024 * 1024)),
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            pid: Some(1000),
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: current_node_bin.to_path_buf(),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: current_version.to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(mock_rpc_client));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        service_manager
+            .upgrade(UpgradeOptions {
+                auto_restart: false,
+                bootstrap_peers: Vec::new(),
+                env_variables: None,
+                force: false,
+                start_service: true,
+                target_bin_path: target_node_bin.to_path_buf(),
+                target_version: Version::parse(target_version).unwrap(),
+            })
+            .await?;
+
+        assert_eq!(service_manager.service.service_data.cpu_quota, Some(1.5));
+        assert_eq!(
+            service_manager.service.service_data.memory_max,
+            Some(ByteSize(1024 * 1024 * 1024))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_should_remove_an_added_node() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let log_dir = temp_dir.child("safenode1-logs");
+        log_dir.create_dir_all()?;
+        let data_dir = temp_dir.child("safenode1-data");
+        data_dir.create_dir_all()?;
+        let safenode_bin = data_dir.child("safenode");
+        safenode_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: data_dir.to_path_buf(),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: log_dir.to_path_buf(),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            pid: None,
+            peer_id: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: safenode_bin.to_path_buf(),
+            status: ServiceStatus::Stopped,
+            service_name: "safenode1".to_string(),
+            version: "0.98.1".to_string(),
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+        };
+        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        service_manager.remove(false).await?;
+
+        assert_matches!(
+            service_manager.service.service_data.status,
+            ServiceStatus::Removed
+        );
+        log_dir.assert(predicate::path::missing());
+        data_dir.assert(predicate::path::missing());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_should_return_an_error_if_attempting_to_remove_a_running_node() -> Result<()> {
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true);
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            pid: Some(1000),
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        let result = service_manager.remove(false).await;
+        match result {
+            Ok(_) => panic!("This test should result in an error"),
+            Err(e) => assert_eq!(
+                "The service(s) is already running: [\"safenode1\"]",
+                e.to_string()
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_should_return_an_error_for_a_node_that_was_marked_running_but_was_not_actually_running(
+    ) -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let log_dir = temp_dir.child("safenode1-logs");
+        log_dir.create_dir_all()?;
+        let data_dir = temp_dir.child("safenode1-data");
+        data_dir.create_dir_all()?;
+        let safenode_bin = data_dir.child("safenode");
+        safenode_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| false);
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            pid: Some(1000),
+            peer_id: Some(PeerId::from_str(
+                "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
+            )?),
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        let result = service_manager.remove(false).await;
+        match result {
+            Ok(_) => panic!("This test should result in an error"),
+            Err(e) => assert_eq!(
+                "The service status is not as expected. Expected: Running",
+                e.to_string()
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_should_remove_an_added_node_and_keep_directories() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let log_dir = temp_dir.child("safenode1-logs");
+        log_dir.create_dir_all()?;
+        let data_dir = temp_dir.child("safenode1-data");
+        data_dir.create_dir_all()?;
+        let safenode_bin = data_dir.child("safenode");
+        safenode_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: data_dir.to_path_buf(),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: log_dir.to_path_buf(),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            pid: None,
+            peer_id: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: safenode_bin.to_path_buf(),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Stopped,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        service_manager.remove(true).await?;
+
+        assert_matches!(
+            service_manager.service.service_data.status,
+            ServiceStatus::Removed
+        );
+        log_dir.assert(predicate::path::is_dir());
+        data_dir.assert(predicate::path::is_dir());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_should_remove_a_user_mode_service() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let log_dir = temp_dir.child("safenode1-logs");
+        log_dir.create_dir_all()?;
+        let data_dir = temp_dir.child("safenode1-data");
+        data_dir.create_dir_all()?;
+        let safenode_bin = data_dir.child("safenode");
+        safenode_bin.write_binary(b"fake safenode binary")?;
+
+        let mut mock_service_control = MockServiceControl::new();
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(true))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut service_data = NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: data_dir.to_path_buf(),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: log_dir.to_path_buf(),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            pid: None,
+            peer_id: None,
+            reward_balance: Some(NanoTokens::zero()),
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: safenode_bin.to_path_buf(),
+            status: ServiceStatus::Stopped,
+            service_name: "safenode1".to_string(),
+            upnp: false,
+            user: None,
+            user_mode: true,
+            version: "0.98.1".to_string(),
+        };
+        let service = NodeService::new(&mut service_data, Box::new(MockRpcClient::new()));
+        let mut service_manager = ServiceManager::new(
+            service,
+            Box::new(mock_service_control),
+            VerbosityLevel::Normal,
+        );
+
+        service_manager.remove(false).await?;
+
+        assert_matches!(
+            service_manager.service.service_data.status,
+            ServiceStatus::Removed
+        );
+        log_dir.assert(predicate::path::missing());
+        data_dir.assert(predicate::path::missing());
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_owner_drift_should_report_nodes_with_a_different_owner() {
+        let node = |number: u16, owner: Option<&str>, status: ServiceStatus| NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}"
+            )),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number,
+            owner: owner.map(|owner| owner.to_string()),
+            peer_id: None,
+            pid: None,
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                8080 + number,
+            ),
+            safenode_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}/safenode"
+            )),
+            service_name: format!("safenode{number}"),
+            status,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![
+                node(1, Some("fleet_owner"), ServiceStatus::Running),
+                node(2, Some("someone_else"), ServiceStatus::Running),
+                node(3, Some("fleet_owner"), ServiceStatus::Stopped),
+                node(4, None, ServiceStatus::Removed),
+            ],
+            pending_node_add: None,
+            save_path: PathBuf::from("/var/safenode-manager/node_registry.json"),
+        };
+
+        assert_eq!(
+            find_owner_drift(&node_registry, "fleet_owner"),
+            vec!["safenode2".to_string()]
+        );
+    }
+
+    #[test]
+    fn find_port_conflicts_in_fleet_should_report_ports_shared_across_nodes() {
+        let node = |number: u16, rpc_port: u16, node_port: Option<u16>, status: ServiceStatus| {
+            NodeServiceData {
+                auto_restart: false,
+                connected_peers: None,
+                cpu_quota: None,
+                data_dir_path: PathBuf::from(format!(
+                    "/var/safenode-manager/services/safenode{number}"
+                )),
+                env_file: None,
+                ephemeral: false,
+                genesis: false,
+                health_port: None,
+                home_network: false,
+                listen_addr: None,
+                local: false,
+                log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+                log_format: None,
+                log_level: None,
+                maintenance_window: None,
+                memory_max: None,
+                metrics_port: None,
+                min_connections: None,
+                node_port,
+                number,
+                owner: None,
+                peer_id: None,
+                pid: None,
+                reward_balance: None,
+                rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), rpc_port),
+                safenode_path: PathBuf::from(format!(
+                    "/var/safenode-manager/services/safenode{number}/safenode"
+                )),
+                service_name: format!("safenode{number}"),
+                status,
+                upnp: false,
+                user: Some("safe".to_string()),
+                user_mode: false,
+                version: "0.98.1".to_string(),
+            }
+        };
+        // added separately, with a health port that is safenode2's node port
+        let mut safenode3 = node(3, 13003, Some(12003), ServiceStatus::Stopped);
+        safenode3.health_port = Some(12002);
+        let node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![
+                node(1, 13001, Some(12001), ServiceStatus::Running),
+                // added separately, with the same RPC port as safenode1
+                node(2, 13001, Some(12002), ServiceStatus::Running),
+                safenode3,
+                // removed nodes no longer hold their ports
+                node(4, 13003, Some(12001), ServiceStatus::Removed),
+            ],
+            pending_node_add: None,
+            save_path: PathBuf::from("/var/safenode-manager/node_registry.json"),
+        };
+
+        assert_eq!(
+            find_port_conflicts_in_fleet(&node_registry),
+            vec![
+                PortConflict {
+                    port: 12002,
+                    services: vec![
+                        ("safenode2".to_string(), PortKind::Node),
+                        ("safenode3".to_string(), PortKind::Health),
+                    ],
+                },
+                PortConflict {
+                    port: 13001,
+                    services: vec![
+                        ("safenode1".to_string(), PortKind::Rpc),
+                        ("safenode2".to_string(), PortKind::Rpc),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn restart_node_should_stop_then_start_the_named_service() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+        let mut seq = Sequence::new();
+
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true)
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode2"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode2"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(PathBuf::from(
+                "/var/safenode-manager/services/safenode2/safenode",
+            )))
+            .times(1)
+            .returning(|_| Ok(1001))
+            .in_sequence(&mut seq);
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 1001,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode2"),
+                log_path: PathBuf::from("/var/log/safenode/safenode2"),
+                version: "0.98.1".to_string(),
+                uptime: std::time::Duration::from_secs(1),
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let node = |number: u16, pid: u32| NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}"
+            )),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number,
+            owner: None,
+            peer_id: None,
+            pid: Some(pid),
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                8080 + number,
+            ),
+            safenode_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}/safenode"
+            )),
+            service_name: format!("safenode{number}"),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let mut node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![node(1, 999), node(2, 1000)],
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        restart_node(
+            &mut node_registry,
+            "safenode2",
+            Box::new(mock_service_control),
+            Box::new(mock_rpc_client),
+            VerbosityLevel::Normal,
+        )
+        .await?;
+
+        assert_eq!(node_registry.nodes[1].pid, Some(1001));
+        assert_matches!(node_registry.nodes[1].status, ServiceStatus::Running);
+        assert_eq!(node_registry.nodes[0].pid, Some(999));
+
+        let result = restart_node(
+            &mut node_registry,
+            "safenode3",
+            Box::new(MockServiceControl::new()),
+            Box::new(MockRpcClient::new()),
+            VerbosityLevel::Normal,
+        )
+        .await;
+        assert_matches!(result, Err(Error::ServiceNotFound(name)) if name == "safenode3");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn set_node_owner_should_only_change_the_owner_of_the_named_service() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+        let mut seq = Sequence::new();
+
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true)
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode2"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode2"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_install()
+            .withf(|install_ctx, user_mode| {
+                let args = install_ctx
+                    .args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string())
+                    .collect::<Vec<_>>();
+                let has_directives = match &install_ctx.contents {
+                    Some(contents) => [
+                        "EnvironmentFile=\"/etc/safenode/safenode2.env\"",
+                        "CPUQuota=50%",
+                        "MemoryMax=536870912",
+                    ]
+                    .iter()
+                    .all(|directive| contents.lines().any(|line| line == *directive)),
+                    // The directives are only written on Linux, as the unit's contents.
+                    None => !cfg!(target_os = "linux"),
+                };
+                install_ctx.label.to_string() == "safenode2"
+                    && args.windows(2).any(|pair| pair == ["--owner", "new-owner"])
+                    && args
+                        .windows(2)
+                        .any(|pair| pair == ["--min-connections", "25"])
+                    && args.windows(4).any(|window| {
+                        window == ["--maintenance-start", "23:00", "--maintenance-end", "01:00"]
+                    })
+                    && has_directives
+                    && !user_mode
+            })
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode2"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(PathBuf::from(
+                "/var/safenode-manager/services/safenode2/safenode",
+            )))
+            .times(1)
+            .returning(|_| Ok(1001))
+            .in_sequence(&mut seq);
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 1001,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode2"),
+                log_path: PathBuf::from("/var/log/safenode/safenode2"),
+                version: "0.98.1".to_string(),
+                uptime: std::time::Duration::from_secs(1),
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let node = |number: u16, pid: u32| NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}"
+            )),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number,
+            owner: None,
+            peer_id: None,
+            pid: Some(pid),
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                8080 + number,
+            ),
+            safenode_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}/safenode"
+            )),
+            service_name: format!("safenode{number}"),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        };
+        let mut safenode2 = node(2, 1000);
+        safenode2.cpu_quota = Some(0.5);
+        safenode2.env_file = Some(PathBuf::from("/etc/safenode/safenode2.env"));
+        safenode2.maintenance_window = Some((
+            NaiveTime::from_hms_opt(23, 0, 0).expect("a valid time"),
+            NaiveTime::from_hms_opt(1, 0, 0).expect("a valid time"),
+        ));
+        safenode2.memory_max = Some(ByteSize(512 * 1024 * 1024));
+        safenode2.min_connections = Some(25);
+        let mut node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![node(1, 999), safenode2],
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        set_node_owner(
+            &mut node_registry,
+            "safenode2",
+            "new-owner".to_string(),
+            Box::new(mock_service_control),
+            Box::new(mock_rpc_client),
+            VerbosityLevel::Normal,
+        )
+        .await?;
+
+        assert_eq!(node_registry.nodes[1].pid, Some(1001));
+        assert_matches!(node_registry.nodes[1].status, ServiceStatus::Running);
+        assert_eq!(node_registry.nodes[1].owner, Some("new-owner".to_string()));
+        assert_eq!(node_registry.nodes[1].min_connections, Some(25));
+        assert_eq!(node_registry.nodes[0].pid, Some(999));
+        assert_eq!(node_registry.nodes[0].owner, None);
+
+        let result = set_node_owner(
+            &mut node_registry,
+            "safenode3",
+            "new-owner".to_string(),
+            Box::new(MockServiceControl::new()),
+            Box::new(MockRpcClient::new()),
+            VerbosityLevel::Normal,
+        )
+        .await;
+        assert_matches!(result, Err(Error::ServiceNotFound(name)) if name == "safenode3");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upgrade_node_should_replace_the_binary_and_keep_the_service_args() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let service_dir = temp_dir.child("services").child("safenode1");
+        service_dir.create_dir_all()?;
+        let current_node_bin = service_dir.child("safenode");
+        current_node_bin.write_binary(b"safenode 0.98.1")?;
+        let target_node_bin = temp_dir.child("safenode");
+        target_node_bin.write_binary(b"safenode 0.99.0")?;
+        let data_dir = temp_dir.child("data");
+        let log_dir = temp_dir.child("logs");
+        let peer = "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE";
+
+        let expected_program = current_node_bin.to_path_buf();
+        let expected_args = vec![
+            "--rpc".to_string(),
+            "127.0.0.1:8081".to_string(),
+            "--root-dir".to_string(),
+            data_dir.to_string_lossy().to_string(),
+            "--log-output-dest".to_string(),
+            log_dir.to_string_lossy().to_string(),
+            "--port".to_string(),
+            "12001".to_string(),
+            "--metrics-server-port".to_string(),
+            "13001".to_string(),
+            "--owner".to_string(),
+            "alice".to_string(),
+            "--peer".to_string(),
+            peer.to_string(),
+        ];
+
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+        let mut seq = Sequence::new();
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true)
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_install()
+            .withf(move |install_ctx, user_mode| {
+                let args = install_ctx
+                    .args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string())
+                    .collect::<Vec<_>>();
+                install_ctx.program == expected_program && args == expected_args && !user_mode
+            })
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf()))
+            .times(1)
+            .returning(|_| Ok(1001))
+            .in_sequence(&mut seq);
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 1001,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: "0.99.0".to_string(),
+                uptime: std::time::Duration::from_secs(1),
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let mut node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![peer.parse()?],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![NodeServiceData {
+                auto_restart: false,
+                connected_peers: None,
+                cpu_quota: None,
+                data_dir_path: data_dir.to_path_buf(),
+                env_file: None,
+                ephemeral: false,
+                genesis: false,
+                health_port: None,
+                home_network: false,
+                listen_addr: None,
+                local: false,
+                log_dir_path: log_dir.to_path_buf(),
+                log_format: None,
+                log_level: None,
+                maintenance_window: None,
+                memory_max: None,
+                metrics_port: Some(13001),
+                min_connections: None,
+                node_port: Some(12001),
+                number: 1,
+                owner: Some("alice".to_string()),
+                peer_id: None,
+                pid: Some(1000),
+                reward_balance: None,
+                rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+                safenode_path: current_node_bin.to_path_buf(),
+                service_name: "safenode1".to_string(),
+                status: ServiceStatus::Running,
+                upnp: false,
+                user: Some("safe".to_string()),
+                user_mode: false,
+                version: "0.98.1".to_string(),
+            }],
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        upgrade_node(
+            &mut node_registry,
+            "safenode1",
+            Version::parse("0.99.0")?,
+            target_node_bin.to_path_buf(),
+            Box::new(mock_service_control),
+            Box::new(mock_rpc_client),
+            VerbosityLevel::Normal,
+        )
+        .await?;
+
+        current_node_bin.assert("safenode 0.99.0");
+        assert_eq!(node_registry.nodes[0].version, "0.99.0");
+        assert_eq!(node_registry.nodes[0].pid, Some(1001));
+        assert_matches!(node_registry.nodes[0].status, ServiceStatus::Running);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upgrade_node_should_keep_the_env_file_resource_limits_and_min_connections(
+    ) -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let service_dir = temp_dir.child("services").child("safenode1");
+        service_dir.create_dir_all()?;
+        let current_node_bin = service_dir.child("safenode");
+        current_node_bin.write_binary(b"safenode 0.98.1")?;
+        let target_node_bin = temp_dir.child("safenode");
+        target_node_bin.write_binary(b"safenode 0.99.0")?;
+        let data_dir = temp_dir.child("data");
+        let log_dir = temp_dir.child("logs");
+        let peer = "/ip4/127.0.0.1/udp/12000/quic-v1/p2p/12D3KooWRBhwfeP2Y4TCx1SM6s9rUoHhR5STiGwxBhgFRcw3UERE";
+
+        let expected_program = current_node_bin.to_path_buf();
+        let expected_args = vec![
+            "--rpc".to_string(),
+            "127.0.0.1:8081".to_string(),
+            "--root-dir".to_string(),
+            data_dir.to_string_lossy().to_string(),
+            "--log-output-dest".to_string(),
+            log_dir.to_string_lossy().to_string(),
+            "--port".to_string(),
+            "12001".to_string(),
+            "--metrics-server-port".to_string(),
+            "13001".to_string(),
+            "--min-connections".to_string(),
+            "25".to_string(),
+            "--owner".to_string(),
+            "alice".to_string(),
+            "--peer".to_string(),
+            peer.to_string(),
+        ];
+
+        let mut mock_service_control = MockServiceControl::new();
+        let mut mock_rpc_client = MockRpcClient::new();
+        let mut seq = Sequence::new();
+        mock_service_control
+            .expect_is_service_process_running()
+            .with(eq(1000))
+            .times(1)
+            .returning(|_| true)
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_stop()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_uninstall()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_install()
+            .withf(move |install_ctx, user_mode| {
+                let args = install_ctx
+                    .args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string())
+                    .collect::<Vec<_>>();
+                let expected_directives = [
+                    "EnvironmentFile=\"/etc/safenode/safenode1.env\"",
+                    "CPUQuota=150%",
+                    "MemoryMax=1073741824",
+                ];
+                let has_directives = match &install_ctx.contents {
+                    Some(contents) => expected_directives
+                        .iter()
+                        .all(|directive| contents.lines().any(|line| line == *directive)),
+                    // The directives are only written on Linux, as the unit's contents.
+                    None => !cfg!(target_os = "linux"),
+                };
+                install_ctx.program == expected_program
+                    && args == expected_args
+                    && has_directives
+                    && !user_mode
+            })
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_start()
+            .with(eq("safenode1"), eq(false))
+            .times(1)
+            .returning(|_, _| Ok(()))
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_wait()
+            .with(eq(3000))
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+        mock_service_control
+            .expect_get_process_pid()
+            .with(eq(current_node_bin.to_path_buf()))
+            .times(1)
+            .returning(|_| Ok(1001))
+            .in_sequence(&mut seq);
+        mock_rpc_client.expect_node_info().times(1).returning(|| {
+            Ok(NodeInfo {
+                pid: 1001,
+                peer_id: PeerId::from_str("12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR")?,
+                data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+                log_path: PathBuf::from("/var/log/safenode/safenode1"),
+                version: "0.99.0".to_string(),
+                uptime: std::time::Duration::from_secs(1),
+                wallet_balance: 0,
+            })
+        });
+        mock_rpc_client
+            .expect_network_info()
+            .times(1)
+            .returning(|| {
+                Ok(NetworkInfo {
+                    connected_peers: Vec::new(),
+                    listeners: Vec::new(),
+                })
+            });
+
+        let mut node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![peer.parse()?],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![NodeServiceData {
+                auto_restart: false,
+                connected_peers: None,
+                cpu_quota: Some(1.5),
+                data_dir_path: data_dir.to_path_buf(),
+                env_file: Some(PathBuf::from("/etc/safenode/safenode1.env")),
+                ephemeral: false,
+                genesis: false,
+                health_port: None,
+                home_network: false,
+                listen_addr: None,
+                local: false,
+                log_dir_path: log_dir.to_path_buf(),
+                log_format: None,
+                log_level: None,
+                maintenance_window: None,
+                memory_max: Some(ByteSize(1024 * 1024 * 1024)),
+                metrics_port: Some(13001),
+                min_connections: Some(25),
+                node_port: Some(12001),
+                number: 1,
+                owner: Some("alice".to_string()),
+                peer_id: None,
+                pid: Some(1000),
+                reward_balance: None,
+                rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+                safenode_path: current_node_bin.to_path_buf(),
+                service_name: "safenode1".to_string(),
+                status: ServiceStatus::Running,
+                upnp: false,
+                user: Some("safe".to_string()),
+                user_mode: false,
+                version: "0.98.1".to_string(),
+            }],
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        upgrade_node(
+            &mut node_registry,
+            "safenode1",
+            Version::parse("0.99.0")?,
+            target_node_bin.to_path_buf(),
+            Box::new(mock_service_control),
+            Box::new(mock_rpc_client),
+            VerbosityLevel::Normal,
+        )
+        .await?;
+
+        current_node_bin.assert("safenode 0.99.0");
+        assert_eq!(node_registry.nodes[0].version, "0.99.0");
+        assert_eq!(node_registry.nodes[0].pid, Some(1001));
+        assert_matches!(node_registry.nodes[0].status, ServiceStatus::Running);
+        assert_eq!(
+            node_registry.nodes[0].env_file,
+            Some(PathBuf::from("/etc/safenode/safenode1.env"))
+        );
+        assert_eq!(node_registry.nodes[0].cpu_quota, Some(1.5));
+        assert_eq!(
+            node_registry.nodes[0].memory_max,
+            Some(ByteSize(1024 * 1024 * 1024))
+        );
+        assert_eq!(node_registry.nodes[0].min_connections, Some(25));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tail_node_logs_should_return_the_last_lines_of_the_node_log() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let log_dir = temp_dir.child("logs");
+        log_dir.create_dir_all()?;
+        log_dir
+            .child(NODE_LOG_FILE_NAME)
+            .write_str("line 1\nline 2\nline 3\nline 4\nline 5\n")?;
+
+        let node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes: vec![NodeServiceData {
+                auto_restart: false,
+                connected_peers: None,
+                cpu_quota: None,
+                data_dir_path: temp_dir.child("data").to_path_buf(),
+                env_file: None,
+                ephemeral: false,
+                genesis: false,
+                health_port: None,
+                home_network: false,
+                listen_addr: None,
+                local: false,
+                log_dir_path: log_dir.to_path_buf(),
+                log_format: None,
+                log_level: None,
+                maintenance_window: None,
+                memory_max: None,
+                metrics_port: None,
+                min_connections: None,
+                node_port: None,
+                number: 1,
+                owner: None,
+                peer_id: None,
+                pid: None,
+                reward_balance: None,
+                rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+                safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+                service_name: "safenode1".to_string(),
+                status: ServiceStatus::Running,
+                upnp: false,
+                user: Some("safe".to_string()),
+                user_mode: false,
+                version: "0.98.1".to_string(),
+            }],
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        let tail = tail_node_logs(&node_registry, "safenode1", 2)?;
+        assert_eq!(tail, vec!["line 4".to_string(), "line 5".to_string()]);
+
+        let tail = tail_node_logs(&node_registry, "safenode1", 10)?;
+        assert_eq!(tail.len(), 5);
+        assert_eq!(tail[0], "line 1");
+
+        let result = tail_node_logs(&node_registry, "safenode2", 2);
+        assert_matches!(result, Err(Error::ServiceNotFound(name)) if name == "safenode2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn collect_diagnostics_should_bundle_logs_and_redact_secrets() -> Result<()> {
+        let temp_dir = assert_fs::TempDir::new()?;
+        let nodes = (1..=2)
+            .map(|number| {
+                let log_dir = temp_dir.child(format!("logs/safenode{number}"));
+                log_dir.create_dir_all()?;
+                log_dir.child(NODE_LOG_FILE_NAME).write_str(&format!(
+                    "safenode{number} log line\n\
+                         safenode{number} started with SN_SECRET_KEY set to super-secret-value\n\
+                         safenode{number} connecting with api_token=hunter2, retries=3"
+                ))?;
+                Ok(NodeServiceData {
+                    auto_restart: false,
+                    connected_peers: None,
+                    cpu_quota: None,
+                    data_dir_path: temp_dir
+                        .child(format!("data/safenode{number}"))
+                        .to_path_buf(),
+                    env_file: None,
+                    ephemeral: false,
+                    genesis: false,
+                    health_port: None,
+                    home_network: false,
+                    listen_addr: None,
+                    local: false,
+                    log_dir_path: log_dir.to_path_buf(),
+                    log_format: None,
+                    log_level: None,
+                    maintenance_window: None,
+                    memory_max: None,
+                    metrics_port: None,
+                    min_connections: None,
+                    node_port: None,
+                    number,
+                    owner: None,
+                    peer_id: None,
+                    pid: None,
+                    reward_balance: None,
+                    rpc_socket_addr: SocketAddr::new(
+                        IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                        8080 + number,
+                    ),
+                    safenode_path: PathBuf::from(format!(
+                        "/var/safenode-manager/services/safenode{number}/safenode"
+                    )),
+                    service_name: format!("safenode{number}"),
+                    status: ServiceStatus::Running,
+                    upnp: false,
+                    user: Some("safe".to_string()),
+                    user_mode: false,
+                    version: "0.98.1".to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let node_registry = NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: Some(vec![(
+                "SN_SECRET_KEY".to_string(),
+                "super-secret-value".to_string(),
+            )]),
+            faucet: None,
+            nat_status: None,
+            nodes,
+            pending_node_add: None,
+            save_path: temp_dir.child("node_reg.json").to_path_buf(),
+        };
+
+        let archive_path = collect_diagnostics(
+            &node_registry,
+            &["safenode1", "safenode2"],
+            temp_dir.child("diagnostics").path(),
+        )?;
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(archive_path)?)?;
+        let mut names = archive.file_names().map(String::from).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "config.txt".to_string(),
+                format!("safenode1/{NODE_LOG_FILE_NAME}"),
+                format!("safenode2/{NODE_LOG_FILE_NAME}"),
+                "version.txt".to_string(),
+            ]
+        );
+
+        for index in 0..archive.len() {
+            let mut contents = String::new();
+            std::io::Read::read_to_string(&mut archive.by_index(index)?, &mut contents)?;
+            assert!(!contents.contains("super-secret-value"));
+            assert!(!contents.contains("hunter2"));
+        }
+
+        let mut log = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name(&format!("safenode1/{NODE_LOG_FILE_NAME}"))?,
+            &mut log,
+        )?;
+        assert_eq!(
+            log,
+            "safenode1 log line\n\
+             safenode1 started with SN_SECRET_KEY set to <redacted>\n\
+             safenode1 connecting with api_token=<redacted>, retries=3"
+        );
+
+        let mut config = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("config.txt")?, &mut config)?;
+        assert!(config.contains("SN_SECRET_KEY=<redacted>"));
+
+        let result = collect_diagnostics(
+            &node_registry,
+            &["safenode3"],
+            temp_dir.child("diagnostics").path(),
+        );
+        assert_matches!(result, Err(Error::ServiceNotFound(name)) if name == "safenode3");
 
         Ok(())
     }
@@ -294,6 +294,7 @@ pub async fn run_network(
         let pid = launcher.launch_faucet(&bootstrap_peers[0])?;
         let version = get_bin_version(&options.faucet_bin_path)?;
         let faucet = FaucetServiceData {
+            dispense_amount: None,
             faucet_path: options.faucet_bin_path,
             local: true,
             log_dir_path: get_faucet_data_dir(),
@@ -348,14 +349,22 @@ pub async fn run_node(
     Ok(NodeServiceData {
         auto_restart: false,
         connected_peers,
+        cpu_quota: None,
         data_dir_path: node_info.data_path,
+        env_file: None,
+        ephemeral: false,
         genesis: run_options.genesis,
+        health_port: None,
         home_network: false,
         listen_addr: Some(listen_addrs),
         local: true,
         log_dir_path: node_info.log_path,
         log_format: run_options.log_format,
+        log_level: None,
+        maintenance_window: None,
+        memory_max: None,
         metrics_port: None,
+        min_connections: None,
         node_port: None,
         number: run_options.number,
         owner: run_options.owner,
@@ -14,7 +14,7 @@ use color_eyre::{
     eyre::{eyre, OptionExt},
     Result,
 };
-use libp2p::PeerId;
+use libp2p::{Multiaddr, PeerId};
 use sn_service_management::{
     control::{ServiceControl, ServiceController},
     rpc::RpcClient,
@@ -27,6 +27,7 @@ pub async fn restart_node_service(
     retain_peer_id: bool,
 ) -> Result<()> {
     let nodes_len = node_registry.nodes.len();
+    let is_registered_genesis = is_registered_genesis_node(node_registry, peer_id);
     let current_node_mut = node_registry
         .nodes
         .iter_mut()
@@ -36,6 +37,12 @@ pub async fn restart_node_service(
             format!("Could not find the provided PeerId: {peer_id:?}")
         })?;
     let current_node_clone = current_node_mut.clone();
+    if current_node_clone.genesis && !retain_peer_id {
+        error!("The genesis node can only be restarted with its peer ID retained");
+        return Err(eyre!(
+            "The genesis node can only be restarted with its peer ID retained"
+        ));
+    }
 
     let rpc_client = RpcClient::from_socket_addr(current_node_mut.rpc_socket_addr);
     let service = NodeService::new(current_node_mut, Box::new(rpc_client));
@@ -62,23 +69,12 @@ pub async fn restart_node_service(
                 )
             })?;
         let install_ctx = InstallNodeServiceCtxBuilder {
-            autostart: current_node_clone.auto_restart,
-            bootstrap_peers: node_registry.bootstrap_peers.clone(),
-            data_dir_path: current_node_clone.data_dir_path.clone(),
-            env_variables: node_registry.environment_variables.clone(),
-            genesis: current_node_clone.genesis,
-            home_network: current_node_clone.home_network,
-            local: current_node_clone.local,
-            log_dir_path: current_node_clone.log_dir_path.clone(),
-            log_format: current_node_clone.log_format,
-            metrics_port: None,
-            owner: current_node_clone.owner.clone(),
-            name: current_node_clone.service_name.clone(),
-            node_port: current_node_clone.get_safenode_port(),
-            rpc_socket_addr: current_node_clone.rpc_socket_addr,
-            safenode_path: current_node_clone.safenode_path.clone(),
-            service_user: current_node_clone.user.clone(),
-            upnp: current_node_clone.upnp,
+            confirm_genesis: is_registered_genesis,
+            ..reinstall_ctx_builder(
+                &current_node_clone,
+                node_registry.bootstrap_peers.clone(),
+                node_registry.environment_variables.clone(),
+            )
         }
         .build()?;
         service_control.install(install_ctx, false).map_err(|err| {
@@ -177,23 +173,17 @@ pub async fn restart_node_service(
         };
 
         let install_ctx = InstallNodeServiceCtxBuilder {
-            autostart: current_node_clone.auto_restart,
-            bootstrap_peers: node_registry.bootstrap_peers.clone(),
             data_dir_path: data_dir_path.clone(),
-            env_variables: node_registry.environment_variables.clone(),
-            genesis: current_node_clone.genesis,
-            home_network: current_node_clone.home_network,
-            local: current_node_clone.local,
             log_dir_path: log_dir_path.clone(),
-            log_format: current_node_clone.log_format,
             name: new_service_name.clone(),
-            metrics_port: None,
             node_port: None,
             owner: None,
-            rpc_socket_addr: current_node_clone.rpc_socket_addr,
             safenode_path: safenode_path.clone(),
-            service_user: current_node_clone.user.clone(),
-            upnp: current_node_clone.upnp,
+            ..reinstall_ctx_builder(
+                &current_node_clone,
+                node_registry.bootstrap_peers.clone(),
+                node_registry.environment_variables.clone(),
+            )
         }
         .build()?;
         service_control.install(install_ctx, false).map_err(|err| {
@@ -203,14 +193,22 @@ pub async fn restart_node_service(
         let mut node = NodeServiceData {
             auto_restart: current_node_clone.auto_restart,
             connected_peers: None,
+            cpu_quota: current_node_clone.cpu_quota,
             data_dir_path,
+            env_file: current_node_clone.env_file.clone(),
+            ephemeral: current_node_clone.ephemeral,
             genesis: current_node_clone.genesis,
+            health_port: current_node_clone.health_port,
             home_network: current_node_clone.home_network,
             listen_addr: None,
             local: current_node_clone.local,
             log_dir_path,
             log_format: current_node_clone.log_format,
+            log_level: current_node_clone.log_level.clone(),
+            maintenance_window: current_node_clone.maintenance_window,
+            memory_max: current_node_clone.memory_max,
             metrics_port: None,
+            min_connections: current_node_clone.min_connections,
             node_port: None,
             number: new_node_number as u16,
             owner: None,
@@ -242,3 +240,156 @@ pub async fn restart_node_service(
 
     Ok(())
 }
+
+/// The builder that reinstalls a node from its registry entry, so the service keeps the arguments
+/// and unit directives it was added with. The caller replaces whatever must differ, e.g., the name
+/// and paths of a node started with a new peer ID.
+fn reinstall_ctx_builder(
+    node: &NodeServiceData,
+    bootstrap_peers: Vec<Multiaddr>,
+    env_variables: Option<Vec<(String, String)>>,
+) -> InstallNodeServiceCtxBuilder {
+    InstallNodeServiceCtxBuilder {
+        autostart: node.auto_restart,
+        bootstrap_peers,
+        confirm_genesis: false,
+        cpu_quota: node.cpu_quota,
+        data_dir_path: node.data_dir_path.clone(),
+        env_file: node.env_file.clone(),
+        env_variables,
+        ephemeral: node.ephemeral,
+        genesis: node.genesis,
+        health_port: node.health_port,
+        home_network: node.home_network,
+        local: node.local,
+        log_dir_path: node.log_dir_path.clone(),
+        log_format: node.log_format,
+        log_level: node.log_level.clone(),
+        maintenance_window: node.maintenance_window,
+        memory_max: node.memory_max,
+        metrics_port: None,
+        min_connections: node.min_connections,
+        name: node.service_name.clone(),
+        node_port: node.get_safenode_port(),
+        owner: node.owner.clone(),
+        rpc_socket_addr: node.rpc_socket_addr,
+        safenode_path: node.safenode_path.clone(),
+        service_user: node.user.clone(),
+        upnp: node.upnp,
+        user_mode: node.user_mode,
+    }
+}
+
+/// The registry already recording the node as the genesis node is what confirms it when it is
+/// reinstalled, since the network it started already exists.
+fn is_registered_genesis_node(node_registry: &NodeRegistry, peer_id: PeerId) -> bool {
+    node_registry.nodes.iter().any(|node| {
+        node.genesis
+            && node.status != ServiceStatus::Removed
+            && node.peer_id.is_some_and(|id| id == peer_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use sn_service_management::unit::ByteSize;
+    use std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        path::PathBuf,
+    };
+
+    fn node_service_data() -> NodeServiceData {
+        NodeServiceData {
+            auto_restart: false,
+            connected_peers: None,
+            cpu_quota: None,
+            data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            env_file: None,
+            ephemeral: false,
+            genesis: false,
+            health_port: None,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            log_level: None,
+            maintenance_window: None,
+            memory_max: None,
+            metrics_port: None,
+            min_connections: None,
+            node_port: None,
+            number: 1,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
+            safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Running,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }
+    }
+
+    #[test]
+    fn reinstall_ctx_builder_should_keep_the_resource_limits() {
+        let mut node = node_service_data();
+        node.cpu_quota = Some(1.5);
+        node.memory_max = Some(ByteSize(2 * 1024 * 1024 * 1024));
+
+        let builder = reinstall_ctx_builder(&node, vec![], None);
+
+        assert_eq!(builder.cpu_quota, Some(1.5));
+        assert_eq!(builder.memory_max, Some(ByteSize(2 * 1024 * 1024 * 1024)));
+    }
+
+    #[test]
+    fn reinstall_ctx_builder_should_keep_the_maintenance_window() {
+        let window = (
+            NaiveTime::from_hms_opt(23, 0, 0).expect("a valid time"),
+            NaiveTime::from_hms_opt(1, 0, 0).expect("a valid time"),
+        );
+        let mut node = node_service_data();
+        node.maintenance_window = Some(window);
+
+        let builder = reinstall_ctx_builder(&node, vec![], None);
+
+        assert_eq!(builder.maintenance_window, Some(window));
+    }
+
+    #[test]
+    fn reinstall_ctx_builder_should_keep_the_min_connections() {
+        let mut node = node_service_data();
+        node.min_connections = Some(25);
+
+        let builder = reinstall_ctx_builder(&node, vec![], None);
+
+        assert_eq!(builder.min_connections, Some(25));
+    }
+
+    #[test]
+    fn reinstall_ctx_builder_should_keep_the_health_port() {
+        let mut node = node_service_data();
+        node.health_port = Some(14001);
+
+        let builder = reinstall_ctx_builder(&node, vec![], None);
+
+        assert_eq!(builder.health_port, Some(14001));
+    }
+
+    #[test]
+    fn reinstall_ctx_builder_should_keep_the_log_level() {
+        let mut node = node_service_data();
+        node.log_level = Some("sn_node=debug".to_string());
+
+        let builder = reinstall_ctx_builder(&node, vec![], None);
+
+        assert_eq!(builder.log_level, Some("sn_node=debug".to_string()));
+    }
+}
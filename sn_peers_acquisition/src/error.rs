@@ -1,3 +1,4 @@
+use libp2p::Multiaddr;
 use thiserror::Error;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -7,6 +8,8 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub enum Error {
     #[error("Could not parse the supplied multiaddr or socket address")]
     InvalidPeerAddr,
+    #[error(transparent)]
+    UnsupportedTransport(#[from] UnsupportedTransport),
     #[error("Could not obtain network contacts from {0} after {1} retries")]
     NetworkContactsUnretrievable(String, usize),
     #[error("No valid multaddr was present in the contacts file at {0}")]
@@ -20,3 +23,8 @@ pub enum Error {
     #[error(transparent)]
     UrlParseError(#[from] url::ParseError),
 }
+
+/// A peer address with a protocol stack the node can't dial, so it would never connect.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("The peer address {0} does not use a supported transport")]
+pub struct UnsupportedTransport(pub Multiaddr);
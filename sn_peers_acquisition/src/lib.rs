@@ -8,11 +8,11 @@
 
 pub mod error;
 
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, UnsupportedTransport};
 use clap::Args;
 #[cfg(feature = "network-contacts")]
 use lazy_static::lazy_static;
-use libp2p::{multiaddr::Protocol, Multiaddr};
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 use rand::{seq::SliceRandom, thread_rng};
 #[cfg(feature = "network-contacts")]
 use sn_networking::version::get_network_version;
@@ -48,7 +48,7 @@ pub struct PeersArgs {
     /// Peer(s) to use for bootstrap, in a 'multiaddr' format containing the peer ID.
     ///
     /// A multiaddr looks like
-    /// '/ip4/1.2.3.4/tcp/1200/tcp/p2p/12D3KooWRi6wF7yxWLuPSNskXc6kQ5cJ6eaymeMbCRdTnMesPgFx' where
+    /// '/ip4/1.2.3.4/udp/1200/quic-v1/p2p/12D3KooWRi6wF7yxWLuPSNskXc6kQ5cJ6eaymeMbCRdTnMesPgFx' where
     /// `1.2.3.4` is the IP, `1200` is the port and the (optional) last part is the peer ID.
     ///
     /// This argument can be provided multiple times to connect to multiple peers.
@@ -158,7 +158,9 @@ impl PeersArgs {
     }
 }
 
-/// Parse strings like `1.2.3.4:1234` and `/ip4/1.2.3.4/tcp/1234` into a multiaddr.
+/// Parse strings like `1.2.3.4:1234` and `/ip4/1.2.3.4/udp/1234/quic-v1` into a multiaddr.
+///
+/// Multiaddrs are rejected if their transport is not supported, see [`validate_transport`].
 pub fn parse_peer_addr(addr: &str) -> Result<Multiaddr> {
     // Parse valid IPv4 socket address, e.g. `1.2.3.4:1234`.
     if let Ok(addr) = addr.parse::<std::net::SocketAddrV4>() {
@@ -180,12 +182,75 @@ pub fn parse_peer_addr(addr: &str) -> Result<Multiaddr> {
     // Parse any valid multiaddr string
     if let Ok(addr) = addr.parse::<Multiaddr>() {
         debug!("Parsing a full multiaddr: {:?}", addr);
+        validate_transport(&addr)?;
         return Ok(addr);
     }
 
     Err(Error::InvalidPeerAddr)
 }
 
+/// Check the protocol stack of a peer address is one the node can dial.
+///
+/// The address must be an IP or DNS address followed by `/udp/<port>/quic-v1`, or, with the
+/// `websockets` feature, `/tcp/<port>/ws`. Anything after that must be the peer ID, optionally
+/// through a relay circuit.
+pub fn validate_transport(addr: &Multiaddr) -> std::result::Result<(), UnsupportedTransport> {
+    let mut protocols = addr.iter();
+    let transport_supported = match (protocols.next(), protocols.next(), protocols.next()) {
+        (
+            Some(
+                Protocol::Ip4(_)
+                | Protocol::Ip6(_)
+                | Protocol::Dns(_)
+                | Protocol::Dns4(_)
+                | Protocol::Dns6(_),
+            ),
+            Some(Protocol::Udp(_)),
+            Some(Protocol::QuicV1),
+        ) => true,
+        #[cfg(feature = "websockets")]
+        (
+            Some(
+                Protocol::Ip4(_)
+                | Protocol::Ip6(_)
+                | Protocol::Dns(_)
+                | Protocol::Dns4(_)
+                | Protocol::Dns6(_),
+            ),
+            Some(Protocol::Tcp(_)),
+            Some(Protocol::Ws(_)),
+        ) => true,
+        _ => false,
+    };
+    let peer_id_only =
+        protocols.all(|protocol| matches!(protocol, Protocol::P2p(_) | Protocol::P2pCircuit));
+
+    if transport_supported && peer_id_only {
+        Ok(())
+    } else {
+        Err(UnsupportedTransport(addr.clone()))
+    }
+}
+
+/// Extract the `PeerId`s from a list of multiaddrs, e.g. the ones supplied through `SAFE_PEERS`.
+///
+/// The peer ID is taken from the last `/p2p/<peer_id>` component, so for a relayed address this
+/// is the destination peer rather than the relay. Addresses without a `/p2p/` component are
+/// skipped.
+pub fn peer_ids_from_multiaddrs(addrs: &[Multiaddr]) -> Vec<PeerId> {
+    addrs
+        .iter()
+        .filter_map(|addr| {
+            addr.iter()
+                .filter_map(|protocol| match protocol {
+                    Protocol::P2p(peer_id) => Some(peer_id),
+                    _ => None,
+                })
+                .last()
+        })
+        .collect()
+}
+
 #[cfg(feature = "network-contacts")]
 /// Get bootstrap peers from the Network contacts file stored in the given URL.
 ///
@@ -244,3 +309,57 @@ pub async fn get_bootstrap_peers_from_url(url: Url) -> Result<Vec<Multiaddr>> {
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_ids_from_multiaddrs_should_skip_addresses_without_p2p_component() -> Result<()> {
+        let peer_id_1 = PeerId::random();
+        let peer_id_2 = PeerId::random();
+        let addrs = vec![
+            parse_peer_addr(&format!("/ip4/1.2.3.4/udp/1200/quic-v1/p2p/{peer_id_1}"))?,
+            parse_peer_addr("/ip4/1.2.3.5/udp/1201/quic-v1")?,
+            parse_peer_addr("1.2.3.6:1202")?,
+            parse_peer_addr(&format!(
+                "/dns4/example.com/udp/1203/quic-v1/p2p/{peer_id_2}"
+            ))?,
+        ];
+
+        let peer_ids = peer_ids_from_multiaddrs(&addrs);
+        assert_eq!(peer_ids, vec![peer_id_1, peer_id_2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_peer_addr_should_reject_an_unsupported_transport() -> Result<()> {
+        let peer_id = PeerId::random();
+        assert!(parse_peer_addr(&format!("/ip4/1.2.3.4/udp/1200/quic-v1/p2p/{peer_id}")).is_ok());
+
+        let tcp_addr = format!("/ip4/1.2.3.4/tcp/1200/p2p/{peer_id}");
+        assert!(matches!(
+            parse_peer_addr(&tcp_addr),
+            Err(Error::UnsupportedTransport(UnsupportedTransport(addr))) if addr.to_string() == tcp_addr
+        ));
+        let quic_draft_addr: Multiaddr = "/ip4/1.2.3.4/udp/1200/quic".parse().expect("a multiaddr");
+        assert!(validate_transport(&quic_draft_addr).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn peer_ids_from_multiaddrs_should_use_the_destination_peer_of_a_relayed_address() -> Result<()>
+    {
+        let relay_peer_id = PeerId::random();
+        let peer_id = PeerId::random();
+        let addrs = vec![parse_peer_addr(&format!(
+            "/ip4/1.2.3.4/udp/1200/quic-v1/p2p/{relay_peer_id}/p2p-circuit/p2p/{peer_id}"
+        ))?];
+
+        assert_eq!(peer_ids_from_multiaddrs(&addrs), vec![peer_id]);
+
+        Ok(())
+    }
+}
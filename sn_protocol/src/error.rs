@@ -27,6 +27,12 @@ pub enum Error {
     #[error("Could not obtain data dir")]
     CouldNotObtainDataDir,
 
+    // ---------- Cmd recording errors
+    #[error("Could not record Cmd: {0}")]
+    CmdRecordingFailed(String),
+    #[error("Could not replay Cmd: {0}")]
+    CmdReplayFailed(String),
+
     // ---------- Chunk Proof errors
     #[error("Chunk does not exist {0:?}")]
     ChunkDoesNotExist(NetworkAddress),
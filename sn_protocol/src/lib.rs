@@ -42,6 +42,45 @@ use std::{
 };
 use xor_name::XorName;
 
+/// The maximum number of peers to return in a `GetClosestPeers` response.
+/// This is the group size used in safe network protocol to be responsible for
+/// an item in the network.
+/// The peer should be present among the CLOSE_GROUP_SIZE if we're fetching the close_group(peer)
+/// The size has been set to 5 for improved performance.
+pub const CLOSE_GROUP_SIZE: usize = 5;
+
+/// The count of peers that will be considered as close to a record target,
+/// that a replication of the record shall be sent/accepted to/by the peer.
+pub const REPLICATION_PEERS_COUNT: usize = CLOSE_GROUP_SIZE + 2;
+
+/// Majority of a given group (i.e. > 1/2).
+#[inline]
+pub const fn close_group_majority() -> usize {
+    // Calculate the majority of the close group size by dividing it by 2 and adding 1.
+    // This ensures that the majority is always greater than half.
+    CLOSE_GROUP_SIZE / 2 + 1
+}
+
+/// How many copies of the data a client wants the close group to be paid to hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedundancyLevel {
+    /// A single node is paid.
+    One,
+    /// A majority of the close group is paid.
+    Majority,
+    /// Every node in the close group is paid.
+    All,
+}
+
+/// The number of distinct payees a client must pay to store data at the given redundancy level.
+pub fn required_payees(close_group_size: usize, redundancy: RedundancyLevel) -> usize {
+    match redundancy {
+        RedundancyLevel::One => close_group_size.min(1),
+        RedundancyLevel::Majority => close_group_size / 2 + 1,
+        RedundancyLevel::All => close_group_size,
+    }
+}
+
 /// Returns the UDP port from the provided MultiAddr.
 pub fn get_port_from_multiaddr(multi_addr: &Multiaddr) -> Option<u16> {
     // assuming the listening addr contains /ip4/127.0.0.1/udp/56215/quic-v1/p2p/<peer_id>
@@ -366,7 +405,10 @@ impl<'a> std::fmt::Debug for PrettyPrintRecordKey<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{NetworkAddress, PrettyPrintRecordKey};
+    use crate::{
+        close_group_majority, required_payees, NetworkAddress, PrettyPrintRecordKey,
+        RedundancyLevel, CLOSE_GROUP_SIZE, REPLICATION_PEERS_COUNT,
+    };
     use bls::rand::thread_rng;
     use bytes::Bytes;
     use libp2p::kad::{KBucketKey, RecordKey};
@@ -433,4 +475,24 @@ mod tests {
 
         assert!(net_addr_fmt.contains(spend_addr_hex));
     }
+
+    #[test]
+    fn close_group_derived_sizes_should_follow_the_close_group_size() {
+        assert_eq!(close_group_majority(), CLOSE_GROUP_SIZE / 2 + 1);
+        assert!(close_group_majority() * 2 > CLOSE_GROUP_SIZE);
+        assert!(close_group_majority() <= CLOSE_GROUP_SIZE);
+        assert_eq!(REPLICATION_PEERS_COUNT, CLOSE_GROUP_SIZE + 2);
+    }
+
+    #[test]
+    fn required_payees_should_follow_the_redundancy_level() {
+        assert_eq!(required_payees(5, RedundancyLevel::One), 1);
+        assert_eq!(required_payees(5, RedundancyLevel::Majority), 3);
+        assert_eq!(required_payees(8, RedundancyLevel::Majority), 5);
+        assert_eq!(required_payees(5, RedundancyLevel::All), 5);
+        assert_eq!(
+            required_payees(CLOSE_GROUP_SIZE, RedundancyLevel::Majority),
+            close_group_majority()
+        );
+    }
 }
@@ -9,6 +9,7 @@
 //! Data messages and their possible responses.
 mod chunk_proof;
 mod cmd;
+mod cmd_recorder;
 mod node_id;
 mod query;
 mod register;
@@ -16,7 +17,8 @@ mod response;
 
 pub use self::{
     chunk_proof::{ChunkProof, Nonce},
-    cmd::{Cmd, Hash},
+    cmd::{AuditRecord, Cmd, CmdLabels, CmdPriority, CmdSizeBucket, Hash},
+    cmd_recorder::{CmdRecorder, CmdReplayer},
     node_id::NodeId,
     query::Query,
     register::RegisterCmd,
@@ -9,6 +9,9 @@
 
 use crate::{storage::RecordType, NetworkAddress};
 use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use tiny_keccak::{Hasher, Sha3};
+use xor_name::XorName;
 // TODO: remove this dependency and define these types herein.
 pub use sn_transfers::{Hash, PaymentQuote};
 
@@ -43,6 +46,91 @@ pub enum Cmd {
     },
 }
 
+/// The relative priority of a [`Cmd`] when a node is busy processing them.
+///
+/// Variants are ordered from lowest to highest priority.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum CmdPriority {
+    /// Bulk data movement, such as replication.
+    Low,
+    /// Network health reporting.
+    Medium,
+    /// Payment-critical operations.
+    High,
+}
+
+/// The low-cardinality labels of a [`Cmd`], for use in metrics.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct CmdLabels {
+    /// The name of the cmd variant.
+    pub kind: &'static str,
+    /// The size of the encoded cmd, bucketed.
+    pub size: CmdSizeBucket,
+    /// Whether the cmd carries payment quotes.
+    pub carries_quotes: bool,
+}
+
+/// A coarse bucket for the encoded size of a [`Cmd`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum CmdSizeBucket {
+    /// Less than 1KB.
+    Small,
+    /// From 1KB to 100KB.
+    Medium,
+    /// More than 100KB.
+    Large,
+}
+
+impl CmdSizeBucket {
+    /// The bucket for a size in bytes.
+    pub fn from_size(size: usize) -> Self {
+        if size < 1024 {
+            Self::Small
+        } else if size <= 100 * 1024 {
+            Self::Medium
+        } else {
+            Self::Large
+        }
+    }
+
+    /// The label value for the bucket.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Small => "<1KB",
+            Self::Medium => "1-100KB",
+            Self::Large => ">100KB",
+        }
+    }
+}
+
+/// A metadata-only record of a [`Cmd`] processed by a node, for an append-only audit log.
+///
+/// The payload of the cmd (replication keys, quotes, behaviour descriptions) is not kept, only its
+/// encoded size.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// The name of the cmd variant.
+    pub kind: String,
+    /// The destination of the cmd.
+    ///
+    /// Addresses that do not represent an `XorName`, such as peer ids, are hashed into one.
+    pub target: XorName,
+    /// The size of the encoded cmd in bytes.
+    pub size: usize,
+    /// When the record was created.
+    pub timestamp: SystemTime,
+}
+
+impl AuditRecord {
+    /// Serialize the record as a single line of JSON, terminated by a newline, ready to be
+    /// appended to a JSONL audit log.
+    pub fn to_jsonl_line(&self) -> serde_json::Result<String> {
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        Ok(line)
+    }
+}
+
 impl std::fmt::Debug for Cmd {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -82,6 +170,75 @@ impl Cmd {
             Cmd::PeerConsideredAsBad { bad_peer, .. } => bad_peer.clone(),
         }
     }
+
+    /// Create an audit record of the cmd, capturing its kind, target and size but not its payload.
+    pub fn audit_record(&self) -> AuditRecord {
+        let dst = self.dst();
+        let target = dst
+            .as_xorname()
+            .unwrap_or_else(|| XorName::from_content(&dst.as_bytes()));
+
+        AuditRecord {
+            kind: self.kind().to_string(),
+            target,
+            size: self.encoded_size(),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// A bounded set of labels describing the cmd, safe to use as metric labels.
+    pub fn metric_labels(&self) -> CmdLabels {
+        CmdLabels {
+            kind: self.kind(),
+            size: CmdSizeBucket::from_size(self.encoded_size()),
+            carries_quotes: matches!(self, Cmd::QuoteVerification { quotes, .. } if !quotes.is_empty()),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Cmd::Replicate { .. } => "Replicate",
+            Cmd::QuoteVerification { .. } => "QuoteVerification",
+            Cmd::PeerConsideredAsBad { .. } => "PeerConsideredAsBad",
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        rmp_serde::to_vec(self)
+            .map(|bytes| bytes.len())
+            .unwrap_or_default()
+    }
+
+    /// A hash of the cmd that is the same for every copy of the same logical cmd, for use as an
+    /// idempotency key.
+    ///
+    /// The order of the replication keys and of the quotes carries no meaning, so they are sorted
+    /// before the cmd is serialized and hashed.
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        let mut canonical = self.clone();
+        match &mut canonical {
+            Cmd::Replicate { keys, .. } => keys.sort(),
+            Cmd::QuoteVerification { quotes, .. } => quotes.sort(),
+            Cmd::PeerConsideredAsBad { .. } => {}
+        }
+        let bytes = rmp_serde::to_vec(&canonical).unwrap_or_default();
+
+        let mut sha3 = Sha3::v256();
+        let mut output = [0; 32];
+        sha3.update(&bytes);
+        sha3.finalize(&mut output);
+        output
+    }
+
+    /// The priority of the cmd, so payment-critical work can be handled ahead of bulk replication
+    /// when the node is under load.
+    pub fn priority(&self) -> CmdPriority {
+        match self {
+            Cmd::QuoteVerification { .. } => CmdPriority::High,
+            Cmd::PeerConsideredAsBad { .. } => CmdPriority::Medium,
+            Cmd::Replicate { .. } => CmdPriority::Low,
+        }
+    }
 }
 
 impl std::fmt::Display for Cmd {
@@ -114,3 +271,164 @@ impl std::fmt::Display for Cmd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn payment_cmds_should_have_priority_over_replication() {
+        let address = NetworkAddress::from_peer(PeerId::random());
+        let quote_verification = Cmd::QuoteVerification {
+            target: address.clone(),
+            quotes: vec![],
+        };
+        let peer_considered_as_bad = Cmd::PeerConsideredAsBad {
+            detected_by: address.clone(),
+            bad_peer: address.clone(),
+            bad_behaviour: "test".to_string(),
+        };
+        let replicate = Cmd::Replicate {
+            holder: address,
+            keys: vec![],
+        };
+
+        assert!(quote_verification.priority() > peer_considered_as_bad.priority());
+        assert!(peer_considered_as_bad.priority() > replicate.priority());
+        assert_eq!(replicate.priority(), CmdPriority::Low);
+    }
+
+    #[test]
+    fn canonical_hash_should_not_depend_on_the_order_of_keys_or_quotes() {
+        let holder = NetworkAddress::from_peer(PeerId::random());
+        let keys = vec![
+            (
+                NetworkAddress::from_peer(PeerId::random()),
+                RecordType::Chunk,
+            ),
+            (
+                NetworkAddress::from_peer(PeerId::random()),
+                RecordType::NonChunk(XorName::from_content(b"spend")),
+            ),
+            (
+                NetworkAddress::from_peer(PeerId::random()),
+                RecordType::Chunk,
+            ),
+        ];
+        let mut reversed_keys = keys.clone();
+        reversed_keys.reverse();
+
+        let replicate = Cmd::Replicate {
+            holder: holder.clone(),
+            keys: keys.clone(),
+        };
+        let reordered = Cmd::Replicate {
+            holder: holder.clone(),
+            keys: reversed_keys,
+        };
+        assert_eq!(replicate.canonical_hash(), reordered.canonical_hash());
+
+        let quotes = vec![
+            (
+                NetworkAddress::from_peer(PeerId::random()),
+                PaymentQuote::zero(),
+            ),
+            (holder.clone(), PaymentQuote::zero()),
+        ];
+        let quote_verification = Cmd::QuoteVerification {
+            target: holder.clone(),
+            quotes: quotes.clone(),
+        };
+        let reordered = Cmd::QuoteVerification {
+            target: holder.clone(),
+            quotes: quotes.into_iter().rev().collect(),
+        };
+        assert_eq!(
+            quote_verification.canonical_hash(),
+            reordered.canonical_hash()
+        );
+
+        let fewer_keys = Cmd::Replicate {
+            holder,
+            keys: keys[1..].to_vec(),
+        };
+        assert_ne!(replicate.canonical_hash(), fewer_keys.canonical_hash());
+        assert_ne!(
+            replicate.canonical_hash(),
+            quote_verification.canonical_hash()
+        );
+    }
+
+    #[test]
+    fn metric_labels_should_bucket_small_and_large_cmds_separately() {
+        let holder = NetworkAddress::from_peer(PeerId::random());
+        let keys = |count: usize| {
+            (0..count)
+                .map(|_| {
+                    (
+                        NetworkAddress::from_peer(PeerId::random()),
+                        RecordType::Chunk,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let small = Cmd::Replicate {
+            holder: holder.clone(),
+            keys: keys(1),
+        };
+        let large = Cmd::Replicate {
+            holder,
+            keys: keys(5000),
+        };
+
+        let small_labels = small.metric_labels();
+        let large_labels = large.metric_labels();
+        assert_eq!(small_labels.kind, "Replicate");
+        assert_eq!(small_labels.size, CmdSizeBucket::Small);
+        assert_eq!(large_labels.size, CmdSizeBucket::Large);
+        assert!(!small_labels.carries_quotes);
+    }
+
+    #[test]
+    fn audit_record_should_keep_the_kind_and_target_but_not_the_payload() -> serde_json::Result<()>
+    {
+        let peer = NetworkAddress::from_peer(PeerId::random());
+        let peer_target = XorName::from_content(&peer.as_bytes());
+        let chunk_name = XorName::from_content(b"chunk");
+        let chunk = NetworkAddress::ChunkAddress(crate::storage::ChunkAddress::new(chunk_name));
+
+        let replicate = Cmd::Replicate {
+            holder: peer.clone(),
+            keys: vec![(chunk.clone(), RecordType::Chunk)],
+        };
+        let quote_verification = Cmd::QuoteVerification {
+            target: chunk,
+            quotes: vec![(peer.clone(), PaymentQuote::zero())],
+        };
+        let peer_considered_as_bad = Cmd::PeerConsideredAsBad {
+            detected_by: NetworkAddress::from_peer(PeerId::random()),
+            bad_peer: peer,
+            bad_behaviour: "sensitive behaviour description".to_string(),
+        };
+
+        for (cmd, kind, target) in [
+            (replicate, "Replicate", peer_target),
+            (quote_verification, "QuoteVerification", chunk_name),
+            (peer_considered_as_bad, "PeerConsideredAsBad", peer_target),
+        ] {
+            let record = cmd.audit_record();
+            assert_eq!(record.kind, kind);
+            assert_eq!(record.target, target);
+            assert!(record.size > 0);
+
+            let line = record.to_jsonl_line()?;
+            assert!(line.ends_with('\n'));
+            assert_eq!(line.matches('\n').count(), 1);
+            assert!(!line.contains("sensitive behaviour description"));
+            assert_eq!(serde_json::from_str::<AuditRecord>(&line)?, record);
+        }
+
+        Ok(())
+    }
+}
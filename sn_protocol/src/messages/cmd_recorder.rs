@@ -0,0 +1,177 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Cmd;
+use crate::error::{Error, Result};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, ErrorKind, Read, Write},
+    path::Path,
+};
+
+/// Records the `Cmd`s received by a node, so they can be replayed later with a `CmdReplayer`.
+///
+/// Each record is the length of the serialised `Cmd`, as a big-endian `u32`, followed by the
+/// serialised `Cmd` itself.
+pub struct CmdRecorder<W: Write = File> {
+    writer: W,
+}
+
+impl CmdRecorder {
+    /// Open a recording file, appending to it if it already exists.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| Error::CmdRecordingFailed(err.to_string()))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<W: Write> CmdRecorder<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn record(&mut self, cmd: &Cmd) -> Result<()> {
+        let bytes =
+            rmp_serde::to_vec(cmd).map_err(|err| Error::CmdRecordingFailed(err.to_string()))?;
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| Error::CmdRecordingFailed("the Cmd is too large to record".to_string()))?;
+
+        // Write the whole record in one go, so an interrupted write can only ever leave a
+        // truncated record at the end of the file.
+        let mut record = Vec::with_capacity(4 + bytes.len());
+        record.extend_from_slice(&len.to_be_bytes());
+        record.extend_from_slice(&bytes);
+        self.writer
+            .write_all(&record)
+            .and_then(|_| self.writer.flush())
+            .map_err(|err| Error::CmdRecordingFailed(err.to_string()))
+    }
+}
+
+/// Yields the `Cmd`s written by a `CmdRecorder`, in the order they were recorded.
+///
+/// A truncated record at the end of the recording, e.g., from a node that was killed mid-write,
+/// is skipped and ends the replay.
+pub struct CmdReplayer<R: Read = BufReader<File>> {
+    reader: R,
+    finished: bool,
+}
+
+impl CmdReplayer {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|err| Error::CmdReplayFailed(err.to_string()))?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl<R: Read> CmdReplayer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            finished: false,
+        }
+    }
+
+    fn next_cmd(&mut self) -> Result<Option<Cmd>> {
+        let mut len_bytes = [0u8; 4];
+        if !self.read_record_part(&mut len_bytes)? {
+            return Ok(None);
+        }
+        let mut bytes = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+        if !self.read_record_part(&mut bytes)? {
+            return Ok(None);
+        }
+        let cmd =
+            rmp_serde::from_slice(&bytes).map_err(|err| Error::CmdReplayFailed(err.to_string()))?;
+        Ok(Some(cmd))
+    }
+
+    /// Fill the buffer, returning false if the recording ends before it is full.
+    fn read_record_part(&mut self, buf: &mut [u8]) -> Result<bool> {
+        match self.reader.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(err) => Err(Error::CmdReplayFailed(err.to_string())),
+        }
+    }
+}
+
+impl<R: Read> Iterator for CmdReplayer<R> {
+    type Item = Result<Cmd>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let next = self.next_cmd().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.finished = true;
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::RecordType, NetworkAddress};
+    use libp2p::PeerId;
+
+    fn cmds() -> Vec<Cmd> {
+        let peer = NetworkAddress::from_peer(PeerId::random());
+        vec![
+            Cmd::Replicate {
+                holder: peer.clone(),
+                keys: vec![(
+                    NetworkAddress::from_peer(PeerId::random()),
+                    RecordType::Chunk,
+                )],
+            },
+            Cmd::QuoteVerification {
+                target: peer.clone(),
+                quotes: vec![],
+            },
+            Cmd::Replicate {
+                holder: peer,
+                keys: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn recorded_cmds_should_be_replayed_in_order() -> Result<()> {
+        let cmds = cmds();
+        let mut recorder = CmdRecorder::new(Vec::new());
+        for cmd in &cmds {
+            recorder.record(cmd)?;
+        }
+
+        let replayed = CmdReplayer::new(recorder.writer.as_slice()).collect::<Result<Vec<_>>>()?;
+        assert_eq!(replayed, cmds);
+        Ok(())
+    }
+
+    #[test]
+    fn a_truncated_trailing_record_should_end_the_replay() -> Result<()> {
+        let cmds = cmds();
+        let mut recorder = CmdRecorder::new(Vec::new());
+        for cmd in &cmds {
+            recorder.record(cmd)?;
+        }
+        let mut recording = recorder.writer;
+        recording.truncate(recording.len() - 1);
+
+        let replayed = CmdReplayer::new(recording.as_slice()).collect::<Result<Vec<_>>>()?;
+        assert_eq!(replayed, cmds[..2]);
+        Ok(())
+    }
+}
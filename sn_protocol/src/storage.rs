@@ -16,7 +16,7 @@ use std::{str::FromStr, time::Duration};
 
 pub use self::{
     address::{ChunkAddress, RegisterAddress, SpendAddress},
-    chunks::Chunk,
+    chunks::{distinct_close_groups, membership_delta, responsible_chunks, Chunk, MembershipDelta},
     header::{try_deserialize_record, try_serialize_record, RecordHeader, RecordKind, RecordType},
 };
 
@@ -27,6 +27,8 @@ pub use self::{
 /// The Duration/Attempts is chosen based on the internal logic.
 #[derive(Clone, Debug, Copy)]
 pub enum RetryStrategy {
+    /// None: Makes a single attempt, for callers that retry the operation themselves.
+    None,
     /// Quick: Resolves to a 15-second wait or 1 retry attempt.
     Quick,
     /// Balanced: Resolves to a 60-second wait or 3 retry attempt.
@@ -38,6 +40,7 @@ pub enum RetryStrategy {
 impl RetryStrategy {
     pub fn get_duration(&self) -> Duration {
         match self {
+            RetryStrategy::None => Duration::ZERO,
             RetryStrategy::Quick => Duration::from_secs(15),
             RetryStrategy::Balanced => Duration::from_secs(60),
             RetryStrategy::Persistent => Duration::from_secs(180),
@@ -46,6 +49,7 @@ impl RetryStrategy {
 
     pub fn get_count(&self) -> usize {
         match self {
+            RetryStrategy::None => 0,
             RetryStrategy::Quick => 1,
             RetryStrategy::Balanced => 3,
             RetryStrategy::Persistent => 6,
@@ -58,6 +62,7 @@ impl FromStr for RetryStrategy {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "none" => Ok(RetryStrategy::None),
             "quick" => Ok(RetryStrategy::Quick),
             "balanced" => Ok(RetryStrategy::Balanced),
             "persistent" => Ok(RetryStrategy::Persistent),
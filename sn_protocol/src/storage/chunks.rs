@@ -7,9 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::ChunkAddress;
-use crate::NetworkAddress;
+use crate::{NetworkAddress, CLOSE_GROUP_SIZE};
 use bytes::Bytes;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeSet;
 
 use xor_name::XorName;
 
@@ -53,6 +54,14 @@ impl Chunk {
         self.address.xorname()
     }
 
+    /// Returns true if the name matches the hash of the contained value.
+    ///
+    /// The address is a public field, so a `Chunk` built by hand may claim a name that its
+    /// content doesn't hash to.
+    pub fn verify_name(&self) -> bool {
+        XorName::from_content(self.value.as_ref()) == *self.name()
+    }
+
     /// Returns size of contained value.
     pub fn payload_size(&self) -> usize {
         self.value.len()
@@ -64,6 +73,89 @@ impl Chunk {
     }
 }
 
+/// Returns the chunks `node` is responsible for, i.e. those it would be among the
+/// `CLOSE_GROUP_SIZE` closest nodes to, given the `peers` it knows of.
+///
+/// A node can use this to decide which chunks of a batch to store and which to forward.
+pub fn responsible_chunks<'a>(
+    node: &NetworkAddress,
+    chunks: &'a [Chunk],
+    peers: &[NetworkAddress],
+) -> Vec<&'a Chunk> {
+    chunks
+        .iter()
+        .filter(|chunk| is_in_close_group(node, &chunk.network_address(), peers))
+        .collect()
+}
+
+/// The addresses a node became, or stopped being, responsible for between two snapshots of the
+/// peers it knows of.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MembershipDelta {
+    /// Addresses the node is now responsible for, which need to be replicated to it.
+    pub gained: BTreeSet<NetworkAddress>,
+    /// Addresses the node is no longer responsible for.
+    pub lost: BTreeSet<NetworkAddress>,
+}
+
+/// Compare the `node`'s responsibility for each of the `addresses` under the `old_peers` and the
+/// `new_peers`, e.g., before and after the routing table churned.
+pub fn membership_delta(
+    node: &NetworkAddress,
+    old_peers: &[NetworkAddress],
+    new_peers: &[NetworkAddress],
+    addresses: &[NetworkAddress],
+) -> MembershipDelta {
+    let mut delta = MembershipDelta::default();
+    for address in addresses {
+        match (
+            is_in_close_group(node, address, old_peers),
+            is_in_close_group(node, address, new_peers),
+        ) {
+            (false, true) => {
+                let _ = delta.gained.insert(address.clone());
+            }
+            (true, false) => {
+                let _ = delta.lost.insert(address.clone());
+            }
+            _ => {}
+        }
+    }
+    delta
+}
+
+/// The number of distinct close groups, out of the `peers` known, that store the chunks.
+///
+/// Each chunk is stored by the `CLOSE_GROUP_SIZE` peers closest to its address, so this is how
+/// many different sets of peers an upload of the chunks has to reach.
+pub fn distinct_close_groups(chunk_addrs: &[ChunkAddress], peers: &[NetworkAddress]) -> usize {
+    chunk_addrs
+        .iter()
+        .map(|chunk_addr| {
+            let target = NetworkAddress::ChunkAddress(*chunk_addr);
+            let mut close_group = peers.iter().collect::<Vec<_>>();
+            close_group.sort_by_key(|peer| peer.distance(&target));
+            close_group.truncate(CLOSE_GROUP_SIZE);
+            close_group.into_iter().collect::<BTreeSet<_>>()
+        })
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+/// Whether `node` is among the `CLOSE_GROUP_SIZE` closest to `target`, given the `peers` it knows.
+fn is_in_close_group(
+    node: &NetworkAddress,
+    target: &NetworkAddress,
+    peers: &[NetworkAddress],
+) -> bool {
+    let node_distance = node.distance(target);
+    let closer_peers = peers
+        .iter()
+        .filter(|peer| *peer != node && peer.distance(target) < node_distance)
+        .count();
+    closer_peers < CLOSE_GROUP_SIZE
+}
+
 impl Serialize for Chunk {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
         // Address is omitted since it's derived from value
@@ -77,3 +169,129 @@ impl<'de> Deserialize<'de> for Chunk {
         Ok(Self::new(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    fn random_chunks(count: usize) -> Vec<Chunk> {
+        (0..count)
+            .map(|i| Chunk::new(Bytes::from(format!("chunk {i}"))))
+            .collect()
+    }
+
+    #[test]
+    fn verify_name_should_reject_a_chunk_whose_name_does_not_match_its_content() {
+        let chunk = Chunk::new(Bytes::from("some content"));
+        assert!(chunk.verify_name());
+
+        let tampered = Chunk {
+            address: chunk.address,
+            value: Bytes::from("other content"),
+        };
+        assert!(!tampered.verify_name());
+    }
+
+    #[test]
+    fn responsible_chunks_should_return_the_chunks_the_node_is_in_the_close_group_of() {
+        let node = NetworkAddress::from_peer(PeerId::random());
+        let peers = (0..20)
+            .map(|_| NetworkAddress::from_peer(PeerId::random()))
+            .collect::<Vec<_>>();
+        let chunks = random_chunks(50);
+
+        let expected = chunks
+            .iter()
+            .filter(|chunk| {
+                let target = chunk.network_address();
+                let mut close_group = peers.iter().chain([&node]).collect::<Vec<_>>();
+                close_group.sort_by_key(|address| address.distance(&target));
+                close_group.truncate(CLOSE_GROUP_SIZE);
+                close_group.contains(&&node)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(responsible_chunks(&node, &chunks, &peers), expected);
+    }
+
+    #[test]
+    fn membership_delta_should_report_the_addresses_gained_and_lost_after_churn() {
+        let node = NetworkAddress::from_peer(PeerId::random());
+        let old_peers = (0..20)
+            .map(|_| NetworkAddress::from_peer(PeerId::random()))
+            .collect::<Vec<_>>();
+        // Half the old peers leave and new ones join.
+        let new_peers = old_peers[..10]
+            .iter()
+            .cloned()
+            .chain((0..10).map(|_| NetworkAddress::from_peer(PeerId::random())))
+            .collect::<Vec<_>>();
+        let addresses = random_chunks(100)
+            .iter()
+            .map(|chunk| chunk.network_address())
+            .collect::<Vec<_>>();
+
+        let responsible = |peers: &[NetworkAddress]| {
+            addresses
+                .iter()
+                .filter(|target| {
+                    let mut close_group = peers.iter().chain([&node]).collect::<Vec<_>>();
+                    close_group.sort_by_key(|address| address.distance(target));
+                    close_group.truncate(CLOSE_GROUP_SIZE);
+                    close_group.contains(&&node)
+                })
+                .cloned()
+                .collect::<BTreeSet<_>>()
+        };
+        let before = responsible(&old_peers);
+        let after = responsible(&new_peers);
+
+        let delta = membership_delta(&node, &old_peers, &new_peers, &addresses);
+        assert_eq!(delta.gained, &after - &before);
+        assert_eq!(delta.lost, &before - &after);
+        assert_eq!(
+            membership_delta(&node, &old_peers, &old_peers, &addresses),
+            MembershipDelta::default()
+        );
+    }
+
+    #[test]
+    fn distinct_close_groups_should_count_each_group_of_peers_once() {
+        // With one more peer than the close group size, a chunk's close group is every peer bar
+        // the one furthest from it, so chunks with different furthest peers are in two groups.
+        let peers = (0..=CLOSE_GROUP_SIZE)
+            .map(|_| NetworkAddress::from_peer(PeerId::random()))
+            .collect::<Vec<_>>();
+        let furthest_peer = |chunk_addr: &ChunkAddress| {
+            let target = NetworkAddress::ChunkAddress(*chunk_addr);
+            peers
+                .iter()
+                .max_by_key(|peer| peer.distance(&target))
+                .cloned()
+        };
+        let chunk_addr = |i: u64| ChunkAddress::new(XorName::from_content(&i.to_le_bytes()));
+
+        let first = chunk_addr(0);
+        let second = (1..)
+            .map(chunk_addr)
+            .find(|chunk_addr| furthest_peer(chunk_addr) != furthest_peer(&first))
+            .expect("a chunk with another furthest peer");
+
+        assert_eq!(distinct_close_groups(&[first, second, first], &peers), 2);
+        assert_eq!(distinct_close_groups(&[first, first], &peers), 1);
+        assert_eq!(distinct_close_groups(&[], &peers), 0);
+    }
+
+    #[test]
+    fn responsible_chunks_should_return_every_chunk_when_there_are_fewer_peers_than_the_close_group(
+    ) {
+        let node = NetworkAddress::from_peer(PeerId::random());
+        let peers = (0..CLOSE_GROUP_SIZE - 1)
+            .map(|_| NetworkAddress::from_peer(PeerId::random()))
+            .collect::<Vec<_>>();
+        let chunks = random_chunks(10);
+
+        assert_eq!(responsible_chunks(&node, &chunks, &peers).len(), 10);
+    }
+}
@@ -18,7 +18,7 @@ use xor_name::XorName;
 /// Indicates the type of the record content.
 /// Note for `Spend` and `Register`, using its content_hash (in `XorName` format)
 /// to indicate different content body.
-#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum RecordType {
     Chunk,
     NonChunk(XorName),
@@ -12,10 +12,13 @@ use crate::{
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use service_manager::ServiceInstallCtx;
+use sn_transfers::NanoTokens;
 use std::{ffi::OsString, path::PathBuf};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FaucetServiceData {
+    #[serde(default)]
+    pub dispense_amount: Option<NanoTokens>,
     pub faucet_path: PathBuf,
     pub local: bool,
     pub log_dir_path: PathBuf,
@@ -67,6 +70,10 @@ impl<'a> ServiceStateActions for FaucetService<'a> {
         }
 
         args.push(OsString::from("server"));
+        if let Some(dispense_amount) = self.service_data.dispense_amount {
+            args.push(OsString::from("--amount"));
+            args.push(OsString::from(dispense_amount.to_string()));
+        }
 
         Ok(ServiceInstallCtx {
             args,
@@ -13,6 +13,7 @@ pub mod error;
 pub mod faucet;
 pub mod node;
 pub mod rpc;
+pub mod unit;
 
 #[macro_use]
 extern crate tracing;
@@ -100,6 +101,18 @@ pub struct StatusSummary {
     pub faucet: Option<FaucetServiceData>,
 }
 
+/// The plan of a multi-node add operation that has not yet completed.
+///
+/// It is recorded before any services are installed and cleared once all of them have been, so
+/// an interrupted operation can be resumed where it left off. Starting any other add discards it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingNodeAdd {
+    /// The number of nodes requested by the operation.
+    pub count: u16,
+    /// The numbers of the nodes the operation adds, in the order they are added.
+    pub node_numbers: Vec<u16>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeRegistry {
     pub auditor: Option<AuditorServiceData>,
@@ -109,6 +122,8 @@ pub struct NodeRegistry {
     pub faucet: Option<FaucetServiceData>,
     pub nat_status: Option<NatDetectionStatus>,
     pub nodes: Vec<NodeServiceData>,
+    #[serde(default)]
+    pub pending_node_add: Option<PendingNodeAdd>,
     pub save_path: PathBuf,
 }
 
@@ -142,6 +157,7 @@ impl NodeRegistry {
                 faucet: None,
                 nat_status: None,
                 nodes: vec![],
+                pending_node_add: None,
                 save_path: path.to_path_buf(),
             });
         }
@@ -165,6 +181,7 @@ impl NodeRegistry {
                 faucet: None,
                 nat_status: None,
                 nodes: vec![],
+                pending_node_add: None,
                 save_path: path.to_path_buf(),
             });
         }
@@ -6,8 +6,17 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{error::Result, rpc::RpcActions, ServiceStateActions, ServiceStatus, UpgradeOptions};
+use crate::{
+    error::Result,
+    rpc::RpcActions,
+    unit::{
+        add_unit_directives, cpu_quota_directive, env_file_directive, memory_max_directive,
+        ByteSize,
+    },
+    ServiceStateActions, ServiceStatus, UpgradeOptions,
+};
 use async_trait::async_trait;
+use chrono::NaiveTime;
 use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
 use service_manager::{ServiceInstallCtx, ServiceLabel};
@@ -16,6 +25,26 @@ use sn_protocol::get_port_from_multiaddr;
 use sn_transfers::NanoTokens;
 use std::{ffi::OsString, net::SocketAddr, path::PathBuf, str::FromStr};
 
+/// The name of the directory, under a node's data directory, an ephemeral node uses as its root.
+pub const EPHEMERAL_ROOT_DIR_NAME: &str = "ephemeral";
+
+/// Set the node's log levels as its `SN_LOG` variable, which safenode reads them from. The log
+/// level replaces any `SN_LOG` value given in the environment variables.
+pub fn with_log_level(
+    env_variables: Option<Vec<(String, String)>>,
+    log_level: Option<String>,
+) -> Option<Vec<(String, String)>> {
+    match log_level {
+        Some(log_level) => {
+            let mut env_variables = env_variables.unwrap_or_default();
+            env_variables.retain(|(key, _)| key != "SN_LOG");
+            env_variables.push(("SN_LOG".to_string(), log_level));
+            Some(env_variables)
+        }
+        None => env_variables,
+    }
+}
+
 pub struct NodeService<'a> {
     pub service_data: &'a mut NodeServiceData,
     pub rpc_actions: Box<dyn RpcActions + Send>,
@@ -47,7 +76,7 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             OsString::from("--root-dir"),
             OsString::from(
                 self.service_data
-                    .data_dir_path
+                    .root_dir_path()
                     .to_string_lossy()
                     .to_string(),
             ),
@@ -80,6 +109,20 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             args.push(OsString::from("--metrics-server-port"));
             args.push(OsString::from(metrics_port.to_string()));
         }
+        if let Some(health_port) = self.service_data.health_port {
+            args.push(OsString::from("--health-port"));
+            args.push(OsString::from(health_port.to_string()));
+        }
+        if let Some(min_connections) = self.service_data.min_connections {
+            args.push(OsString::from("--min-connections"));
+            args.push(OsString::from(min_connections.to_string()));
+        }
+        if let Some((start, end)) = self.service_data.maintenance_window {
+            args.push(OsString::from("--maintenance-start"));
+            args.push(OsString::from(start.format("%H:%M").to_string()));
+            args.push(OsString::from("--maintenance-end"));
+            args.push(OsString::from(end.format("%H:%M").to_string()));
+        }
 
         if let Some(owner) = &self.service_data.owner {
             args.push(OsString::from("--owner"));
@@ -97,16 +140,28 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             args.push(OsString::from(peers_str));
         }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: options.auto_restart,
             contents: None,
-            environment: options.env_variables,
+            environment: with_log_level(options.env_variables, self.service_data.log_level.clone()),
             label: label.clone(),
             program: self.service_data.safenode_path.to_path_buf(),
             username: self.service_data.user.clone(),
             working_directory: None,
-        })
+        };
+        let mut directives = vec![];
+        if let Some(env_file) = &self.service_data.env_file {
+            directives.push(env_file_directive(env_file));
+        }
+        if let Some(cpu_quota) = self.service_data.cpu_quota {
+            directives.push(cpu_quota_directive(cpu_quota));
+        }
+        if let Some(memory_max) = self.service_data.memory_max {
+            directives.push(memory_max_directive(memory_max));
+        }
+        add_unit_directives(&mut install_ctx, &directives, self.is_user_mode());
+        Ok(install_ctx)
     }
 
     fn data_dir_path(&self) -> PathBuf {
@@ -130,6 +185,12 @@ impl<'a> ServiceStateActions for NodeService<'a> {
     }
 
     fn on_remove(&mut self) {
+        if let Err(err) = self.service_data.wipe_ephemeral_root_dir() {
+            warn!(
+                "Failed to wipe the ephemeral root dir of {}: {err}",
+                self.service_data.service_name
+            );
+        }
         self.service_data.status = ServiceStatus::Removed;
     }
 
@@ -174,6 +235,7 @@ impl<'a> ServiceStateActions for NodeService<'a> {
     }
 
     async fn on_stop(&mut self) -> Result<()> {
+        self.service_data.wipe_ephemeral_root_dir()?;
         self.service_data.pid = None;
         self.service_data.status = ServiceStatus::Stopped;
         self.service_data.connected_peers = None;
@@ -202,15 +264,40 @@ pub struct NodeServiceData {
         deserialize_with = "deserialize_connected_peers"
     )]
     pub connected_peers: Option<Vec<PeerId>>,
+    /// The CPU quota the service's unit caps the node to, as a number of CPUs.
+    #[serde(default)]
+    pub cpu_quota: Option<f32>,
     pub data_dir_path: PathBuf,
+    /// An environment file the service loads, which must be kept when it is reinstalled.
+    #[serde(default)]
+    pub env_file: Option<PathBuf>,
+    /// The node does not persist its data: its root directory is wiped when it stops.
+    #[serde(default)]
+    pub ephemeral: bool,
     pub genesis: bool,
+    /// The port of the node's minimal health endpoint.
+    #[serde(default)]
+    pub health_port: Option<u16>,
     pub home_network: bool,
     pub listen_addr: Option<Vec<Multiaddr>>,
     pub local: bool,
     pub log_dir_path: PathBuf,
     pub log_format: Option<LogFormat>,
+    /// The node's per-module log levels, which are set as its `SN_LOG` variable.
+    #[serde(default)]
+    pub log_level: Option<String>,
+    /// The daily window, as start and end times, during which the node may restart itself or pause
+    /// replication.
+    #[serde(default)]
+    pub maintenance_window: Option<(NaiveTime, NaiveTime)>,
+    /// The memory the service's unit caps the node to.
+    #[serde(default)]
+    pub memory_max: Option<ByteSize>,
     #[serde(default)]
     pub metrics_port: Option<u16>,
+    /// The number of connections the node tries to keep.
+    #[serde(default)]
+    pub min_connections: Option<usize>,
     #[serde(default)]
     pub owner: Option<String>,
     #[serde(default)]
@@ -296,6 +383,38 @@ where
 }
 
 impl NodeServiceData {
+    /// The directory the node keeps its keys, wallet and chunk store in.
+    ///
+    /// For an ephemeral node this is a directory of its own under the data directory, so it can be
+    /// wiped without touching anything else the service keeps there.
+    pub fn root_dir_path(&self) -> PathBuf {
+        if self.ephemeral {
+            self.data_dir_path.join(EPHEMERAL_ROOT_DIR_NAME)
+        } else {
+            self.data_dir_path.clone()
+        }
+    }
+
+    /// Remove the contents of an ephemeral node's root directory, if it exists. The directory
+    /// itself is kept, so the owner and permissions it was created with still apply on the next
+    /// start. Nothing is removed for a node that persists its data.
+    pub fn wipe_ephemeral_root_dir(&self) -> Result<()> {
+        let root_dir = self.root_dir_path();
+        if !self.ephemeral || !root_dir.exists() {
+            return Ok(());
+        }
+        debug!("Wiping the ephemeral root dir at {root_dir:?}");
+        for entry in std::fs::read_dir(&root_dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                std::fs::remove_dir_all(path)?;
+            } else {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the UDP port from our node's listen address.
     pub fn get_safenode_port(&self) -> Option<u16> {
         // assuming the listening addr contains /ip4/127.0.0.1/udp/56215/quic-v1/p2p/<peer_id>
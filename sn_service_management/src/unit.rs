@@ -0,0 +1,108 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! The systemd units of services that need more than `service-manager` generates for them.
+//!
+//! These are shared by adding a service and reinstalling it, e.g., on an upgrade, so a service
+//! keeps the same unit for its whole life.
+
+use serde::{Deserialize, Serialize};
+use service_manager::ServiceInstallCtx;
+use std::{fmt, path::Path};
+
+/// An amount of memory, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteSize(pub u64);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Cap the service's CPU time, as a number of CPUs, e.g., `1.5` for one and a half.
+pub fn cpu_quota_directive(cpu_quota: f32) -> String {
+    format!("CPUQuota={:.0}%", cpu_quota * 100.0)
+}
+
+pub fn memory_max_directive(memory_max: ByteSize) -> String {
+    format!("MemoryMax={memory_max}")
+}
+
+pub fn env_file_directive(env_file: &Path) -> String {
+    format!(
+        "EnvironmentFile={}",
+        systemd_quote(&env_file.to_string_lossy())
+    )
+}
+
+/// On Linux, write the service's systemd unit into the context's contents with the `directives`
+/// added. Without any directives, the unit is left for `service-manager` to generate.
+pub fn add_unit_directives(ctx: &mut ServiceInstallCtx, directives: &[String], user_mode: bool) {
+    if cfg!(target_os = "linux") && !directives.is_empty() {
+        ctx.contents = Some(systemd_unit_contents(ctx, directives, user_mode));
+    }
+}
+
+/// The systemd unit `service-manager` would write for the service, with the `directives` added to
+/// its `[Service]` section.
+///
+/// Providing the contents replaces the generated unit, so everything else in it must match. A
+/// user-mode unit runs as the user whose manager loads it, so it has no `User=` line, and it is
+/// wanted by that manager's `default.target`.
+fn systemd_unit_contents(
+    ctx: &ServiceInstallCtx,
+    directives: &[String],
+    user_mode: bool,
+) -> String {
+    let command = std::iter::once(ctx.program.as_os_str())
+        .chain(ctx.args.iter().map(|arg| arg.as_os_str()))
+        .map(|word| systemd_quote(&word.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut lines = vec![
+        "[Unit]".to_string(),
+        format!("Description={}", ctx.label.to_qualified_name()),
+        "[Service]".to_string(),
+    ];
+    for (key, value) in ctx.environment.iter().flatten() {
+        lines.push(format!(
+            "Environment={}",
+            systemd_quote(&format!("{key}={value}"))
+        ));
+    }
+    lines.push(format!("ExecStart={command}"));
+    lines.push("Restart=on-failure".to_string());
+    if !user_mode {
+        if let Some(username) = &ctx.username {
+            lines.push(format!("User={username}"));
+        }
+    }
+    lines.extend(directives.iter().cloned());
+    lines.push("[Install]".to_string());
+    if user_mode {
+        lines.push("WantedBy=default.target".to_string());
+    } else {
+        lines.push("WantedBy=multi-user.target".to_string());
+    }
+
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+/// Quote a word for a systemd unit, escaping the backslashes, quotes and `%` specifier characters
+/// systemd would otherwise interpret.
+fn systemd_quote(word: &str) -> String {
+    let escaped = word
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("\"{escaped}\"")
+}
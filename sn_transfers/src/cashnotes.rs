@@ -27,7 +27,10 @@ pub use nano::NanoTokens;
 pub use signed_spend::{SignedSpend, Spend};
 pub use spend_reason::SpendReason;
 pub use transaction::Transaction;
-pub use unique_keys::{DerivationIndex, DerivedSecretKey, MainPubkey, MainSecretKey, UniquePubkey};
+pub use unique_keys::{
+    sign_message, verify_message, DerivationIndex, DerivedSecretKey, MainPubkey, MainSecretKey,
+    UniquePubkey,
+};
 
 #[cfg(test)]
 pub(crate) mod tests {
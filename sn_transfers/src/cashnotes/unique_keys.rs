@@ -315,6 +315,24 @@ impl MainSecretKey {
     }
 }
 
+/// Prefixed to messages signed for ownership proofs, so such a signature can never be passed off as
+/// a signature over any other data, e.g., a spend.
+const SIGNED_MESSAGE_PREFIX: &[u8] = b"Safe Network signed message:\n";
+
+fn signed_message_bytes(msg: &[u8]) -> Vec<u8> {
+    [SIGNED_MESSAGE_PREFIX, msg].concat()
+}
+
+/// Sign a message, e.g., a challenge from a rewards dashboard, to prove control of the key.
+pub fn sign_message(key: &MainSecretKey, msg: &[u8]) -> bls::Signature {
+    key.sign(&signed_message_bytes(msg))
+}
+
+/// Verify a signature created with `sign_message`.
+pub fn verify_message(pubkey: &MainPubkey, msg: &[u8], sig: &bls::Signature) -> bool {
+    pubkey.verify(sig, &signed_message_bytes(msg))
+}
+
 /// Construct a BLS public key from a hex-encoded string.
 fn bls_public_from_hex<T: AsRef<[u8]>>(hex: T) -> Result<bls::PublicKey> {
     let bytes = hex::decode(hex).map_err(|_| Error::FailedToDecodeHexToKey)?;
@@ -330,6 +348,27 @@ fn bls_public_from_hex<T: AsRef<[u8]>>(hex: T) -> Result<bls::PublicKey> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_signed_message_verification() {
+        let key = MainSecretKey::random();
+        let msg = b"rewards dashboard challenge 42";
+        let sig = sign_message(&key, msg);
+
+        assert!(verify_message(&key.main_pubkey(), msg, &sig));
+        assert!(!verify_message(
+            &key.main_pubkey(),
+            b"rewards dashboard challenge 43",
+            &sig
+        ));
+        assert!(!verify_message(
+            &MainSecretKey::random().main_pubkey(),
+            msg,
+            &sig
+        ));
+        // the signature is not valid over the raw message
+        assert!(!key.main_pubkey().verify(&sig, msg));
+    }
+
     #[test]
     fn test_pubkeys_hex_conversion() -> eyre::Result<()> {
         let sk = bls::SecretKey::random();
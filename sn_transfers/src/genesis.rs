@@ -16,7 +16,7 @@ use crate::{
 
 use bls::SecretKey;
 use lazy_static::lazy_static;
-use std::{fmt::Debug, path::PathBuf};
+use std::{ffi::OsString, fmt::Debug, path::PathBuf};
 use thiserror::Error;
 
 /// Number of tokens in the Genesis CashNote.
@@ -253,11 +253,33 @@ pub fn create_first_cash_note_from_key(
     Ok(genesis_cash_note)
 }
 
+/// The name of the environment variable that overrides the directory wallets are kept under.
+pub const SAFE_WALLET_DIR_ENV: &str = "SAFE_WALLET_DIR";
+
+/// The directory the faucet, genesis and client wallet directories are created under.
+///
+/// If the `SAFE_WALLET_DIR` environment variable is set to a non-empty path it takes precedence,
+/// e.g. to keep wallets on a volume in a containerised deployment. Otherwise it's `safe` in the
+/// platform's data directory, e.g. `$HOME/.local/share/safe` on Linux.
+pub fn get_wallet_base_dir() -> PathBuf {
+    wallet_base_dir(std::env::var_os(SAFE_WALLET_DIR_ENV))
+}
+
+fn wallet_base_dir(env_override: Option<OsString>) -> PathBuf {
+    match env_override {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let mut data_dirs = dirs_next::data_dir().expect("A homedir to exist.");
+            data_dirs.push("safe");
+            data_dirs
+        }
+    }
+}
+
 // We need deterministic and fix path for the faucet wallet.
 // Otherwise the test instances will not be able to find the same faucet instance.
 pub fn get_faucet_data_dir() -> PathBuf {
-    let mut data_dirs = dirs_next::data_dir().expect("A homedir to exist.");
-    data_dirs.push("safe");
+    let mut data_dirs = get_wallet_base_dir();
     data_dirs.push("test_faucet");
     std::fs::create_dir_all(data_dirs.as_path())
         .expect("Faucet test path to be successfully created.");
@@ -267,8 +289,7 @@ pub fn get_faucet_data_dir() -> PathBuf {
 // We need deterministic and fix path for the genesis wallet.
 // Otherwise the test instances will not be able to find the same genesis instance.
 fn get_genesis_dir() -> PathBuf {
-    let mut data_dirs = dirs_next::data_dir().expect("A homedir to exist.");
-    data_dirs.push("safe");
+    let mut data_dirs = get_wallet_base_dir();
     data_dirs.push("test_genesis");
     std::fs::create_dir_all(data_dirs.as_path())
         .expect("Genesis test path to be successfully created.");
@@ -279,6 +300,20 @@ fn get_genesis_dir() -> PathBuf {
 mod tests {
     use super::*;
 
+    #[test]
+    fn wallet_base_dir_should_prefer_the_env_override() {
+        let default = dirs_next::data_dir()
+            .expect("A homedir to exist.")
+            .join("safe");
+
+        assert_eq!(
+            wallet_base_dir(Some(OsString::from("/mnt/wallets"))),
+            PathBuf::from("/mnt/wallets")
+        );
+        assert_eq!(wallet_base_dir(Some(OsString::new())), default);
+        assert_eq!(wallet_base_dir(None), default);
+    }
+
     #[test]
     fn generate_genesis() {
         for _ in 0..10 {
@@ -19,22 +19,24 @@ pub(crate) use cashnotes::{Input, Output, TransactionBuilder};
 
 /// Types used in the public API
 pub use cashnotes::{
-    CashNote, DerivationIndex, DerivedSecretKey, Hash, MainPubkey, MainSecretKey, NanoTokens,
-    SignedSpend, Spend, SpendAddress, SpendReason, Transaction, UniquePubkey, UnsignedTransfer,
+    sign_message, verify_message, CashNote, DerivationIndex, DerivedSecretKey, Hash, MainPubkey,
+    MainSecretKey, NanoTokens, SignedSpend, Spend, SpendAddress, SpendReason, Transaction,
+    UniquePubkey, UnsignedTransfer,
 };
 pub use error::{Result, TransferError};
 /// Utilities exposed
 pub use genesis::{
     calculate_royalties_fee, create_first_cash_note_from_key, get_faucet_data_dir, get_genesis_sk,
-    is_genesis_parent_tx, is_genesis_spend, load_genesis_wallet, Error as GenesisError,
-    GENESIS_CASHNOTE, GENESIS_CASHNOTE_PARENT_TX, GENESIS_PK, GENESIS_SPEND_UNIQUE_KEY,
-    TOTAL_SUPPLY,
+    get_wallet_base_dir, is_genesis_parent_tx, is_genesis_spend, load_genesis_wallet,
+    Error as GenesisError, GENESIS_CASHNOTE, GENESIS_CASHNOTE_PARENT_TX, GENESIS_PK,
+    GENESIS_SPEND_UNIQUE_KEY, SAFE_WALLET_DIR_ENV, TOTAL_SUPPLY,
 };
 pub use transfers::{CashNoteRedemption, OfflineTransfer, Transfer};
 pub use wallet::{
-    bls_secret_from_hex, wallet_lockfile_name, Error as WalletError, HotWallet, Payment,
-    PaymentQuote, QuotingMetrics, Result as WalletResult, WalletApi, WatchOnlyWallet,
-    QUOTE_EXPIRATION_SECS, WALLET_DIR_NAME,
+    bls_secret_from_hex, sanity_check_key, wallet_lockfile_name, Error as WalletError, HotWallet,
+    Payment, PaymentEnvelope, PaymentQuote, QuoteDiff, QuotingMetrics, Result as WalletResult,
+    WalletApi, WatchOnlyWallet, QUOTE_EXPIRATION_SECS, QUOTE_PROTOCOL_VERSION,
+    SUPPORTED_QUOTE_VERSIONS, WALLET_DIR_NAME,
 };
 
 use bls::SecretKey;
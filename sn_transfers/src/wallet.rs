@@ -62,10 +62,13 @@ mod watch_only;
 
 pub use self::{
     api::{WalletApi, WALLET_DIR_NAME},
-    data_payments::{Payment, PaymentQuote, QuotingMetrics, QUOTE_EXPIRATION_SECS},
+    data_payments::{
+        Payment, PaymentEnvelope, PaymentQuote, QuoteDiff, QuotingMetrics, QUOTE_EXPIRATION_SECS,
+        QUOTE_PROTOCOL_VERSION, SUPPORTED_QUOTE_VERSIONS,
+    },
     error::{Error, Result},
     hot_wallet::HotWallet,
-    keys::bls_secret_from_hex,
+    keys::{bls_secret_from_hex, sanity_check_key},
     wallet_file::wallet_lockfile_name,
     watch_only::WatchOnlyWallet,
 };
@@ -6,15 +6,25 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{MainPubkey, NanoTokens, Transfer};
+use super::error::{Error, Result};
+use crate::{Hash, MainPubkey, MainSecretKey, NanoTokens, Transfer};
+use bls::Ciphertext;
 use libp2p::{identity::PublicKey, PeerId};
 use serde::{Deserialize, Serialize};
-use std::time::SystemTime;
+use std::{collections::HashSet, ops::RangeInclusive, time::SystemTime};
 use xor_name::XorName;
 
 /// The time in seconds that a quote is valid for
 pub const QUOTE_EXPIRATION_SECS: u64 = 3600;
 
+/// The version of the `PaymentQuote` format created by this code.
+///
+/// Quotes created before the version was introduced deserialize as version 0.
+pub const QUOTE_PROTOCOL_VERSION: u16 = 1;
+
+/// The `PaymentQuote` format versions this code can interpret.
+pub const SUPPORTED_QUOTE_VERSIONS: RangeInclusive<u16> = 0..=QUOTE_PROTOCOL_VERSION;
+
 /// The margin allowed for live_time
 const LIVE_TIME_MARGIN: u64 = 10;
 
@@ -27,6 +37,58 @@ pub struct Payment {
     pub quote: PaymentQuote,
 }
 
+impl Payment {
+    /// Returns true if the quote being paid for was given for the provided content.
+    ///
+    /// This stops a payment for one piece of content from being reused to store another.
+    pub fn is_for(&self, content: &XorName) -> bool {
+        self.quote.content == *content
+    }
+}
+
+/// A `Payment` as it travels from the client to the node it pays.
+///
+/// The encrypted form hides the payee and the transfers from passive observers. The plain form is
+/// kept so that nodes and clients which don't encrypt yet can still interoperate.
+#[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum PaymentEnvelope {
+    Plain(Payment),
+    Encrypted(Ciphertext),
+}
+
+impl PaymentEnvelope {
+    /// Wrap a payment for sending, encrypting it to the receiving node's key if one is provided.
+    pub fn seal(payment: Payment, recipient: Option<&MainPubkey>) -> Result<Self> {
+        match recipient {
+            Some(recipient) => {
+                let bytes = rmp_serde::to_vec(&payment)?;
+                Ok(Self::Encrypted(recipient.0.encrypt(bytes)))
+            }
+            None => Ok(Self::Plain(payment)),
+        }
+    }
+
+    /// Whether the payment is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, Self::Encrypted(_))
+    }
+
+    /// Get the payment out of the envelope, decrypting it with our key if needed.
+    pub fn open(self, sk: &MainSecretKey) -> Result<Payment> {
+        match self {
+            Self::Plain(payment) => Ok(payment),
+            Self::Encrypted(cypher) => {
+                let bytes = sk
+                    .secret_key()
+                    .decrypt(&cypher)
+                    .ok_or(Error::FailedToDecypherPayment)?;
+                // decrypting with the wrong key yields garbage rather than an error
+                rmp_serde::from_slice(&bytes).map_err(|_| Error::FailedToDecypherPayment)
+            }
+        }
+    }
+}
+
 /// Information relating to a data payment for one address
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PaymentDetails {
@@ -52,6 +114,11 @@ impl PaymentDetails {
             quote: self.quote.clone(),
         }
     }
+
+    /// create a PaymentEnvelope for a PaymentDetails, encrypted to the node we pay
+    pub fn to_payment_envelope(&self) -> Result<PaymentEnvelope> {
+        PaymentEnvelope::seal(self.to_payment(), Some(&self.recipient))
+    }
 }
 
 /// A generic type for signatures
@@ -91,6 +158,19 @@ impl Default for QuotingMetrics {
     }
 }
 
+/// How a quote differs from a previous quote for the same content, to explain a price change.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteDiff {
+    /// The change in cost in nanos, positive when the price went up.
+    pub amount_delta: i128,
+    /// Whether the quote was given by a different node.
+    pub payee_changed: bool,
+    /// The time between the two quotes in seconds, negative if the previous quote is the newer.
+    pub timestamp_delta_secs: i64,
+    /// Whether the previous quote had expired, which would have required a new quote anyway.
+    pub previous_expired: bool,
+}
+
 /// A payment quote to store data given by a node to a client
 /// Note that the PaymentQuote is a contract between the node and itself to make sure the clients aren’t mispaying.
 /// It is NOT a contract between the client and the node.
@@ -111,6 +191,11 @@ pub struct PaymentQuote {
     pub pub_key: Vec<u8>,
     #[debug(skip)]
     pub signature: QuoteSignature,
+    /// the version of the quote format
+    ///
+    /// This must remain the last field, so quotes serialized before it was added still deserialize.
+    #[serde(default)]
+    pub version: u16,
 }
 
 impl PaymentQuote {
@@ -123,15 +208,20 @@ impl PaymentQuote {
             quoting_metrics: Default::default(),
             pub_key: vec![],
             signature: vec![],
+            version: QUOTE_PROTOCOL_VERSION,
         }
     }
 
     /// returns the bytes to be signed
+    ///
+    /// The version is only signed from version 1 on, so quotes signed by nodes that predate it
+    /// still verify.
     pub fn bytes_for_signing(
         xorname: XorName,
         cost: NanoTokens,
         timestamp: SystemTime,
         quoting_metrics: &QuotingMetrics,
+        version: u16,
     ) -> Vec<u8> {
         let mut bytes = xorname.to_vec();
         bytes.extend_from_slice(&cost.to_bytes());
@@ -147,9 +237,43 @@ impl PaymentQuote {
             Err(_err) => vec![],
         };
         bytes.extend_from_slice(&serialised_quoting_metrics);
+        if version > 0 {
+            bytes.extend_from_slice(&version.to_le_bytes());
+        }
         bytes
     }
 
+    /// Returns a hash of the quoted content and the quoting node's public key.
+    ///
+    /// The signature is left out, so re-signing the same content yields the same hash, which
+    /// makes it usable as a cache key for quotes.
+    pub fn content_hash(&self) -> [u8; 32] {
+        let mut bytes = Self::bytes_for_signing(
+            self.content,
+            self.cost,
+            self.timestamp,
+            &self.quoting_metrics,
+            self.version,
+        );
+        bytes.extend_from_slice(&self.pub_key);
+        *Hash::hash(&bytes).slice()
+    }
+
+    /// Check the quote's format version is one this node can interpret.
+    ///
+    /// Quotes outside the `supported` range are rejected rather than risking their fields being
+    /// misread.
+    pub fn validate_version(&self, supported: RangeInclusive<u16>) -> Result<()> {
+        if supported.contains(&self.version) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedQuoteVersion {
+                version: self.version,
+                supported,
+            })
+        }
+    }
+
     /// Check self is signed by the claimed peer
     pub fn check_is_signed_by_claimed_peer(&self, claimed_peer: PeerId) -> bool {
         let pub_key = if let Ok(pub_key) = PublicKey::try_decode_protobuf(&self.pub_key) {
@@ -171,6 +295,7 @@ impl PaymentQuote {
             self.cost,
             self.timestamp,
             &self.quoting_metrics,
+            self.version,
         );
 
         if !pub_key.verify(&bytes, &self.signature) {
@@ -201,6 +326,74 @@ impl PaymentQuote {
             quoting_metrics: Default::default(),
             pub_key: vec![],
             signature: vec![],
+            version: QUOTE_PROTOCOL_VERSION,
+        }
+    }
+
+    /// Sort the quotes by cost, cheapest first.
+    ///
+    /// The sort is stable, so quotes with the same cost keep their relative order.
+    pub fn sort_by_amount(quotes: &mut [PaymentQuote]) {
+        quotes.sort_by_key(|quote| quote.cost);
+    }
+
+    /// Sort the quotes by cost, most expensive first.
+    ///
+    /// The sort is stable, so quotes with the same cost keep their relative order.
+    pub fn sort_by_amount_descending(quotes: &mut [PaymentQuote]) {
+        quotes.sort_by_key(|quote| std::cmp::Reverse(quote.cost));
+    }
+
+    /// The minimum balance needed to pay all of the `quotes`, plus the estimated `fee_estimate`.
+    ///
+    /// Errors with `TotalPriceTooHigh` if the total doesn't fit in a token amount.
+    pub fn required_balance(
+        quotes: &[PaymentQuote],
+        fee_estimate: NanoTokens,
+    ) -> Result<NanoTokens> {
+        quotes
+            .iter()
+            .try_fold(fee_estimate, |total, quote| total.checked_add(quote.cost))
+            .ok_or(Error::TotalPriceTooHigh)
+    }
+
+    /// The peer that gave the quote, if its public key can be decoded.
+    pub fn peer_id(&self) -> Option<PeerId> {
+        PublicKey::try_decode_protobuf(&self.pub_key)
+            .ok()
+            .map(PeerId::from)
+    }
+
+    /// Pick the cheapest quote whose payee has not been tried yet.
+    ///
+    /// When the chosen payee turns out to be unreachable, it can be added to `tried` and an
+    /// alternate payee from the same set of quotes picked, rather than failing the payment.
+    pub fn next_payee<'a>(
+        quotes: &'a [PaymentQuote],
+        tried: &HashSet<PeerId>,
+    ) -> Option<&'a PaymentQuote> {
+        quotes
+            .iter()
+            .filter(|quote| {
+                quote
+                    .peer_id()
+                    .is_some_and(|peer_id| !tried.contains(&peer_id))
+            })
+            .min_by_key(|quote| quote.cost)
+    }
+
+    /// Describe how this quote differs from a `previous` quote.
+    pub fn explain_diff(&self, previous: &Self) -> QuoteDiff {
+        let timestamp_delta_secs = match self.timestamp.duration_since(previous.timestamp) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        };
+
+        QuoteDiff {
+            amount_delta: i128::from(self.cost.as_nano()) - i128::from(previous.cost.as_nano()),
+            payee_changed: self.pub_key != previous.pub_key,
+            timestamp_delta_secs,
+            previous_expired: previous.has_expired(),
         }
     }
 
@@ -275,6 +468,173 @@ mod tests {
     use libp2p::identity::Keypair;
     use std::{thread::sleep, time::Duration};
 
+    #[test]
+    fn test_explain_diff() {
+        let mut previous = PaymentQuote::test_dummy(XorName::default(), NanoTokens::from(100));
+        previous.pub_key = Keypair::generate_ed25519().public().encode_protobuf();
+        previous.timestamp = SystemTime::now() - Duration::from_secs(60);
+
+        let mut current = PaymentQuote::test_dummy(XorName::default(), NanoTokens::from(150));
+        current.pub_key = Keypair::generate_ed25519().public().encode_protobuf();
+        current.timestamp = previous.timestamp + Duration::from_secs(60);
+
+        let diff = current.explain_diff(&previous);
+        assert_eq!(
+            diff,
+            QuoteDiff {
+                amount_delta: 50,
+                payee_changed: true,
+                timestamp_delta_secs: 60,
+                previous_expired: false,
+            }
+        );
+
+        let reverse_diff = previous.explain_diff(&current);
+        assert_eq!(reverse_diff.amount_delta, -50);
+        assert_eq!(reverse_diff.timestamp_delta_secs, -60);
+    }
+
+    #[test]
+    fn test_next_payee() {
+        let quotes = [30, 10, 20]
+            .into_iter()
+            .map(|cost| {
+                let mut quote =
+                    PaymentQuote::test_dummy(XorName::default(), NanoTokens::from(cost));
+                quote.pub_key = Keypair::generate_ed25519().public().encode_protobuf();
+                quote
+            })
+            .collect::<Vec<_>>();
+
+        let mut tried = HashSet::new();
+        let mut paid_costs = vec![];
+        while let Some(quote) = PaymentQuote::next_payee(&quotes, &tried) {
+            paid_costs.push(quote.cost.as_nano());
+            let _ = tried.insert(quote.peer_id().expect("the quote to have a valid pub_key"));
+        }
+
+        assert_eq!(paid_costs, vec![10, 20, 30]);
+        assert!(PaymentQuote::next_payee(&[PaymentQuote::zero()], &HashSet::new()).is_none());
+    }
+
+    #[test]
+    fn test_required_balance() -> Result<()> {
+        let quotes = [10, 20, 30]
+            .into_iter()
+            .map(|cost| PaymentQuote::test_dummy(XorName::default(), NanoTokens::from(cost)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            PaymentQuote::required_balance(&quotes, NanoTokens::from(5))?,
+            NanoTokens::from(65)
+        );
+        assert_eq!(
+            PaymentQuote::required_balance(&[], NanoTokens::from(5))?,
+            NanoTokens::from(5)
+        );
+
+        let too_expensive = [PaymentQuote::test_dummy(
+            XorName::default(),
+            NanoTokens::from(u64::MAX),
+        )];
+        assert!(matches!(
+            PaymentQuote::required_balance(&too_expensive, NanoTokens::from(1)),
+            Err(Error::TotalPriceTooHigh)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_version() {
+        let mut quote = PaymentQuote::zero();
+        assert_eq!(quote.version, QUOTE_PROTOCOL_VERSION);
+        assert!(quote.validate_version(0..=QUOTE_PROTOCOL_VERSION).is_ok());
+
+        quote.version = QUOTE_PROTOCOL_VERSION + 1;
+        assert!(matches!(
+            quote.validate_version(0..=QUOTE_PROTOCOL_VERSION),
+            Err(Error::UnsupportedQuoteVersion { version, .. }) if version == QUOTE_PROTOCOL_VERSION + 1
+        ));
+
+        quote.version = 0;
+        assert!(quote.validate_version(1..=QUOTE_PROTOCOL_VERSION).is_err());
+        assert!(quote.validate_version(SUPPORTED_QUOTE_VERSIONS).is_ok());
+    }
+
+    #[test]
+    fn test_version_is_covered_by_the_signature() {
+        let keypair = Keypair::generate_ed25519();
+        let peer_id = keypair.public().to_peer_id();
+
+        let mut quote = PaymentQuote::zero();
+        quote.pub_key = keypair.public().encode_protobuf();
+        let bytes = PaymentQuote::bytes_for_signing(
+            quote.content,
+            quote.cost,
+            quote.timestamp,
+            &quote.quoting_metrics,
+            quote.version,
+        );
+        quote.signature = keypair.sign(&bytes).expect("the quote to be signed");
+        assert!(quote.check_is_signed_by_claimed_peer(peer_id));
+
+        quote.version = 0;
+        assert!(!quote.check_is_signed_by_claimed_peer(peer_id));
+    }
+
+    #[test]
+    fn test_unversioned_quote_signature_still_verifies() {
+        let keypair = Keypair::generate_ed25519();
+        let peer_id = keypair.public().to_peer_id();
+
+        let mut quote = PaymentQuote::zero();
+        quote.version = 0;
+        quote.pub_key = keypair.public().encode_protobuf();
+        let mut bytes = quote.content.to_vec();
+        bytes.extend_from_slice(&quote.cost.to_bytes());
+        bytes.extend_from_slice(
+            &quote
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("Unix epoch to be in the past")
+                .as_secs()
+                .to_le_bytes(),
+        );
+        bytes.extend_from_slice(
+            &rmp_serde::to_vec(&quote.quoting_metrics).expect("the metrics to serialize"),
+        );
+        quote.signature = keypair.sign(&bytes).expect("the quote to be signed");
+
+        assert!(quote.check_is_signed_by_claimed_peer(peer_id));
+    }
+
+    #[test]
+    fn test_quote_without_version_deserializes_as_version_zero() {
+        #[derive(Serialize)]
+        struct UnversionedQuote {
+            content: XorName,
+            cost: NanoTokens,
+            timestamp: SystemTime,
+            quoting_metrics: QuotingMetrics,
+            pub_key: Vec<u8>,
+            signature: QuoteSignature,
+        }
+
+        let unversioned = UnversionedQuote {
+            content: XorName::default(),
+            cost: NanoTokens::from(10),
+            timestamp: SystemTime::now(),
+            quoting_metrics: Default::default(),
+            pub_key: vec![],
+            signature: vec![],
+        };
+        let bytes = rmp_serde::to_vec(&unversioned).expect("the quote to serialize");
+        let quote: PaymentQuote = rmp_serde::from_slice(&bytes).expect("the quote to deserialize");
+
+        assert_eq!(quote.cost, NanoTokens::from(10));
+        assert_eq!(quote.version, 0);
+    }
+
     #[test]
     fn test_is_newer_than() {
         let old_quote = PaymentQuote::zero();
@@ -297,6 +657,7 @@ mod tests {
             quote.cost,
             quote.timestamp,
             &quote.quoting_metrics,
+            quote.version,
         );
         let signature = if let Ok(sig) = keypair.sign(&bytes) {
             sig
@@ -325,6 +686,130 @@ mod tests {
         assert!(!quote.check_is_signed_by_claimed_peer(false_peer));
     }
 
+    #[test]
+    fn test_content_hash() {
+        let keypair = Keypair::generate_ed25519();
+        let mut quote = PaymentQuote::zero();
+        quote.pub_key = keypair.public().encode_protobuf();
+
+        // Signing doesn't change the content hash
+        let mut signed_quote = quote.clone();
+        let bytes = PaymentQuote::bytes_for_signing(
+            quote.content,
+            quote.cost,
+            quote.timestamp,
+            &quote.quoting_metrics,
+            quote.version,
+        );
+        signed_quote.signature = if let Ok(sig) = keypair.sign(&bytes) {
+            sig
+        } else {
+            panic!("Cannot sign the quote!");
+        };
+        assert_eq!(quote.content_hash(), signed_quote.content_hash());
+
+        // Changing the content does
+        let mut changed_quote = quote.clone();
+        changed_quote.cost = NanoTokens::from(1);
+        assert_ne!(quote.content_hash(), changed_quote.content_hash());
+
+        let mut changed_quote = quote.clone();
+        changed_quote.content = XorName::random(&mut rand::thread_rng());
+        assert_ne!(quote.content_hash(), changed_quote.content_hash());
+
+        let mut changed_quote = quote.clone();
+        changed_quote.quoting_metrics.close_records_stored = 1;
+        assert_ne!(quote.content_hash(), changed_quote.content_hash());
+
+        let mut changed_quote = quote.clone();
+        changed_quote.version += 1;
+        assert_ne!(quote.content_hash(), changed_quote.content_hash());
+    }
+
+    #[test]
+    fn test_sort_by_amount() {
+        let quotes: Vec<_> = [3, 1, 2, 1]
+            .into_iter()
+            .map(|cost| {
+                PaymentQuote::test_dummy(
+                    XorName::random(&mut rand::thread_rng()),
+                    NanoTokens::from(cost),
+                )
+            })
+            .collect();
+
+        let mut ascending = quotes.clone();
+        PaymentQuote::sort_by_amount(&mut ascending);
+        assert_eq!(
+            ascending,
+            vec![
+                quotes[1].clone(),
+                quotes[3].clone(),
+                quotes[2].clone(),
+                quotes[0].clone()
+            ]
+        );
+
+        let mut descending = quotes.clone();
+        PaymentQuote::sort_by_amount_descending(&mut descending);
+        assert_eq!(
+            descending,
+            vec![
+                quotes[0].clone(),
+                quotes[2].clone(),
+                quotes[1].clone(),
+                quotes[3].clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_payment_is_for() {
+        let content = XorName::random(&mut rand::thread_rng());
+        let other_content = XorName::random(&mut rand::thread_rng());
+        let payment = Payment {
+            transfers: vec![],
+            quote: PaymentQuote::test_dummy(content, NanoTokens::from(1)),
+        };
+
+        assert!(payment.is_for(&content));
+        assert!(!payment.is_for(&other_content));
+    }
+
+    #[test]
+    fn test_payment_envelope_round_trip() -> Result<()> {
+        let sk = MainSecretKey::random();
+        let payment = Payment {
+            transfers: vec![],
+            quote: PaymentQuote::test_dummy(XorName::random(&mut rand::thread_rng()), 1.into()),
+        };
+
+        let envelope = PaymentEnvelope::seal(payment.clone(), Some(&sk.main_pubkey()))?;
+        assert!(envelope.is_encrypted());
+        assert_eq!(envelope.open(&sk)?, payment);
+
+        let envelope = PaymentEnvelope::seal(payment.clone(), None)?;
+        assert!(!envelope.is_encrypted());
+        assert_eq!(envelope.open(&sk)?, payment);
+        Ok(())
+    }
+
+    #[test]
+    fn test_payment_envelope_wrong_key_fails() -> Result<()> {
+        let sk = MainSecretKey::random();
+        let payment = Payment {
+            transfers: vec![],
+            quote: PaymentQuote::test_dummy(XorName::random(&mut rand::thread_rng()), 1.into()),
+        };
+
+        let envelope = PaymentEnvelope::seal(payment, Some(&sk.main_pubkey()))?;
+        assert!(matches!(
+            envelope.open(&MainSecretKey::random()),
+            Err(Error::FailedToDecypherPayment)
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_historical_verify() {
         let mut old_quote = PaymentQuote::zero();
@@ -64,6 +64,9 @@ pub enum Error {
     /// Failed to decode a hex string to a key
     #[error("Could not decode hex string to key")]
     FailedToDecodeHexToKey,
+    /// A key could not sign a message that verifies with its public key
+    #[error("The key failed its sanity check, it could not sign a message that verifies with its public key")]
+    KeySanityCheckFailed,
     /// Failed to serialize a main key to hex
     #[error("Could not serialize main key to hex: {0}")]
     FailedToHexEncodeKey(String),
@@ -73,12 +76,27 @@ pub enum Error {
     /// Failed to decypher transfer with our key, maybe it was encrypted to another key
     #[error("Failed to decypher transfer with our key, maybe it was not for us")]
     FailedToDecypherTransfer,
+    /// Failed to decypher a payment with our key, maybe it was encrypted to another key
+    #[error("Failed to decypher payment with our key, maybe it was not for us")]
+    FailedToDecypherPayment,
     /// No cached payment found for address
     #[error("No ongoing payment found for address {0:?}")]
     NoPaymentForAddress(XorName),
     /// The payment Quote has expired.
     #[error("The payment quote made for {0:?} has expired")]
     QuoteExpired(XorName),
+    /// The payment Quote uses a format version that can't be interpreted.
+    #[error(
+        "The payment quote uses version {version}, but only versions {supported:?} are supported"
+    )]
+    UnsupportedQuoteVersion {
+        version: u16,
+        supported: std::ops::RangeInclusive<u16>,
+    },
+
+    /// A wallet operation did not complete in time, e.g., because the disk is unresponsive
+    #[error("The wallet operation did not complete within {0:?}")]
+    Timeout(std::time::Duration),
 
     /// DAG error
     #[error("DAG error: {0}")]
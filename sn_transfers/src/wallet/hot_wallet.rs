@@ -8,7 +8,7 @@
 
 use super::{
     api::{WalletApi, WALLET_DIR_NAME},
-    data_payments::{PaymentDetails, PaymentQuote},
+    data_payments::{Payment, PaymentDetails, PaymentEnvelope, PaymentQuote},
     keys::{get_main_key_from_disk, store_new_keypair},
     wallet_file::{
         get_unconfirmed_spend_requests, load_created_cash_note, remove_cash_notes,
@@ -30,7 +30,7 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
     fs::File,
     path::{Path, PathBuf},
-    time::Instant,
+    time::{Duration, Instant},
 };
 use xor_name::XorName;
 
@@ -176,6 +176,19 @@ impl HotWallet {
         Self::load_from_path_and_key(&wallet_dir, None)
     }
 
+    /// Like `load_from`, but fails with `Error::Timeout` if loading takes longer than `timeout`.
+    pub fn load_from_with_timeout(root_dir: &Path, timeout: Duration) -> Result<Self> {
+        let root_dir = root_dir.to_path_buf();
+        run_with_timeout(timeout, move || Self::load_from(&root_dir))
+    }
+
+    /// Like `try_load_from`, but fails with `Error::Timeout` if loading takes longer than
+    /// `timeout`.
+    pub fn try_load_from_with_timeout(root_dir: &Path, timeout: Duration) -> Result<Self> {
+        let root_dir = root_dir.to_path_buf();
+        run_with_timeout(timeout, move || Self::try_load_from(&root_dir))
+    }
+
     /// Loads a serialized wallet from a given path, no additional element will
     /// be added to the provided path and strictly taken as the wallet files location.
     pub fn load_from_path(wallet_dir: &Path, main_key: Option<MainSecretKey>) -> Result<Self> {
@@ -249,6 +262,20 @@ impl HotWallet {
         self.watchonly_wallet.balance()
     }
 
+    /// How much more balance is needed to pay all of the `quotes` plus the estimated `fee_estimate`.
+    ///
+    /// Returns `None` when the wallet already holds enough.
+    pub fn balance_shortfall(
+        &self,
+        quotes: &[PaymentQuote],
+        fee_estimate: NanoTokens,
+    ) -> Result<Option<NanoTokens>> {
+        let required = PaymentQuote::required_balance(quotes, fee_estimate)?;
+        Ok(required
+            .checked_sub(self.balance())
+            .filter(|shortfall| !shortfall.is_zero()))
+    }
+
     pub fn sign(
         &self,
         spends: impl IntoIterator<Item = (Spend, DerivationIndex)>,
@@ -636,6 +663,11 @@ impl HotWallet {
             .map_err(|_| Error::FailedToDecypherTransfer)
     }
 
+    /// Get a payment made to us out of its envelope, decrypting it with our key if needed.
+    pub fn open_payment(&self, envelope: PaymentEnvelope) -> Result<Payment> {
+        envelope.open(&self.key)
+    }
+
     pub fn derive_key(&self, derivation_index: &DerivationIndex) -> DerivedSecretKey {
         self.key.derive_key(derivation_index)
     }
@@ -681,6 +713,24 @@ impl HotWallet {
     }
 }
 
+/// Run a blocking wallet operation on its own thread, giving up on it after `timeout`.
+///
+/// The operation can't be cancelled, so a hung operation keeps its thread until it returns.
+fn run_with_timeout<T, F>(timeout: Duration, op: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let _handle = std::thread::spawn(move || {
+        // the receiver is gone if we've timed out, in which case the result is dropped
+        let _ = sender.send(op());
+    });
+    receiver
+        .recv_timeout(timeout)
+        .map_err(|_| Error::Timeout(timeout))?
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -692,12 +742,40 @@ mod tests {
             data_payments::PaymentQuote, hot_wallet::WALLET_DIR_NAME, wallet_file::store_wallet,
             watch_only::WatchOnlyWallet, KeyLessWallet,
         },
-        MainSecretKey, NanoTokens, SpendAddress,
+        MainSecretKey, NanoTokens, SpendAddress, WalletError,
     };
     use assert_fs::TempDir;
     use eyre::Result;
+    use std::time::Duration;
     use xor_name::XorName;
 
+    #[test]
+    fn run_with_timeout_should_fail_when_the_operation_is_too_slow() -> Result<()> {
+        let slow = super::run_with_timeout(Duration::from_millis(50), || {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(())
+        });
+        assert!(
+            matches!(slow, Err(WalletError::Timeout(timeout)) if timeout == Duration::from_millis(50))
+        );
+
+        let fast = super::run_with_timeout(Duration::from_secs(2), || Ok(42))?;
+        assert_eq!(fast, 42);
+        Ok(())
+    }
+
+    #[test]
+    fn load_from_with_timeout_should_load_a_wallet_in_time() -> Result<()> {
+        let dir = create_temp_dir();
+        let key = MainSecretKey::random();
+        let address = key.main_pubkey();
+        let _ = HotWallet::create_from_key(dir.path(), key)?;
+
+        let wallet = HotWallet::load_from_with_timeout(dir.path(), Duration::from_secs(10))?;
+        assert_eq!(wallet.address(), address);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn keyless_wallet_to_and_from_file() -> Result<()> {
         let key = MainSecretKey::random();
@@ -919,6 +997,30 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn balance_shortfall_reports_the_missing_amount() -> Result<()> {
+        let dir = create_temp_dir();
+        let mut wallet = HotWallet::create_from_key(dir.path(), MainSecretKey::random())?;
+        let cash_note =
+            create_first_cash_note_from_key(&wallet.key).expect("Genesis creation to succeed.");
+        wallet.deposit_and_store_to_disk(&vec![cash_note])?;
+
+        let mut rng = bls::rand::thread_rng();
+        let quotes = vec![
+            PaymentQuote::test_dummy(XorName::random(&mut rng), 100.into()),
+            PaymentQuote::test_dummy(XorName::random(&mut rng), 200.into()),
+        ];
+        assert_eq!(wallet.balance_shortfall(&quotes, 10.into())?, None);
+
+        let fee_estimate = NanoTokens::from(GENESIS_CASHNOTE_AMOUNT);
+        assert_eq!(
+            wallet.balance_shortfall(&quotes, fee_estimate)?,
+            Some(NanoTokens::from(300))
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn send_wallet_to_and_from_file() -> Result<()> {
         let dir = create_temp_dir();
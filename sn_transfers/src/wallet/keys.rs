@@ -15,6 +15,8 @@ use std::path::Path;
 const MAIN_SECRET_KEY_FILENAME: &str = "main_secret_key";
 /// Filename for storing the node's reward (BLS hex-encoded) public key.
 const MAIN_PUBKEY_FILENAME: &str = "main_pubkey";
+/// The message signed to check a key.
+const SANITY_CHECK_MSG: &[u8] = b"sn_transfers main secret key sanity check";
 
 /// Writes the public address and main key (hex-encoded) to different locations at disk.
 pub(crate) fn store_new_keypair(wallet_dir: &Path, main_key: &MainSecretKey) -> Result<()> {
@@ -71,9 +73,31 @@ pub fn bls_secret_from_hex<T: AsRef<[u8]>>(hex: T) -> Result<bls::SecretKey> {
     Ok(sk)
 }
 
+/// Check that a key, e.g. one just restored from a mnemonic, hex or a keystore, can sign a
+/// message that verifies with its own public key.
+///
+/// A key whose bytes were corrupted on import would derive a different public key from the one
+/// the wallet was created with. Checking a key against an expected public key this way fails with
+/// `Error::KeySanityCheckFailed`.
+pub fn sanity_check_key(key: &MainSecretKey) -> Result<()> {
+    check_key_signs_for(key, &key.main_pubkey())
+}
+
+fn check_key_signs_for(key: &MainSecretKey, main_pubkey: &MainPubkey) -> Result<()> {
+    let signature = key.sign(SANITY_CHECK_MSG);
+    if main_pubkey.verify(&signature, SANITY_CHECK_MSG) {
+        Ok(())
+    } else {
+        Err(Error::KeySanityCheckFailed)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{get_main_key_from_disk, store_new_keypair, MainSecretKey};
+    use super::{
+        check_key_signs_for, get_main_key_from_disk, sanity_check_key, store_new_keypair, Error,
+        MainSecretKey,
+    };
     use assert_fs::TempDir;
     use eyre::Result;
 
@@ -88,6 +112,18 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn sanity_check_key_should_pass_for_a_valid_key_and_fail_for_another_pubkey() {
+        let main_key = MainSecretKey::random();
+        assert!(sanity_check_key(&main_key).is_ok());
+
+        let other_pubkey = MainSecretKey::random().main_pubkey();
+        assert!(matches!(
+            check_key_signs_for(&main_key, &other_pubkey),
+            Err(Error::KeySanityCheckFailed)
+        ));
+    }
+
     fn create_temp_dir() -> TempDir {
         TempDir::new().expect("Should be able to create a temp dir.")
     }